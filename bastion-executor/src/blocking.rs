@@ -57,7 +57,7 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use std::{env, thread};
 
-use crossbeam_channel::{bounded, Receiver, Sender};
+use crossbeam_channel::{bounded, select, unbounded, Receiver, Sender};
 use lazy_static::lazy_static;
 
 use crate::{load_balancer, placement, utils};
@@ -99,6 +99,12 @@ static MAX_THREADS: AtomicU64 = AtomicU64::new(10_000);
 struct Pool {
     sender: Sender<LightProc>,
     receiver: Receiver<LightProc>,
+    /// Queue of the tasks which were scheduled without waiting
+    /// for a thread to be available (see [spawn_blocking_queued]).
+    ///
+    /// [spawn_blocking_queued]: fn.spawn_blocking_queued.html
+    queue_sender: Sender<LightProc>,
+    queue_receiver: Receiver<LightProc>,
 }
 
 lazy_static! {
@@ -110,7 +116,7 @@ lazy_static! {
                 .spawn(|| {
                     self::affinity_pinner();
 
-                    for task in &POOL.receiver {
+                    while let Some(task) = recv_task(None) {
                         task.run();
                     }
                 })
@@ -138,7 +144,13 @@ lazy_static! {
         // latency snappy in the overall async system by
         // reducing bufferbloat.
         let (sender, receiver) = bounded(0);
-        Pool { sender, receiver }
+        let (queue_sender, queue_receiver) = unbounded();
+        Pool {
+            sender,
+            receiver,
+            queue_sender,
+            queue_receiver,
+        }
     };
 
     static ref ROUND_ROBIN_PIN: Mutex<CoreId> = Mutex::new(CoreId { id: 0 });
@@ -276,7 +288,7 @@ fn create_blocking_thread() {
 
             // Adjust the pool size counter before and after spawn
             *POOL_SIZE.lock().unwrap() += 1;
-            while let Ok(task) = POOL.receiver.recv_timeout(wait_limit) {
+            while let Some(task) = recv_task(Some(wait_limit)) {
                 task.run();
             }
             *POOL_SIZE.lock().unwrap() -= 1;
@@ -318,6 +330,35 @@ fn schedule(t: LightProc) {
     }
 }
 
+/// Enqueues work without ever blocking: if no thread is ready to
+/// accept it, it is queued until one is and a new thread is
+/// spawned to run it.
+fn schedule_queued(t: LightProc) {
+    // Add up for every incoming scheduled task
+    FREQUENCY.fetch_add(1, Ordering::Acquire);
+
+    if let Err(err) = POOL.sender.try_send(t) {
+        POOL.queue_sender.send(err.into_inner()).unwrap();
+        create_blocking_thread();
+    }
+}
+
+/// Waits for a task sent to the pool (either directly or through
+/// its queue), until the given timeout elapsed if there is one.
+fn recv_task(timeout: Option<Duration>) -> Option<LightProc> {
+    match timeout {
+        Some(timeout) => select! {
+            recv(POOL.receiver) -> task => task.ok(),
+            recv(POOL.queue_receiver) -> task => task.ok(),
+            default(timeout) => None,
+        },
+        None => select! {
+            recv(POOL.receiver) -> task => task.ok(),
+            recv(POOL.queue_receiver) -> task => task.ok(),
+        },
+    }
+}
+
 /// Spawns a blocking task.
 ///
 /// The task will be spawned onto a thread pool specifically dedicated to blocking tasks.
@@ -331,6 +372,26 @@ where
     handle
 }
 
+/// Spawns a blocking task which is scheduled without blocking.
+///
+/// Unlike a task spawned using [spawn_blocking], whose scheduling
+/// waits for a thread of the pool to be available, this task is
+/// queued when every thread is busy (and a new thread is spawned
+/// to run it). This makes it possible to wake it up from an
+/// asynchronous thread (e.g. when it waits for messages) without
+/// stalling it.
+///
+/// [spawn_blocking]: fn.spawn_blocking.html
+pub fn spawn_blocking_queued<F, R>(future: F, stack: ProcStack) -> RecoverableHandle<R>
+where
+    F: Future<Output = R> + Send + 'static,
+    R: Send + 'static,
+{
+    let (task, handle) = LightProc::recoverable(future, schedule_queued, stack);
+    task.schedule();
+    handle
+}
+
 ///
 /// Low watermark value, defines the bare minimum of the pool.
 /// Spawns initial thread set.
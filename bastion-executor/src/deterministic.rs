@@ -0,0 +1,333 @@
+//!
+//! Deterministic single-threaded scheduler
+//!
+//! A [Scheduler] runs all the processes spawned onto it on a single
+//! thread, picking the next process to run among the ready ones
+//! using a pseudo-random generator initialized with a seed, so that
+//! processes are always run in the same order for a given seed.
+//!
+//! The timers of the processes running on a scheduler (see
+//! [timer::sleep]) use its virtual time, which only advances (to
+//! the next timer's deadline) once no process is ready to run.
+//!
+//! Once [enable]d, the global scheduler replaces the thread pool
+//! for every process spawned using [pool::spawn]. Processes
+//! spawned onto the blocking thread pool (see [blocking]) still run
+//! on its threads, and their timers still use the timer thread and
+//! real time. Note that the order in which processes run can still
+//! depend on what happens outside of the scheduler (e.g. on other
+//! threads sending messages to them).
+//!
+//! [Scheduler]: struct.Scheduler.html
+//! [blocking]: ../blocking/index.html
+//! [timer::sleep]: ../timer/fn.sleep.html
+//! [enable]: fn.enable.html
+//! [pool::spawn]: ../pool/fn.spawn.html
+use crate::timer::Timers;
+use crate::worker;
+use lazy_static::lazy_static;
+use lightproc::prelude::*;
+use std::cell::RefCell;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+// Whether processes spawned using `pool::spawn` run on the thread
+// pool or on the global deterministic scheduler, which can only be
+// decided once.
+const UNDECIDED: u8 = 0;
+const POOL: u8 = 1;
+const DETERMINISTIC: u8 = 2;
+
+static MODE: AtomicU8 = AtomicU8::new(UNDECIDED);
+
+lazy_static! {
+    static ref GLOBAL: Mutex<Option<Scheduler>> = Mutex::new(None);
+}
+
+thread_local! {
+    // The scheduler running processes on the current thread.
+    static CURRENT: RefCell<Option<Scheduler>> = const { RefCell::new(None) };
+}
+
+///
+/// Makes every process spawned using [pool::spawn] run on a global
+/// deterministic scheduler using the given seed, on a dedicated
+/// thread, instead of on the thread pool.
+///
+/// Because this changes how every process is run, it is meant
+/// to be called once at the start of a program or test (e.g.
+/// before initializing Bastion, which spawns processes).
+///
+/// This method returns `Ok(())` if it succeeded (or if the global
+/// scheduler was already enabled with the same seed), or `Err(())`
+/// if it was enabled with another seed or if a process was already
+/// spawned onto the thread pool.
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::deterministic;
+///
+/// deterministic::enable(42).expect("The thread pool was already used.");
+/// assert_eq!(deterministic::seed(), Some(42));
+/// ```
+///
+/// [pool::spawn]: ../pool/fn.spawn.html
+pub fn enable(seed: u64) -> Result<(), ()> {
+    let mut global = GLOBAL.lock().unwrap();
+    if let Some(scheduler) = &*global {
+        return if scheduler.seed() == seed {
+            Ok(())
+        } else {
+            Err(())
+        };
+    }
+
+    if MODE
+        .compare_exchange(UNDECIDED, DETERMINISTIC, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return Err(());
+    }
+
+    let scheduler = Scheduler::new(seed);
+    let running = scheduler.clone();
+    let spawned = thread::Builder::new()
+        .name("bastion-deterministic-thread".to_string())
+        .spawn(move || running.run_forever());
+    if spawned.is_err() {
+        // Nothing can run on the scheduler, so it is still
+        // undecided.
+        MODE.store(UNDECIDED, Ordering::SeqCst);
+        return Err(());
+    }
+
+    *global = Some(scheduler);
+    Ok(())
+}
+
+///
+/// Returns the seed of the global deterministic scheduler, if it
+/// was enabled.
+pub fn seed() -> Option<u64> {
+    enabled().map(|scheduler| scheduler.seed())
+}
+
+// Returns the global scheduler if it was enabled, deciding to use
+// the thread pool otherwise.
+pub(crate) fn global() -> Option<Scheduler> {
+    let _ = MODE.compare_exchange(UNDECIDED, POOL, Ordering::SeqCst, Ordering::SeqCst);
+    enabled()
+}
+
+// Returns the global scheduler if it was enabled.
+pub(crate) fn enabled() -> Option<Scheduler> {
+    if MODE.load(Ordering::SeqCst) == DETERMINISTIC {
+        GLOBAL.lock().unwrap().clone()
+    } else {
+        None
+    }
+}
+
+// Returns the scheduler running processes on the current thread.
+pub(crate) fn current() -> Option<Scheduler> {
+    CURRENT
+        .try_with(|current| current.borrow().clone())
+        .ok()
+        .flatten()
+}
+
+///
+/// Scheduler running processes one at a time, in an order only
+/// depending on its seed, with virtual time.
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::deterministic::Scheduler;
+/// use bastion_executor::timer;
+/// use lightproc::prelude::*;
+/// use std::time::Duration;
+///
+/// let scheduler = Scheduler::new(42);
+/// scheduler.spawn(
+///     async {
+///         // This doesn't wait for an hour...
+///         timer::sleep(Duration::from_secs(3600)).await;
+///     },
+///     ProcStack::default(),
+/// );
+///
+/// scheduler.run_until_idle();
+/// // ...but the virtual time advanced by an hour.
+/// assert_eq!(scheduler.now(), Duration::from_secs(3600));
+/// ```
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    seed: u64,
+    state: Mutex<State>,
+    // Notified when a process becomes ready or a timer is added.
+    cond: Condvar,
+}
+
+struct State {
+    ready: Vec<LightProc>,
+    rng: Rng,
+    now: Duration,
+    timers: Timers,
+}
+
+// A SplitMix64 generator.
+struct Rng(u64);
+
+impl Scheduler {
+    ///
+    /// Creates a new scheduler using the given seed, which doesn't
+    /// run any process until it is asked to (see [run_until_idle]).
+    ///
+    /// [run_until_idle]: #method.run_until_idle
+    pub fn new(seed: u64) -> Self {
+        let state = State {
+            ready: vec![],
+            rng: Rng(seed),
+            now: Duration::default(),
+            timers: Timers::default(),
+        };
+
+        Scheduler {
+            inner: Arc::new(Inner {
+                seed,
+                state: Mutex::new(state),
+                cond: Condvar::new(),
+            }),
+        }
+    }
+
+    ///
+    /// Returns the seed this scheduler was created with.
+    pub fn seed(&self) -> u64 {
+        self.inner.seed
+    }
+
+    ///
+    /// Returns the virtual time elapsed since this scheduler was
+    /// created.
+    pub fn now(&self) -> Duration {
+        self.inner.state.lock().unwrap().now
+    }
+
+    ///
+    /// Spawns a process onto this scheduler.
+    pub fn spawn<F, T>(&self, future: F, stack: ProcStack) -> RecoverableHandle<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let scheduler = self.clone();
+        let (proc, handle) =
+            LightProc::recoverable(future, move |proc| scheduler.schedule(proc), stack);
+        proc.schedule();
+        handle
+    }
+
+    ///
+    /// Runs the processes spawned onto this scheduler on the current
+    /// thread, advancing its virtual time when needed, until none of
+    /// them is ready to run and no timer is left.
+    ///
+    /// This is only meant for schedulers created using [new]: the
+    /// global scheduler (see [enable]) runs its processes on its own
+    /// thread.
+    ///
+    /// [new]: #method.new
+    /// [enable]: fn.enable.html
+    pub fn run_until_idle(&self) {
+        while let Some(proc) = self.next() {
+            self.run(proc);
+        }
+    }
+
+    fn run_forever(&self) {
+        loop {
+            self.run_until_idle();
+
+            let state = self.inner.state.lock().unwrap();
+            if state.ready.is_empty() && state.timers.is_empty() {
+                drop(self.inner.cond.wait(state).unwrap());
+            }
+        }
+    }
+
+    fn run(&self, proc: LightProc) {
+        CURRENT.with(|current| *current.borrow_mut() = Some(self.clone()));
+        worker::set_stack(proc.stack(), || proc.run());
+        CURRENT.with(|current| *current.borrow_mut() = None);
+    }
+
+    fn schedule(&self, proc: LightProc) {
+        self.inner.state.lock().unwrap().ready.push(proc);
+        self.inner.cond.notify_one();
+    }
+
+    // Returns the next process to run, picked among the ready ones,
+    // advancing the virtual time to the next timer's deadline if
+    // none is ready.
+    fn next(&self) -> Option<LightProc> {
+        loop {
+            let wakers = {
+                let mut state = self.inner.state.lock().unwrap();
+                if !state.ready.is_empty() {
+                    let index = (state.rng.next() % state.ready.len() as u64) as usize;
+                    return Some(state.ready.swap_remove(index));
+                }
+
+                let deadline = state.timers.next_deadline()?;
+                if deadline > state.now {
+                    state.now = deadline;
+                }
+
+                let now = state.now;
+                state.timers.expire(now)
+            };
+
+            wakers.into_iter().for_each(Waker::wake);
+        }
+    }
+
+    pub(crate) fn poll_timer(&self, deadline: Duration, waker: &Waker) -> Poll<()> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.now >= deadline {
+            return Poll::Ready(());
+        }
+
+        state.timers.insert(deadline, waker.clone());
+        self.inner.cond.notify_one();
+        Poll::Pending
+    }
+}
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl Debug for Scheduler {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Scheduler")
+            .field("seed", &self.inner.seed)
+            .field("now", &self.now())
+            .finish()
+    }
+}
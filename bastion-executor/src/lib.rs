@@ -34,6 +34,7 @@ mod macros;
 
 pub mod allocator;
 pub mod blocking;
+pub mod deterministic;
 pub mod distributor;
 pub mod load_balancer;
 pub mod placement;
@@ -41,6 +42,7 @@ pub mod pool;
 pub mod run;
 pub mod run_queue;
 pub mod sleepers;
+pub mod timer;
 pub mod worker;
 
 mod utils;
@@ -4,6 +4,11 @@
 //! Pool management and tracking belongs here.
 //! We spawn futures onto the pool with [spawn] method of global run queue or
 //! with corresponding [Worker]'s spawn method.
+//!
+//! Once the [deterministic] scheduler is enabled, [spawn] uses it instead of the pool.
+//!
+//! [deterministic]: ../deterministic/index.html
+use crate::deterministic;
 use crate::distributor::Distributor;
 use crate::run_queue::{Injector, Stealer};
 use crate::sleepers::Sleepers;
@@ -42,7 +47,12 @@ where
     F: Future<Output = T> + Send + 'static,
     T: Send + 'static,
 {
-    self::get().spawn(future, stack)
+    // Processes run on the deterministic scheduler instead of the
+    // pool if it was enabled.
+    match deterministic::global() {
+        Some(scheduler) => scheduler.spawn(future, stack),
+        None => self::get().spawn(future, stack),
+    }
 }
 
 ///
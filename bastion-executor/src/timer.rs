@@ -0,0 +1,225 @@
+//!
+//! Timers of the executor
+//!
+//! Processes can wait for a given duration using [sleep], which
+//! uses the real time unless it is polled by a process running on a
+//! [deterministic] scheduler, in which case it uses the scheduler's
+//! virtual time.
+//!
+//! [sleep]: fn.sleep.html
+//! [deterministic]: ../deterministic/index.html
+use crate::deterministic::{self, Scheduler};
+use lazy_static::lazy_static;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+///
+/// Returns the time elapsed since the executor's clock started.
+///
+/// This is the virtual time of the deterministic scheduler running
+/// the current process (or of the global one, if it was enabled),
+/// or the real time otherwise.
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::timer;
+///
+/// let before = timer::now();
+/// assert!(timer::now() >= before);
+/// ```
+pub fn now() -> Duration {
+    match clock() {
+        Some(scheduler) => scheduler.now(),
+        None => real().start.elapsed(),
+    }
+}
+
+///
+/// Creates a future which resolves once the given duration elapsed,
+/// according to the executor's clock (see [now]).
+///
+/// # Example
+/// ```rust
+/// use bastion_executor::prelude::*;
+/// use bastion_executor::timer;
+/// use lightproc::prelude::*;
+/// use std::time::Duration;
+///
+/// run(
+///     async {
+///         timer::sleep(Duration::from_millis(10)).await;
+///     },
+///     ProcStack::default(),
+/// );
+/// ```
+///
+/// [now]: fn.now.html
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: now() + duration,
+    }
+}
+
+///
+/// Future returned by [sleep].
+///
+/// [sleep]: fn.sleep.html
+#[derive(Debug)]
+pub struct Sleep {
+    deadline: Duration,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let deadline = self.deadline;
+        match clock() {
+            Some(scheduler) => scheduler.poll_timer(deadline, cx.waker()),
+            None => real().poll_timer(deadline, cx.waker()),
+        }
+    }
+}
+
+// Returns the deterministic scheduler whose clock should be used,
+// if any.
+fn clock() -> Option<Scheduler> {
+    deterministic::current().or_else(deterministic::enabled)
+}
+
+///
+/// Timers waiting for their deadline, ordered by deadline.
+#[derive(Debug, Default)]
+pub(crate) struct Timers {
+    entries: BinaryHeap<Reverse<Entry>>,
+    next_id: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    deadline: Duration,
+    // Used to order timers with the same deadline by creation.
+    id: u64,
+    waker: Waker,
+}
+
+impl Timers {
+    pub(crate) fn insert(&mut self, deadline: Duration, waker: Waker) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.entries.push(Reverse(Entry {
+            deadline,
+            id,
+            waker,
+        }));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub(crate) fn next_deadline(&self) -> Option<Duration> {
+        self.entries.peek().map(|Reverse(entry)| entry.deadline)
+    }
+
+    /// Removes the timers whose deadline is before `now`, returning
+    /// their wakers in order.
+    pub(crate) fn expire(&mut self, now: Duration) -> Vec<Waker> {
+        let mut wakers = vec![];
+        while let Some(Reverse(entry)) = self.entries.peek() {
+            if entry.deadline > now {
+                break;
+            }
+
+            if let Some(Reverse(entry)) = self.entries.pop() {
+                wakers.push(entry.waker);
+            }
+        }
+
+        wakers
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.deadline, self.id).cmp(&(other.deadline, other.id))
+    }
+}
+
+// The timers using the real time, whose wakers are called by a
+// dedicated thread.
+struct RealTimers {
+    start: Instant,
+    timers: Mutex<Timers>,
+    cond: Condvar,
+}
+
+fn real() -> &'static RealTimers {
+    lazy_static! {
+        static ref REAL: RealTimers = {
+            thread::Builder::new()
+                .name("bastion-timer-thread".to_string())
+                .spawn(|| real().run())
+                .expect("cannot start the timer thread");
+
+            RealTimers {
+                start: Instant::now(),
+                timers: Mutex::new(Timers::default()),
+                cond: Condvar::new(),
+            }
+        };
+    }
+    &REAL
+}
+
+impl RealTimers {
+    fn poll_timer(&self, deadline: Duration, waker: &Waker) -> Poll<()> {
+        if self.start.elapsed() >= deadline {
+            return Poll::Ready(());
+        }
+
+        self.timers.lock().unwrap().insert(deadline, waker.clone());
+        self.cond.notify_one();
+        Poll::Pending
+    }
+
+    fn run(&self) {
+        let mut timers = self.timers.lock().unwrap();
+        loop {
+            let now = self.start.elapsed();
+            let wakers = timers.expire(now);
+            if !wakers.is_empty() {
+                drop(timers);
+                wakers.into_iter().for_each(Waker::wake);
+                timers = self.timers.lock().unwrap();
+                continue;
+            }
+
+            timers = match timers.next_deadline() {
+                Some(deadline) => self.cond.wait_timeout(timers, deadline - now).unwrap().0,
+                None => self.cond.wait(timers).unwrap(),
+            };
+        }
+    }
+}
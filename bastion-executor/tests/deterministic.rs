@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use bastion_executor::deterministic::Scheduler;
+    use bastion_executor::timer;
+    use futures::future;
+    use lightproc::proc_stack::ProcStack;
+    use std::sync::{Arc, Mutex};
+    use std::task::Poll;
+    use std::time::Duration;
+
+    // Returns the order in which processes yielding several times
+    // ran on a scheduler using the given seed.
+    fn run_order(seed: u64) -> Vec<usize> {
+        let scheduler = Scheduler::new(seed);
+        let order = Arc::new(Mutex::new(vec![]));
+
+        for id in 0..8 {
+            let order = order.clone();
+            scheduler.spawn(
+                async move {
+                    for _ in 0..4 {
+                        order.lock().unwrap().push(id);
+
+                        // Yields to the other processes.
+                        let mut yielded = false;
+                        future::poll_fn(|cx| {
+                            if yielded {
+                                Poll::Ready(())
+                            } else {
+                                yielded = true;
+                                cx.waker().wake_by_ref();
+                                Poll::Pending
+                            }
+                        })
+                        .await;
+                    }
+                },
+                ProcStack::default(),
+            );
+        }
+
+        scheduler.run_until_idle();
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[test]
+    fn same_seed_same_order() {
+        let order = run_order(42);
+        assert_eq!(order.len(), 32);
+        assert_eq!(run_order(42), order);
+        assert_ne!(run_order(7), order);
+    }
+
+    #[test]
+    fn virtual_time() {
+        let scheduler = Scheduler::new(0);
+        let woken = Arc::new(Mutex::new(vec![]));
+
+        for secs in &[30, 10, 20] {
+            let woken = woken.clone();
+            scheduler.spawn(
+                async move {
+                    timer::sleep(Duration::from_secs(*secs)).await;
+                    woken.lock().unwrap().push((*secs, timer::now()));
+                },
+                ProcStack::default(),
+            );
+        }
+
+        scheduler.run_until_idle();
+        assert_eq!(scheduler.now(), Duration::from_secs(30));
+        assert_eq!(
+            *woken.lock().unwrap(),
+            vec![
+                (10, Duration::from_secs(10)),
+                (20, Duration::from_secs(20)),
+                (30, Duration::from_secs(30)),
+            ]
+        );
+    }
+}
@@ -27,6 +27,7 @@ fn main() {
     let workers = Bastion::children(|children: Children| {
         children
             .with_redundancy(100) // Let's have a pool of an hundred workers.
+            .with_blocking() // Reading and writing the streams blocks.
             .with_exec(move |ctx: BastionContext| {
                 async move {
                     println!("Worker started!");
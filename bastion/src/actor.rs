@@ -0,0 +1,306 @@
+//!
+//! A trait-based alternative to writing the future of a children
+//! group's elements using the [`msg!`] macro, where an [`Actor`]
+//! handles each type of message it accepts in its own [`Handler`]
+//! implementation.
+//!
+//! [`msg!`]: ../macro.msg.html
+//! [`Actor`]: trait.Actor.html
+//! [`Handler`]: trait.Handler.html
+use crate::context::BastionContext;
+use crate::envelope::{RefAddr, SignedMessage};
+use crate::message::{AnswerSender, Message};
+use fxhash::FxHashMap;
+use std::any::TypeId;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+type Handle<A> =
+    Box<dyn Fn(&mut A, &BastionContext, SignedMessage) -> Result<(), ()> + Send + Sync>;
+
+/// An actor, which is run by the elements of a children group
+/// created using [`Children::with_actor`].
+///
+/// Each element of the group creates a new actor when it is
+/// started or restarted, calls its [`started`] method and then
+/// dispatches the messages it receives to the [`Handler`]
+/// implementation for their type (as registered by [`handlers`]),
+/// or to [`unhandled`] if there isn't any. Its [`stopped`] method
+/// is called once the element stops, is killed or faults.
+///
+/// If a method returns `Err(())` (or panics), the element faults.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// #[derive(Default)]
+/// struct Counter(u64);
+///
+/// impl Actor for Counter {
+///     fn handlers(handlers: Handlers<Self>) -> Handlers<Self> {
+///         handlers.handle::<u64>().handle::<&'static str>()
+///     }
+/// }
+///
+/// impl Handler<u64> for Counter {
+///     fn handle(&mut self, _: &BastionContext, added: u64, _: Request) -> Result<(), ()> {
+///         self.0 += added;
+///         Ok(())
+///     }
+/// }
+///
+/// impl Handler<&'static str> for Counter {
+///     fn handle(&mut self, _: &BastionContext, _: &'static str, req: Request) -> Result<(), ()> {
+///         // Answers the current count if the message was "asked".
+///         req.answer(self.0).ok();
+///         Ok(())
+///     }
+/// }
+///
+/// # fn main() {
+///     # Bastion::init();
+///     #
+/// Bastion::children(|children| {
+///     children.with_actor(Counter::default)
+/// }).expect("Couldn't create the children group.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`Children::with_actor`]: ../children/struct.Children.html#method.with_actor
+/// [`started`]: #method.started
+/// [`stopped`]: #method.stopped
+/// [`handlers`]: #tymethod.handlers
+/// [`unhandled`]: #method.unhandled
+/// [`Handler`]: trait.Handler.html
+pub trait Actor: Send + Sized + 'static {
+    /// Registers the types of messages this actor handles, each
+    /// of which must have a [`Handler`] implementation.
+    ///
+    /// This is only called once per children group.
+    ///
+    /// [`Handler`]: trait.Handler.html
+    fn handlers(handlers: Handlers<Self>) -> Handlers<Self>;
+
+    /// Called when the element running this actor is started or
+    /// restarted, before it handles any message.
+    fn started(&mut self, _ctx: &BastionContext) -> Result<(), ()> {
+        Ok(())
+    }
+
+    /// Called when the element running this actor stops, is
+    /// killed, or faults because one of this actor's methods
+    /// returned an error (but not when it panicked).
+    fn stopped(&mut self, _ctx: &BastionContext) {}
+
+    /// Called when the element running this actor receives a
+    /// message whose type isn't handled by the actor.
+    ///
+    /// The default implementation drops the message.
+    fn unhandled(&mut self, ctx: &BastionContext, msg: SignedMessage) -> Result<(), ()> {
+        warn!(
+            "Actor({}): Dropping an unhandled message: {:?}",
+            ctx.current().id(),
+            msg
+        );
+        Ok(())
+    }
+}
+
+/// The handler of an [`Actor`] for the messages of type `M`.
+///
+/// Messages that were broadcasted are handled by the
+/// `Handler<Arc<M>>` implementation instead (see
+/// [`Handlers::handle_broadcast`]).
+///
+/// [`Actor`]: trait.Actor.html
+/// [`Handlers::handle_broadcast`]: struct.Handlers.html#method.handle_broadcast
+pub trait Handler<M: Message>: Actor {
+    /// Handles a message of type `M`.
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of the element running the actor.
+    /// * `msg` - The message to handle.
+    /// * `req` - The message's sender and the way to answer it if
+    ///   it was "asked".
+    fn handle(&mut self, ctx: &BastionContext, msg: M, req: Request) -> Result<(), ()>;
+}
+
+/// The types of messages handled by an [`Actor`], which are
+/// dispatched to it using their `TypeId`.
+///
+/// See [`Actor::handlers`].
+///
+/// [`Actor`]: trait.Actor.html
+/// [`Actor::handlers`]: trait.Actor.html#tymethod.handlers
+pub struct Handlers<A: Actor> {
+    // The handlers of the messages that were told or asked...
+    msgs: FxHashMap<TypeId, Handle<A>>,
+    // ...and of those that were broadcasted.
+    broadcasts: FxHashMap<TypeId, Handle<A>>,
+}
+
+#[derive(Debug)]
+/// The sender of a message handled by an [`Actor`], along with
+/// the way to answer it if it was "asked".
+///
+/// [`Actor`]: trait.Actor.html
+pub struct Request {
+    sign: RefAddr,
+    sender: Option<AnswerSender>,
+    // The signature of the actor, used to answer.
+    current: RefAddr,
+}
+
+impl<A: Actor> Handlers<A> {
+    pub(crate) fn new() -> Self {
+        let msgs = FxHashMap::default();
+        let broadcasts = FxHashMap::default();
+
+        Handlers { msgs, broadcasts }
+    }
+
+    /// Registers the [`Handler`] of the actor for the messages
+    /// of type `M` that were told or asked.
+    ///
+    /// [`Handler`]: trait.Handler.html
+    pub fn handle<M: Message>(mut self) -> Self
+    where
+        A: Handler<M>,
+    {
+        let handle: Handle<A> = Box::new(|actor, ctx, smsg| {
+            let (mut msg, sign) = smsg.extract();
+            let sender = msg.take_sender();
+            // FIXME: panics?
+            let msg = msg.downcast::<M>().unwrap();
+            let req = Request::new(sign, sender, ctx.signature());
+
+            actor.handle(ctx, msg, req)
+        });
+
+        self.msgs.insert(TypeId::of::<M>(), handle);
+        self
+    }
+
+    /// Registers the `Handler<Arc<M>>` of the actor for the
+    /// messages of type `M` that were broadcasted.
+    pub fn handle_broadcast<M: Message + Sync>(mut self) -> Self
+    where
+        A: Handler<Arc<M>>,
+    {
+        let handle: Handle<A> = Box::new(|actor, ctx, smsg| {
+            let (msg, sign) = smsg.extract();
+            // FIXME: panics?
+            let msg = msg.downcast_ref::<M>().unwrap();
+            let req = Request::new(sign, None, ctx.signature());
+
+            actor.handle(ctx, msg, req)
+        });
+
+        self.broadcasts.insert(TypeId::of::<M>(), handle);
+        self
+    }
+
+    pub(crate) fn dispatch(
+        &self,
+        actor: &mut A,
+        ctx: &BastionContext,
+        smsg: SignedMessage,
+    ) -> Result<(), ()> {
+        let handlers = if smsg.msg.is_broadcast() {
+            &self.broadcasts
+        } else {
+            &self.msgs
+        };
+
+        match handlers.get(&smsg.msg.type_id()) {
+            Some(handle) => handle(actor, ctx, smsg),
+            None => actor.unhandled(ctx, smsg),
+        }
+    }
+}
+
+impl Request {
+    pub(crate) fn new(sign: RefAddr, sender: Option<AnswerSender>, current: RefAddr) -> Self {
+        Request {
+            sign,
+            sender,
+            current,
+        }
+    }
+
+    /// Returns the signature of the message's sender.
+    pub fn signature(&self) -> &RefAddr {
+        &self.sign
+    }
+
+    /// Returns whether the message was "asked" and can thus be
+    /// answered.
+    pub fn is_ask(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Answers the message if it was "asked".
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)`
+    /// if the message wasn't "asked" or the answer couldn't be
+    /// sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The answer to send.
+    pub fn answer<M: Message>(self, msg: M) -> Result<(), M> {
+        match self.sender {
+            Some(sender) => sender.send(msg, self.current),
+            None => Err(msg),
+        }
+    }
+}
+
+// Calls the actor's `stopped` method when the future running
+// it is dropped (ie. when the element stops or is killed).
+struct Running<A: Actor> {
+    actor: A,
+    ctx: BastionContext,
+}
+
+pub(crate) async fn run<A: Actor>(
+    actor: A,
+    ctx: BastionContext,
+    handlers: Arc<Handlers<A>>,
+) -> Result<(), ()> {
+    let mut running = Running { actor, ctx };
+    running.actor.started(&running.ctx)?;
+
+    loop {
+        let smsg = running.ctx.recv().await?;
+        let Running { actor, ctx } = &mut running;
+        handlers.dispatch(actor, ctx, smsg)?;
+    }
+}
+
+impl<A: Actor> Drop for Running<A> {
+    fn drop(&mut self) {
+        // The element faulted.
+        if std::thread::panicking() {
+            return;
+        }
+
+        self.actor.stopped(&self.ctx);
+    }
+}
+
+impl<A: Actor> Debug for Handlers<A> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Handlers")
+            .field("msgs", &self.msgs.len())
+            .field("broadcasts", &self.broadcasts.len())
+            .finish()
+    }
+}
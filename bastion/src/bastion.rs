@@ -1,16 +1,16 @@
-use crate::broadcast::{Broadcast, Parent};
+use crate::child_ref::ChildRef;
 use crate::children::Children;
 use crate::children_ref::ChildrenRef;
 use crate::config::Config;
-use crate::context::{BastionContext, BastionId};
-use crate::envelope::Envelope;
-use crate::message::{BastionMessage, Message};
-use crate::path::BastionPathElement;
+use crate::context::BastionContext;
+use crate::envelope::RefAddr;
+use crate::message::Message;
+use crate::path::BastionPath;
 use crate::supervisor::{Supervisor, SupervisorRef};
-use crate::system::SYSTEM;
+use crate::system::BastionSystem;
+use crate::typed::{TypedChildren, TypedChildrenRef};
 use core::future::Future;
 use std::fmt::{self, Debug, Formatter};
-use std::thread;
 
 /// A `struct` allowing to access the system's API to initialize it,
 /// start, stop and kill it and to create new supervisors and top-level
@@ -154,6 +154,11 @@ impl Bastion {
     /// [`Bastion::init_with`] at least once before using any of
     /// bastion's features.**
     ///
+    /// Once the system stopped (see [`Bastion::stop`] and
+    /// [`Bastion::kill`]), calling this method again initializes a
+    /// new one, with a new system supervisor, new dead letters and
+    /// without any of the names or topics used by the previous one.
+    ///
     /// # Example
     ///
     /// ```rust
@@ -172,6 +177,8 @@ impl Bastion {
     ///
     /// [`Config`]: struct.Config.html
     /// [`Bastion::init_with`]: #method.init_with
+    /// [`Bastion::stop`]: #method.stop
+    /// [`Bastion::kill`]: #method.kill
     pub fn init() {
         let config = Config::default();
         Bastion::init_with(config)
@@ -184,6 +191,9 @@ impl Bastion {
     /// `Bastion::init_with` at least once before using any of
     /// bastion's features.**
     ///
+    /// Once the system stopped, calling this method again
+    /// initializes a new one using the specified [`Config`].
+    ///
     /// # Arguments
     ///
     /// * `config` - The configuration used to initialize the system.
@@ -211,13 +221,43 @@ impl Bastion {
     /// [`Bastion::init`]: #method.init
     pub fn init_with(config: Config) {
         debug!("Bastion: Initializing with config: {:?}", config);
-        if config.backtraces().is_hide() {
-            debug!("Bastion: Hiding backtraces.");
-            std::panic::set_hook(Box::new(|_| ()));
-        }
+        BastionSystem::init_default(config);
+    }
 
-        // NOTE: this is just to make sure that SYSTEM has been initialized by lazy_static
-        SYSTEM.sender().is_closed();
+    /// Returns the default [`BastionSystem`], which all the other
+    /// methods of `Bastion` use, initializing it with the default
+    /// [`Config`] if [`Bastion::init`] or [`Bastion::init_with`]
+    /// weren't called before.
+    ///
+    /// Once stopped, the same system keeps being returned until
+    /// [`Bastion::init`] or [`Bastion::init_with`] is called again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// let system: BastionSystem = Bastion::system();
+    /// let children_ref = system.children(|children| {
+    ///     // ...
+    /// #   children
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionSystem`]: struct.BastionSystem.html
+    /// [`Config`]: struct.Config.html
+    /// [`Bastion::init`]: #method.init
+    /// [`Bastion::init_with`]: #method.init_with
+    pub fn system() -> BastionSystem {
+        BastionSystem::default_system()
     }
 
     /// Creates a new [`Supervisor`], passes it through the specified
@@ -260,22 +300,7 @@ impl Bastion {
         S: FnOnce(Supervisor) -> Supervisor,
     {
         debug!("Bastion: Creating supervisor.");
-        let parent = Parent::system();
-        let bcast = Broadcast::new(parent, BastionPathElement::Supervisor(BastionId::new()));
-
-        debug!("Bastion: Initializing Supervisor({}).", bcast.id());
-        let supervisor = Supervisor::new(bcast);
-        let supervisor = init(supervisor);
-        debug!("Supervisor({}): Initialized.", supervisor.id());
-        let supervisor_ref = supervisor.as_ref();
-
-        debug!("Bastion: Deploying Supervisor({}).", supervisor.id());
-        let msg = BastionMessage::deploy_supervisor(supervisor);
-        let envelope = Envelope::new(msg, SYSTEM.path().clone(), SYSTEM.sender().clone());
-        trace!("Bastion: Sending envelope: {:?}", envelope);
-        SYSTEM.sender().unbounded_send(envelope).map_err(|_| ())?;
-
-        Ok(supervisor_ref)
+        Bastion::system().supervisor(init)
     }
 
     /// Creates a new [`Children`], passes it through the specified
@@ -331,7 +356,64 @@ impl Bastion {
         C: FnOnce(Children) -> Children,
     {
         debug!("Bastion: Creating children group.");
-        SYSTEM.supervisor().children(init)
+        Bastion::system().children(init)
+    }
+
+    /// Creates a new [`TypedChildren`], whose elements only handle
+    /// messages of type `P`, passes it through the specified `init`
+    /// closure and then sends it to the system's default supervisor
+    /// for it to start supervising it.
+    ///
+    /// This methods returns a [`TypedChildrenRef`] referencing the
+    /// newly created children group it it succeeded, or `Err(())`
+    /// otherwise.
+    ///
+    /// See [`children`].
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure taking the new [`TypedChildren`] as
+    ///   an argument and returning it once configured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// #[derive(Debug)]
+    /// enum Greeting {
+    ///     Hello(String),
+    /// }
+    ///
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// let children_ref: TypedChildrenRef<Greeting> = Bastion::typed_children(|children| {
+    ///     children.with_exec(|ctx: TypedContext<Greeting>| {
+    ///         async move {
+    ///             let (Greeting::Hello(name), _) = ctx.recv().await?;
+    ///             println!("Hello, {}!", name);
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`TypedChildren`]: typed/struct.TypedChildren.html
+    /// [`TypedChildrenRef`]: typed/struct.TypedChildrenRef.html
+    /// [`children`]: #method.children
+    pub fn typed_children<P, C>(init: C) -> Result<TypedChildrenRef<P>, ()>
+    where
+        P: Message,
+        C: FnOnce(TypedChildren<P>) -> TypedChildren<P>,
+    {
+        debug!("Bastion: Creating typed children group.");
+        Bastion::system().typed_children(init)
     }
 
     /// Creates a new [`Children`] which will have the given closure
@@ -376,7 +458,7 @@ impl Bastion {
         I: Fn(BastionContext) -> F + Send + Sync + 'static,
         F: Future<Output = Result<(), ()>> + Send + 'static,
     {
-        Bastion::children(|ch| ch.with_redundancy(1).with_exec(action))
+        Bastion::system().spawn(action)
     }
 
     /// Sends a message to the system which will then send it to all
@@ -424,16 +506,303 @@ impl Bastion {
     ///     # Bastion::block_until_stopped();
     /// # }
     /// ```
-    pub fn broadcast<M: Message>(msg: M) -> Result<(), M> {
-        debug!("Bastion: Broadcasting message: {:?}", msg);
-        let msg = BastionMessage::broadcast(msg);
-        let envelope = Envelope::from_dead_letters(msg);
-        trace!("Bastion: Sending envelope: {:?}", envelope);
-        // FIXME: panics?
-        SYSTEM
-            .sender()
-            .unbounded_send(envelope)
-            .map_err(|err| err.into_inner().into_msg().unwrap())
+    pub fn broadcast<M: Message + Sync>(msg: M) -> Result<(), M> {
+        Bastion::system().broadcast(msg)
+    }
+
+    /// Registers the child referenced by the specified [`ChildRef`]
+    /// under the specified name, allowing it to be found later
+    /// using [`Bastion::whereis`].
+    ///
+    /// The name stays registered when the child is restarted by
+    /// its supervisor (it then references the new instance of the
+    /// child) and is unregistered when the child stops.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())` if
+    /// the name is already used by another running child.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to register the child under.
+    /// * `child` - A reference to the child to register.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// let children_ref = Bastion::children(|children| {
+    ///     // ...
+    ///     # children
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// let child_ref = &children_ref.elems()[0];
+    /// Bastion::register("logger", child_ref).expect("Couldn't register the child.");
+    ///
+    /// assert_eq!(Bastion::whereis("logger").as_ref(), Some(child_ref));
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`ChildRef`]: children/struct.ChildRef.html
+    /// [`Bastion::whereis`]: #method.whereis
+    pub fn register(name: &str, child: &ChildRef) -> Result<(), ()> {
+        Bastion::system().register(name, child)
+    }
+
+    /// Unregisters the specified name, previously registered
+    /// using [`Bastion::register`] or [`BastionContext::register`].
+    ///
+    /// This method returns a [`ChildRef`] referencing the child
+    /// that was registered under this name, or `None` if the name
+    /// wasn't registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to unregister.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    ///     # let child_ref = &children_ref.elems()[0];
+    ///     # Bastion::register("logger", child_ref).unwrap();
+    /// Bastion::unregister("logger");
+    ///
+    /// assert!(Bastion::whereis("logger").is_none());
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Bastion::register`]: #method.register
+    /// [`BastionContext::register`]: context/struct.BastionContext.html#method.register
+    /// [`ChildRef`]: children/struct.ChildRef.html
+    pub fn unregister(name: &str) -> Option<ChildRef> {
+        Bastion::system().unregister(name)
+    }
+
+    /// Returns a [`ChildRef`] referencing the running child
+    /// registered under the specified name (using
+    /// [`Bastion::register`] or [`BastionContext::register`]),
+    /// or `None` if there isn't any.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name the child was registered under.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             ctx.register("logger").expect("Couldn't register the child.");
+    ///
+    ///             loop {
+    ///                 msg! { ctx.recv().await?,
+    ///                     msg: &'static str => {
+    ///                         println!("{}", msg);
+    ///                     };
+    ///                     _: _ => ();
+    ///                 }
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// // Later, from anywhere...
+    /// if let Some(logger) = Bastion::whereis("logger") {
+    ///     logger.tell_anonymously("A message containing data.").ok();
+    /// }
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`ChildRef`]: children/struct.ChildRef.html
+    /// [`Bastion::register`]: #method.register
+    /// [`BastionContext::register`]: context/struct.BastionContext.html#method.register
+    pub fn whereis(name: &str) -> Option<ChildRef> {
+        Bastion::system().whereis(name)
+    }
+
+    /// Publishes a message to every running child subscribed
+    /// (using [`BastionContext::subscribe`]) to a pattern matching
+    /// the specified topic.
+    ///
+    /// The children will receive the message as if it was
+    /// broadcasted (see the [`msg!`] macro).
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)` if
+    /// no child is subscribed to the topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic to publish the message about.
+    /// * `msg` - The message to publish.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             ctx.subscribe("orders.#").expect("Couldn't subscribe.");
+    ///
+    ///             loop {
+    ///                 msg! { ctx.recv().await?,
+    ///                     ref msg: &'static str => {
+    ///                         // Handle the published message...
+    ///                     };
+    ///                     _: _ => ();
+    ///                 }
+    ///             }
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// // Later, once the child subscribed...
+    /// Bastion::publish("orders.created", "A message containing data.").ok();
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionContext::subscribe`]: context/struct.BastionContext.html#method.subscribe
+    /// [`msg!`]: macro.msg.html
+    pub fn publish<M: Message + Sync>(topic: &str, msg: M) -> Result<(), M> {
+        Bastion::system().publish(topic, msg)
+    }
+
+    /// Returns the [`RefAddr`] of the running supervisor,
+    /// children group or child identified by the given path, by
+    /// walking down the supervision tree, or `None` if it isn't
+    /// running.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the element to resolve, which can
+    ///   have been parsed from a string (see [`BastionPath`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             msg! { ctx.recv().await?,
+    ///                 ref msg: &'static str => {
+    ///                     // The sender's path could have been persisted...
+    ///                     let path: BastionPath = signature!().path().to_string().parse().unwrap();
+    ///
+    ///                     // ...and resolved later on.
+    ///                     if let Some(addr) = Bastion::resolve(&path) {
+    ///                         ctx.tell(&addr, "A message containing data.").ok();
+    ///                     }
+    ///                 };
+    ///                 _: _ => ();
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`RefAddr`]: envelope/struct.RefAddr.html
+    /// [`BastionPath`]: path/struct.BastionPath.html
+    pub fn resolve(path: &BastionPath) -> Option<RefAddr> {
+        Bastion::system().resolve(path)
+    }
+
+    /// Returns the [`RefAddr`]s of all the running supervisors,
+    /// children groups and children whose path matches the
+    /// given pattern, by walking down the supervision tree.
+    ///
+    /// This method returns `Err(())` if the pattern isn't valid.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A path formatted like the ones parsed by
+    ///   [`BastionPath`]'s `FromStr` implementation but where
+    ///   any element can be replaced by a `*` to match every
+    ///   element at that position (eg. `/<uuid>/<uuid>/*` to
+    ///   select every child of a children group).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             // Select every child of the same children group...
+    ///             let path = ctx.signature().path().to_string();
+    ///             let (group, _) = path.split_at(path.rfind('/').unwrap());
+    ///             let pattern = format!("{}/*", group);
+    ///
+    ///             // ...and send them a message.
+    ///             for addr in Bastion::select(&pattern).expect("Invalid pattern.") {
+    ///                 ctx.tell(&addr, "A message containing data.").ok();
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`RefAddr`]: envelope/struct.RefAddr.html
+    /// [`BastionPath`]: path/struct.BastionPath.html
+    pub fn select(pattern: &str) -> Result<Vec<RefAddr>, ()> {
+        Bastion::system().select(pattern)
     }
 
     /// Sends a message to the system to tell it to start
@@ -459,12 +828,7 @@ impl Bastion {
     /// }
     /// ```
     pub fn start() {
-        debug!("Bastion: Starting.");
-        let msg = BastionMessage::start();
-        let envelope = Envelope::from_dead_letters(msg);
-        trace!("Bastion: Sending envelope: {:?}", envelope);
-        // FIXME: Err(Error)
-        SYSTEM.sender().unbounded_send(envelope).ok();
+        Bastion::system().start()
     }
 
     /// Sends a message to the system to tell it to stop
@@ -490,12 +854,7 @@ impl Bastion {
     /// }
     /// ```
     pub fn stop() {
-        debug!("Bastion: Stopping.");
-        let msg = BastionMessage::stop();
-        let envelope = Envelope::from_dead_letters(msg);
-        trace!("Bastion: Sending envelope: {:?}", envelope);
-        // FIXME: Err(Error)
-        SYSTEM.sender().unbounded_send(envelope).ok();
+        Bastion::system().stop()
     }
 
     /// Sends a message to the system to tell it to kill every
@@ -520,19 +879,7 @@ impl Bastion {
     /// }
     /// ```
     pub fn kill() {
-        debug!("Bastion: Killing.");
-        let msg = BastionMessage::kill();
-        let envelope = Envelope::from_dead_letters(msg);
-        trace!("Bastion: Sending envelope: {:?}", envelope);
-        // FIXME: Err(Error)
-        SYSTEM.sender().unbounded_send(envelope).ok();
-
-        // FIXME: panics
-        let mut system = SYSTEM.handle().lock().wait().unwrap();
-        if let Some(system) = system.take() {
-            debug!("Bastion: Cancelling system handle.");
-            system.cancel();
-        }
+        Bastion::system().kill()
     }
 
     /// Blocks the current thread until the system is stopped
@@ -563,17 +910,7 @@ impl Bastion {
     /// [`Bastion::stop()`]: #method.stop
     /// [`Bastion::kill()`]: #method.kill
     pub fn block_until_stopped() {
-        debug!("Bastion: Blocking until system is stopped.");
-        loop {
-            // FIXME: panics
-            let system = SYSTEM.handle().lock().wait().unwrap();
-            if system.is_none() {
-                debug!("Bastion: Unblocking because system is stopped.");
-                return;
-            }
-
-            thread::yield_now();
-        }
+        Bastion::system().block_until_stopped()
     }
 }
 
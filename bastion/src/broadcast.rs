@@ -4,7 +4,7 @@ use crate::envelope::Envelope;
 use crate::message::BastionMessage;
 use crate::path::{BastionPath, BastionPathElement};
 use crate::supervisor::SupervisorRef;
-use crate::system::SYSTEM;
+use crate::system::BastionSystem;
 use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use futures::prelude::*;
 use fxhash::FxHashMap;
@@ -27,7 +27,7 @@ pub(crate) struct Broadcast {
 #[derive(Debug, Clone)]
 pub(crate) enum Parent {
     None,
-    System,
+    System(BastionSystem),
     Supervisor(SupervisorRef),
     Children(ChildrenRef),
 }
@@ -42,7 +42,7 @@ impl Parent {
 
     pub(super) fn is_system(&self) -> bool {
         match self {
-            Parent::System => true,
+            Parent::System(_) => true,
             _ => false,
         }
     }
@@ -54,7 +54,7 @@ impl Broadcast {
         let children = FxHashMap::default();
 
         let parent_path: BastionPath = match &parent {
-            Parent::None | Parent::System => BastionPath::root(),
+            Parent::None | Parent::System(_) => BastionPath::root(),
             Parent::Supervisor(sv_ref) => BastionPath::clone(sv_ref.path()),
             Parent::Children(ch_ref) => BastionPath::clone(ch_ref.path()),
         };
@@ -108,6 +108,15 @@ impl Broadcast {
         &self.parent
     }
 
+    pub(crate) fn system(&self) -> &BastionSystem {
+        self.parent.running_in()
+    }
+
+    pub(crate) fn set_name(&mut self, name: Option<String>) {
+        let path = BastionPath::clone(&self.path).with_name(name);
+        self.path = Arc::new(path);
+    }
+
     pub(crate) fn register(&mut self, child: &Self) {
         self.children
             .insert(child.id().clone(), child.sender.clone());
@@ -160,6 +169,10 @@ impl Broadcast {
         let env = Envelope::new(msg, self.path.clone(), self.sender.clone());
         // FIXME: Err(msg)
         self.send_parent(env).ok();
+
+        // NOTE: closing the channel allows refs to know that
+        //      the element isn't alive anymore.
+        self.recver.close();
     }
 
     pub(crate) fn faulted(&mut self) {
@@ -169,6 +182,8 @@ impl Broadcast {
         let env = Envelope::new(msg, self.path.clone(), self.sender.clone());
         // FIXME: Err(msg)
         self.send_parent(env).ok();
+
+        self.recver.close();
     }
 
     pub(crate) fn send_parent(&self, envelope: Envelope) -> Result<(), Envelope> {
@@ -204,8 +219,8 @@ impl Parent {
         Parent::None
     }
 
-    pub(crate) fn system() -> Self {
-        Parent::System
+    pub(crate) fn system(system: BastionSystem) -> Self {
+        Parent::System(system)
     }
 
     pub(crate) fn supervisor(supervisor: SupervisorRef) -> Self {
@@ -232,11 +247,22 @@ impl Parent {
         }
     }
 
+    // Returns the system the element is running in.
+    pub(crate) fn running_in(&self) -> &BastionSystem {
+        match self {
+            // FIXME
+            Parent::None => unimplemented!(),
+            Parent::System(system) => system,
+            Parent::Supervisor(supervisor) => supervisor.system(),
+            Parent::Children(children) => children.system(),
+        }
+    }
+
     fn send(&self, env: Envelope) -> Result<(), Envelope> {
         match self {
             // FIXME
             Parent::None => unimplemented!(),
-            Parent::System => SYSTEM
+            Parent::System(system) => system
                 .sender()
                 .unbounded_send(env)
                 .map_err(|err| err.into_inner()),
@@ -260,6 +286,7 @@ mod tests {
     use crate::context::{BastionId, NIL_ID};
     use crate::envelope::Envelope;
     use crate::path::{BastionPath, BastionPathElement};
+    use crate::system::BastionSystem;
     use futures::channel::mpsc;
     use futures::executor;
     use futures::poll;
@@ -269,12 +296,13 @@ mod tests {
 
     #[test]
     fn send_children() {
-        let mut parent = Broadcast::new_root(Parent::System);
+        let system = BastionSystem::new();
+        let mut parent = Broadcast::new_root(Parent::system(system.clone()));
 
         let mut children = vec![];
         for _ in 0..4 {
             let child = Broadcast::new(
-                Parent::System,
+                Parent::system(system.clone()),
                 BastionPathElement::Supervisor(BastionId::new()),
             );
             parent.register(&child);
@@ -0,0 +1,57 @@
+//!
+//! A checkpoint keeps the last state saved by an element of a
+//! children group, which is handed to the element replacing it
+//! when the group is restarted.
+use std::any::Any;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex};
+
+pub(crate) struct StateInit(Box<dyn Fn() -> Box<dyn Any + Send> + Send + Sync>);
+
+#[derive(Clone)]
+pub(crate) struct Checkpoint(Arc<Mutex<Box<dyn Any + Send>>>);
+
+impl StateInit {
+    pub(crate) fn new<I, S>(init: I) -> Self
+    where
+        I: Fn() -> S + Send + Sync + 'static,
+        S: Send + 'static,
+    {
+        let init = Box::new(move || {
+            let state: Box<dyn Any + Send> = Box::new(init());
+            state
+        });
+
+        StateInit(init)
+    }
+
+    pub(crate) fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(Arc::new(Mutex::new((self.0)())))
+    }
+}
+
+impl Checkpoint {
+    pub(crate) fn load<S: Clone + 'static>(&self) -> Option<S> {
+        // FIXME: panics?
+        let state = self.0.lock().unwrap();
+        state.downcast_ref::<S>().cloned()
+    }
+
+    pub(crate) fn save<S: Send + 'static>(&self, state: S) {
+        // FIXME: panics?
+        let mut saved = self.0.lock().unwrap();
+        *saved = Box::new(state);
+    }
+}
+
+impl Debug for StateInit {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("StateInit").finish()
+    }
+}
+
+impl Debug for Checkpoint {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Checkpoint").finish()
+    }
+}
@@ -1,10 +1,11 @@
 //!
 //! Child is a element of Children group executing user-defined computation
 use crate::broadcast::Broadcast;
-use crate::context::{BastionContext, BastionId, ContextState};
-use crate::envelope::Envelope;
+use crate::context::{BastionContext, BastionId, ContextState, Tasks};
+use crate::envelope::{Envelope, RefAddr};
 use crate::message::BastionMessage;
-use bastion_executor::pool;
+use crate::monitor::ExitReason;
+use bastion_executor::{blocking, pool};
 use futures::pending;
 use futures::poll;
 use futures::prelude::*;
@@ -33,6 +34,9 @@ pub(crate) struct Child {
     // is received.
     pre_start_msgs: Vec<Envelope>,
     started: bool,
+    // The tasks spawned by the child's future, which are
+    // cancelled when the child exits.
+    tasks: Tasks,
 }
 
 impl Init {
@@ -53,7 +57,12 @@ impl Init {
 }
 
 impl Child {
-    pub(crate) fn new(exec: Exec, bcast: Broadcast, state: Qutex<ContextState>) -> Self {
+    pub(crate) fn new(
+        exec: Exec,
+        bcast: Broadcast,
+        state: Qutex<ContextState>,
+        tasks: Tasks,
+    ) -> Self {
         debug!("Child({}): Initializing.", bcast.id());
         let pre_start_msgs = Vec::new();
         let started = false;
@@ -64,6 +73,7 @@ impl Child {
             state,
             pre_start_msgs,
             started,
+            tasks,
         }
     }
 
@@ -74,6 +84,7 @@ impl Child {
         let parent = self.bcast.parent().clone().into_children().unwrap();
         let path = self.bcast.path().clone();
         let sender = self.bcast.sender().clone();
+        let system = self.bcast.system().clone();
 
         // FIXME: with_pid
         ProcStack::default().with_after_panic(move || {
@@ -81,6 +92,10 @@ impl Child {
             let id = id.clone();
             warn!("Child({}): Panicked.", id);
 
+            let sign = RefAddr::new(path.clone(), sender.clone());
+            system.monitors().exited(&id, sign, ExitReason::Faulted);
+            system.tree().unregister(&path);
+
             let msg = BastionMessage::faulted(id);
             let env = Envelope::new(msg, path.clone(), sender.clone());
             // TODO: handle errors
@@ -95,11 +110,26 @@ impl Child {
     fn stopped(&mut self) {
         debug!("Child({}): Stopped.", self.id());
         self.bcast.stopped();
+        self.exited(ExitReason::Stopped);
+    }
+
+    fn killed(&mut self) {
+        debug!("Child({}): Killed.", self.id());
+        self.bcast.stopped();
+        self.exited(ExitReason::Killed);
     }
 
     fn faulted(&mut self) {
         debug!("Child({}): Faulted.", self.id());
         self.bcast.faulted();
+        self.exited(ExitReason::Faulted);
+    }
+
+    fn exited(&self, reason: ExitReason) {
+        let sign = RefAddr::new(self.bcast.path().clone(), self.bcast.sender().clone());
+        let system = self.bcast.system();
+        system.monitors().exited(self.id(), sign, reason);
+        system.tree().unregister(self.bcast.path());
     }
 
     async fn handle(&mut self, env: Envelope) -> Result<(), ()> {
@@ -120,7 +150,7 @@ impl Child {
                 msg: BastionMessage::Kill,
                 ..
             } => {
-                self.stopped();
+                self.killed();
 
                 return Err(());
             }
@@ -152,11 +182,23 @@ impl Child {
                 msg: BastionMessage::Stopped { .. },
                 ..
             } => unimplemented!(),
-            // FIXME
+            // NOTE: this is only sent to children when a child
+            //      they are linked to faulted, or with their own
+            //      identifier when a fault is injected into them
+            //      (see `testkit::inject_fault`).
             Envelope {
-                msg: BastionMessage::Faulted { .. },
+                msg: BastionMessage::Faulted { id },
                 ..
-            } => unimplemented!(),
+            } => {
+                if &id == self.id() {
+                    warn!("Child({}): Fault injected.", self.id());
+                } else {
+                    warn!("Child({}): Linked Child({}) faulted.", self.id(), id);
+                }
+                self.faulted();
+
+                return Err(());
+            }
         }
 
         Ok(())
@@ -164,6 +206,9 @@ impl Child {
 
     async fn run(mut self) {
         debug!("Child({}): Launched.", self.id());
+        let addr = RefAddr::new(self.bcast.path().clone(), self.bcast.sender().clone());
+        self.bcast.system().tree().register(addr);
+
         loop {
             match poll!(&mut self.bcast.next()) {
                 // TODO: Err if started == true?
@@ -245,6 +290,12 @@ impl Child {
                 Poll::Pending => (),
             }
 
+            let tasks = self.tasks.clone();
+            if let Poll::Ready(()) = poll!(future::poll_fn(|ctx| tasks.poll_panicked(ctx))) {
+                warn!("Child({}): A task panicked.", self.id());
+                return self.faulted();
+            }
+
             pending!();
         }
     }
@@ -253,6 +304,20 @@ impl Child {
         let stack = self.stack();
         pool::spawn(self.run(), stack)
     }
+
+    pub(crate) fn launch_blocking(self) -> RecoverableHandle<()> {
+        let stack = self.stack();
+        // Elements are woken up by the other ones (which can run on
+        // the asynchronous pool) when they receive messages.
+        blocking::spawn_blocking_queued(self.run(), stack)
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        // The child exited or was cancelled.
+        self.tasks.cancel();
+    }
 }
 
 impl Future for Exec {
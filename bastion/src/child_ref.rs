@@ -3,8 +3,11 @@
 use crate::broadcast::Sender;
 use crate::context::BastionId;
 use crate::envelope::{Envelope, RefAddr};
+use crate::incarnation::Incarnation;
 use crate::message::{Answer, BastionMessage, Message};
+use crate::monitor::Terminated;
 use crate::path::BastionPath;
+use crate::system::BastionSystem;
 use std::cmp::{Eq, PartialEq};
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -16,11 +19,22 @@ pub struct ChildRef {
     id: BastionId,
     sender: Sender,
     path: Arc<BastionPath>,
+    system: BastionSystem,
 }
 
 impl ChildRef {
-    pub(crate) fn new(id: BastionId, sender: Sender, path: Arc<BastionPath>) -> ChildRef {
-        ChildRef { id, sender, path }
+    pub(crate) fn new(
+        id: BastionId,
+        sender: Sender,
+        path: Arc<BastionPath>,
+        system: BastionSystem,
+    ) -> ChildRef {
+        ChildRef {
+            id,
+            sender,
+            path,
+            system,
+        }
     }
 
     /// Returns the identifier of the children group element this
@@ -110,7 +124,7 @@ impl ChildRef {
     pub fn tell_anonymously<M: Message>(&self, msg: M) -> Result<(), M> {
         debug!("ChildRef({}): Telling message: {:?}", self.id(), msg);
         let msg = BastionMessage::tell(msg);
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         // FIXME: panics?
         self.send(env).map_err(|env| env.into_msg().unwrap())
     }
@@ -196,7 +210,7 @@ impl ChildRef {
     pub fn ask_anonymously<M: Message>(&self, msg: M) -> Result<Answer, M> {
         debug!("ChildRef({}): Asking message: {:?}", self.id(), msg);
         let (msg, answer) = BastionMessage::ask(msg);
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         // FIXME: panics?
         self.send(env).map_err(|env| env.into_msg().unwrap())?;
 
@@ -229,7 +243,7 @@ impl ChildRef {
     pub fn stop(&self) -> Result<(), ()> {
         debug!("ChildRef({}): Stopping.", self.id);
         let msg = BastionMessage::stop();
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         self.send(env).map_err(|_| ())
     }
 
@@ -259,7 +273,7 @@ impl ChildRef {
     pub fn kill(&self) -> Result<(), ()> {
         debug!("ChildRef({}): Killing.", self.id());
         let msg = BastionMessage::kill();
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         self.send(env).map_err(|_| ())
     }
 
@@ -268,6 +282,68 @@ impl ChildRef {
         RefAddr::new(self.path.clone(), self.sender.clone())
     }
 
+    /// Returns whether the child this `ChildRef` is referencing
+    /// is still running (ie. it hasn't stopped, been killed or
+    /// faulted yet).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    ///     # let child_ref = &children_ref.elems()[0];
+    /// assert!(child_ref.is_alive());
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    pub fn is_alive(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Returns a [`Terminated`] future which resolves to the
+    /// [`ExitReason`] of the child this `ChildRef` is
+    /// referencing once it terminated.
+    ///
+    /// If the child already terminated, the future
+    /// resolves to [`ExitReason::Unknown`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use futures::executor;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     # Bastion::start();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    ///     # let child_ref = &children_ref.elems()[0];
+    /// let terminated = child_ref.terminated();
+    /// child_ref.kill().expect("Couldn't send the message.");
+    ///
+    /// assert_eq!(executor::block_on(terminated), ExitReason::Killed);
+    /// assert!(!child_ref.is_alive());
+    ///     #
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Terminated`]: ../monitor/struct.Terminated.html
+    /// [`ExitReason`]: ../monitor/enum.ExitReason.html
+    /// [`ExitReason::Unknown`]: ../monitor/enum.ExitReason.html#variant.Unknown
+    pub fn terminated(&self) -> Terminated {
+        self.system.monitors().terminated(self.id(), &self.sender)
+    }
+
     pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
         trace!("ChildRef({}): Sending message: {:?}", self.id(), env);
         self.sender
@@ -282,6 +358,10 @@ impl ChildRef {
     pub(crate) fn path(&self) -> &Arc<BastionPath> {
         &self.path
     }
+
+    pub(crate) fn system(&self) -> &BastionSystem {
+        &self.system
+    }
 }
 
 impl PartialEq for ChildRef {
@@ -291,3 +371,172 @@ impl PartialEq for ChildRef {
 }
 
 impl Eq for ChildRef {}
+
+#[derive(Debug, Clone)]
+/// A "reference" to an element of a children group which, unlike
+/// [`ChildRef`], keeps referencing the element replacing it each
+/// time the group is restarted.
+///
+/// Messages sent while the group is restarting are kept until
+/// the new element is launched and then sent to it. They are
+/// dropped if the group stops instead.
+///
+/// A `StableChildRef` can be obtained using
+/// [`StableChildrenRef::elems`].
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// # fn main() {
+///     # Bastion::init();
+///     #
+/// let children_ref = Bastion::children(|children| {
+///     // ...
+///     # children
+/// }).expect("Couldn't create the children group.");
+///
+/// let child_ref: StableChildRef = children_ref.stable().elems()[0].clone();
+/// // This will still work after the group was restarted...
+/// child_ref.tell_anonymously("A message.").expect("Couldn't send the message.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`ChildRef`]: struct.ChildRef.html
+/// [`StableChildrenRef::elems`]: ../children_ref/struct.StableChildrenRef.html#method.elems
+pub struct StableChildRef {
+    incarnation: Arc<Incarnation>,
+    system: BastionSystem,
+}
+
+impl StableChildRef {
+    pub(crate) fn new(incarnation: Arc<Incarnation>, system: BastionSystem) -> Self {
+        StableChildRef {
+            incarnation,
+            system,
+        }
+    }
+
+    /// Returns a [`ChildRef`] referencing the element currently
+    /// running in place of the one this `StableChildRef` is
+    /// referencing, or `None` if it is restarting or stopped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    ///     # let child_ref = children_ref.stable().elems()[0].clone();
+    /// if let Some(current) = child_ref.current() {
+    ///     let child_id: &BastionId = current.id();
+    ///     // ...
+    /// }
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`ChildRef`]: struct.ChildRef.html
+    pub fn current(&self) -> Option<ChildRef> {
+        let addr = self.incarnation.current()?;
+        let path = addr.path().clone();
+        let id = path.id().clone();
+
+        Some(ChildRef::new(
+            id,
+            addr.sender().clone(),
+            path,
+            self.system.clone(),
+        ))
+    }
+
+    /// Sends a message to the element currently running in place
+    /// of the one this `StableChildRef` is referencing, or to the
+    /// one replacing it if the group is restarting.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)` if
+    /// the group stopped.
+    ///
+    /// See [`ChildRef::tell_anonymously`].
+    ///
+    /// # Argument
+    ///
+    /// * `msg` - The message to send.
+    ///
+    /// [`ChildRef::tell_anonymously`]: struct.ChildRef.html#method.tell_anonymously
+    pub fn tell_anonymously<M: Message>(&self, msg: M) -> Result<(), M> {
+        debug!("StableChildRef: Telling message: {:?}", msg);
+        let msg = BastionMessage::tell(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
+        // FIXME: panics?
+        self.send(env).map_err(|env| env.into_msg().unwrap())
+    }
+
+    /// Sends a message to the element currently running in place
+    /// of the one this `StableChildRef` is referencing, or to the
+    /// one replacing it if the group is restarting, allowing it
+    /// to answer.
+    ///
+    /// This method returns [`Answer`] if it succeeded, or
+    /// `Err(msg)` if the group stopped.
+    ///
+    /// See [`ChildRef::ask_anonymously`].
+    ///
+    /// # Argument
+    ///
+    /// * `msg` - The message to send.
+    ///
+    /// [`Answer`]: ../message/struct.Answer.html
+    /// [`ChildRef::ask_anonymously`]: struct.ChildRef.html#method.ask_anonymously
+    pub fn ask_anonymously<M: Message>(&self, msg: M) -> Result<Answer, M> {
+        debug!("StableChildRef: Asking message: {:?}", msg);
+        let (msg, answer) = BastionMessage::ask(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
+        // FIXME: panics?
+        self.send(env).map_err(|env| env.into_msg().unwrap())?;
+
+        Ok(answer)
+    }
+
+    /// Sends a message to the element currently running in place
+    /// of the one this `StableChildRef` is referencing to tell it
+    /// to stop its execution.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())`
+    /// otherwise.
+    pub fn stop(&self) -> Result<(), ()> {
+        debug!("StableChildRef: Stopping.");
+        let msg = BastionMessage::stop();
+        let env = Envelope::from_dead_letters(&self.system, msg);
+        self.send(env).map_err(|_| ())
+    }
+
+    /// Sends a message to the element currently running in place
+    /// of the one this `StableChildRef` is referencing to tell it
+    /// to suicide.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())`
+    /// otherwise.
+    pub fn kill(&self) -> Result<(), ()> {
+        debug!("StableChildRef: Killing.");
+        let msg = BastionMessage::kill();
+        let env = Envelope::from_dead_letters(&self.system, msg);
+        self.send(env).map_err(|_| ())
+    }
+
+    pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
+        trace!("StableChildRef: Sending message: {:?}", env);
+        self.incarnation.send(env)
+    }
+}
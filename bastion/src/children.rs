@@ -1,14 +1,19 @@
 //!
 //! Children are a group of child supervised under a supervisor
-use crate::broadcast::{Broadcast, Parent, Sender};
+use crate::actor::{self, Actor, Handlers};
+use crate::broadcast::{Broadcast, Parent};
 use crate::callbacks::Callbacks;
+use crate::checkpoint::{Checkpoint, StateInit};
 use crate::child::{Child, Init};
 use crate::child_ref::ChildRef;
 use crate::children_ref::ChildrenRef;
-use crate::context::{BastionContext, BastionId, ContextState};
-use crate::envelope::Envelope;
+use crate::context::{BastionContext, BastionId, ContextState, State, Tasks};
+use crate::envelope::{Envelope, RefAddr};
+use crate::incarnation::GroupIncarnation;
 use crate::message::BastionMessage;
+use crate::monitor::ExitReason;
 use crate::path::BastionPathElement;
+use crate::persistence::{Journal, Persistent, Storage};
 use bastion_executor::pool;
 use futures::pending;
 use futures::poll;
@@ -20,8 +25,11 @@ use qutex::Qutex;
 use std::fmt::Debug;
 use std::future::Future;
 use std::iter::FromIterator;
+use std::sync::Arc;
 use std::task::Poll;
 
+const DEFAULT_STASH_CAPACITY: usize = 1000;
+
 #[derive(Debug)]
 /// A children group that will contain a defined number of
 /// elements (set with [`with_redundancy`] or `1` by default)
@@ -71,7 +79,12 @@ use std::task::Poll;
 pub struct Children {
     bcast: Broadcast,
     // The currently launched elements of the group.
-    launched: FxHashMap<BastionId, (Sender, RecoverableHandle<()>)>,
+    launched: FxHashMap<BastionId, (ChildRef, RecoverableHandle<()>)>,
+    // The order in which the elements were launched (ie. their
+    // index). It is used to rebind the names registered by an
+    // element to the element replacing it when the group is
+    // restarted, and to list the elements in `ChildrenRef`.
+    order: Vec<BastionId>,
     // The closure returning the future that will be used by
     // every element of the group.
     init: Init,
@@ -84,26 +97,52 @@ pub struct Children {
     // is received.
     pre_start_msgs: Vec<Envelope>,
     started: bool,
+    // The group's and its elements' current incarnations, used
+    // by the stable references to the group and its elements.
+    incarnation: Arc<GroupIncarnation>,
+    // The closure creating the initial state of each element
+    // of the group (if any), and the last state saved by the
+    // element running in each slot of the group, which is kept
+    // when the group is restarted.
+    state_init: Option<StateInit>,
+    checkpoints: Vec<Checkpoint>,
+    // The maximum number of messages each element can stash.
+    stash_capacity: usize,
+    // Whether the elements of the group run on the blocking
+    // thread pool instead of the asynchronous one.
+    blocking: bool,
 }
 
 impl Children {
     pub(crate) fn new(bcast: Broadcast) -> Self {
         debug!("Children({}): Initializing.", bcast.id());
         let launched = FxHashMap::default();
+        let order = Vec::new();
         let init = Init::default();
         let redundancy = 1;
         let callbacks = Callbacks::new();
         let pre_start_msgs = Vec::new();
         let started = false;
+        let incarnation = Arc::new(GroupIncarnation::new());
+        let state_init = None;
+        let checkpoints = Vec::new();
+        let stash_capacity = DEFAULT_STASH_CAPACITY;
+        let blocking = false;
 
         Children {
             bcast,
             launched,
+            order,
             init,
             redundancy,
             callbacks,
             pre_start_msgs,
             started,
+            incarnation,
+            state_init,
+            checkpoints,
+            stash_capacity,
+            blocking,
         }
     }
 
@@ -122,6 +161,9 @@ impl Children {
         // TODO: stop or kill?
         self.kill().await;
 
+        // The name of the children group stays the same.
+        let mut bcast = bcast;
+        bcast.set_name(self.bcast.path().name().map(str::to_string));
         self.bcast = bcast;
         self.started = false;
 
@@ -172,6 +214,22 @@ impl Children {
         &self.callbacks
     }
 
+    // Called by the group's supervisor once it exited and won't be
+    // restarted, so that stable references stop buffering the
+    // messages sent to it.
+    pub(crate) fn exited_for_good(&self) {
+        debug!("Children({}): Exited for good.", self.id());
+        // The names and subscriptions of the elements were kept in
+        // case the group was restarted, which won't happen anymore.
+        let system = self.bcast.system();
+        for id in &self.order {
+            system.registry().unregister_child(id);
+            system.topics().unsubscribe_child(id);
+        }
+
+        self.incarnation.stopped();
+    }
+
     pub(crate) fn as_ref(&self) -> ChildrenRef {
         trace!(
             "Children({}): Creating new ChildrenRef({}).",
@@ -183,15 +241,20 @@ impl Children {
         let sender = self.bcast.sender().clone();
         let path = self.bcast.path().clone();
 
+        // The elements are listed in the order of their index.
         let mut children = Vec::with_capacity(self.launched.len());
-        for (id, (sender, _)) in &self.launched {
-            trace!("Children({}): Creating new ChildRef({}).", self.id(), id);
-            // TODO: clone or ref?
-            let child = ChildRef::new(id.clone(), sender.clone(), path.clone());
-            children.push(child);
+        for id in &self.order {
+            if let Some((child, _)) = self.launched.get(id) {
+                trace!("Children({}): Creating new ChildRef({}).", self.id(), id);
+                // TODO: clone or ref?
+                children.push(child.clone());
+            }
         }
 
-        ChildrenRef::new(id, sender, path, children)
+        let incarnation = self.incarnation.clone();
+        let system = self.bcast.system().clone();
+
+        ChildrenRef::new(id, sender, path, children, incarnation, system)
     }
 
     /// Sets the closure taking a [`BastionContext`] and returning a
@@ -243,10 +306,380 @@ impl Children {
         F: Future<Output = Result<(), ()>> + Send + 'static,
     {
         trace!("Children({}): Setting exec closure.", self.id());
+        self.clear_state();
         self.init = Init::new(init);
         self
     }
 
+    /// Sets the closure taking the index of an element of this
+    /// children group and a [`BastionContext`], and returning the
+    /// [`Future`] that this element will run.
+    ///
+    /// An element's index is in `0..redundancy` (see
+    /// [`with_redundancy`]) and stays the same when it is
+    /// restarted, so that the element replacing it keeps handling
+    /// the same part of the work (eg. the same partition).
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure taking the index of an element and
+    ///   its [`BastionContext`], and returning the [`Future`] it
+    ///   will run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// let partitions = vec!["a-h", "i-p", "q-z"];
+    ///
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_redundancy(partitions.len())
+    ///         .with_exec_indexed(move |index, ctx: BastionContext| {
+    ///             // Each element handles its own partition...
+    ///             let partition = partitions[index];
+    ///             async move {
+    ///                 println!("{}: handling {}", ctx.current().id(), partition);
+    ///                 // ...
+    ///
+    ///                 Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionContext`]: ../context/struct.BastionContext.html
+    /// [`Future`]: https://doc.rust-lang.org/std/future/trait.Future.html
+    /// [`with_redundancy`]: #method.with_redundancy
+    pub fn with_exec_indexed<I, F>(mut self, init: I) -> Self
+    where
+        I: Fn(usize, BastionContext) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        trace!("Children({}): Setting indexed exec closure.", self.id());
+        self.clear_state();
+        self.init = Init::new(move |ctx: BastionContext| init(ctx.index(), ctx));
+        self
+    }
+
+    /// Sets the closure returning the [`Actor`] that each element
+    /// of this children group will run, instead of the future
+    /// returned by the closure passed to [`with_exec`].
+    ///
+    /// The closure is called each time an element is started or
+    /// restarted.
+    ///
+    /// # Arguments
+    ///
+    /// * `factory` - The closure returning a new actor.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// struct Greeter;
+    ///
+    /// impl Actor for Greeter {
+    ///     fn handlers(handlers: Handlers<Self>) -> Handlers<Self> {
+    ///         handlers.handle::<String>()
+    ///     }
+    /// }
+    ///
+    /// impl Handler<String> for Greeter {
+    ///     fn handle(&mut self, _: &BastionContext, name: String, _: Request) -> Result<(), ()> {
+    ///         println!("Hello, {}!", name);
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_actor(|| Greeter)
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Actor`]: ../actor/trait.Actor.html
+    /// [`with_exec`]: #method.with_exec
+    pub fn with_actor<A, F>(mut self, factory: F) -> Self
+    where
+        A: Actor,
+        F: Fn() -> A + Send + Sync + 'static,
+    {
+        trace!("Children({}): Setting actor factory.", self.id());
+        self.clear_state();
+        let handlers = Arc::new(A::handlers(Handlers::new()));
+
+        self.init = Init::new(move |ctx: BastionContext| {
+            let actor = factory();
+            actor::run(actor, ctx, handlers.clone())
+        });
+        self
+    }
+
+    /// Sets the closure returning the initial state of this
+    /// children group's elements, and the closure taking a
+    /// [`BastionContext`] and the [`State`] of an element of this
+    /// children group, and returning the future that the element
+    /// will run.
+    ///
+    /// Unlike the future returned by `exec`, the state isn't
+    /// created again when the group is restarted: `init` is only
+    /// called once per element of the group and the element
+    /// replacing another one is handed the last state it saved
+    /// using [`State::save`] instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure returning the initial state of
+    ///   an element of this children group.
+    /// * `exec` - The closure taking a [`BastionContext`] and a
+    ///   [`State`], and returning the future that will be run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_state(
+    ///         || 0usize,
+    ///         |ctx: BastionContext, state: State<usize>| {
+    ///             async move {
+    ///                 // The last saved count (or `0`)...
+    ///                 let mut count = state.get();
+    ///                 loop {
+    ///                     ctx.recv().await?;
+    ///                     count += 1;
+    ///                     // ...which will be kept if the element
+    ///                     // is restarted.
+    ///                     state.save(count);
+    ///                 }
+    ///             }
+    ///         },
+    ///     )
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionContext`]: ../context/struct.BastionContext.html
+    /// [`State`]: ../context/struct.State.html
+    /// [`State::save`]: ../context/struct.State.html#method.save
+    pub fn with_state<S, I, E, F>(mut self, init: I, exec: E) -> Self
+    where
+        S: Clone + Send + 'static,
+        I: Fn() -> S + Send + Sync + 'static,
+        E: Fn(BastionContext, State<S>) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        trace!("Children({}): Setting state and exec closures.", self.id());
+        self.state_init = Some(StateInit::new(init));
+        self.checkpoints.clear();
+
+        self.init = Init::new(move |ctx: BastionContext| {
+            // FIXME: panics? (the checkpoint is always created
+            //      when the group has a state)
+            let state = State::new(ctx.checkpoint().unwrap());
+            exec(ctx, state)
+        });
+        self
+    }
+
+    // Forgets the state set using `with_state` (along with the one
+    // saved by each element), which isn't used anymore once the
+    // elements run another closure or an actor.
+    fn clear_state(&mut self) {
+        self.state_init = None;
+        self.checkpoints.clear();
+    }
+
+    /// Sets the closure taking a [`BastionContext`] and the
+    /// [`Journal`] of an element of this children group, and
+    /// returning the future that the element will run.
+    ///
+    /// Before the closure is called, the element's state is
+    /// recovered by replaying the events persisted to its journal
+    /// (see [`Journal::recover`]), each time the element is
+    /// started or restarted. The element faults if the journal
+    /// can't be recovered.
+    ///
+    /// The persistence identifier of the journal of each element
+    /// is `id` followed by a `.` and the element's index in the
+    /// group (eg. `"orders.0"`).
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The persistence identifier of this children group.
+    /// * `storage` - The storage of the elements' journals.
+    /// * `init` - The closure taking a [`BastionContext`] and a
+    ///   [`Journal`], and returning the future that will be run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// use bastion::persistence::{FileStorage, Journal, Persistent};
+    /// # use std::convert::TryInto;
+    ///
+    /// #[derive(Default)]
+    /// struct Orders(Vec<u64>);
+    ///
+    /// impl Persistent for Orders {
+    ///     type Event = u64;
+    ///
+    ///     fn apply(&mut self, order: &u64) {
+    ///         self.0.push(*order);
+    ///     }
+    ///     # fn encode_event(order: &u64) -> Vec<u8> { order.to_le_bytes().to_vec() }
+    ///     # fn decode_event(bytes: &[u8]) -> Option<u64> {
+    ///     #     Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    ///     # }
+    ///     // ...
+    /// }
+    ///
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// let dir = std::env::temp_dir().join("bastion-orders");
+    /// let storage = FileStorage::new(dir).expect("Couldn't create the storage.");
+    ///
+    /// Bastion::children(|children| {
+    ///     children.with_persistence(
+    ///         "orders",
+    ///         storage,
+    ///         |ctx: BastionContext, mut journal: Journal<Orders>| {
+    ///             async move {
+    ///                 loop {
+    ///                     msg! { ctx.recv().await?,
+    ///                         order: u64 => {
+    ///                             // The order is persisted before being
+    ///                             // applied...
+    ///                             journal.persist(order).await.map_err(|_| ())?;
+    ///                         };
+    ///                         _: _ => ();
+    ///                     }
+    ///                 }
+    ///             }
+    ///         },
+    ///     )
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionContext`]: ../context/struct.BastionContext.html
+    /// [`Journal`]: ../persistence/struct.Journal.html
+    /// [`Journal::recover`]: ../persistence/struct.Journal.html#method.recover
+    pub fn with_persistence<P, S, I, F>(mut self, id: &str, storage: S, init: I) -> Self
+    where
+        P: Persistent,
+        S: Storage,
+        I: Fn(BastionContext, Journal<P>) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        trace!(
+            "Children({}): Setting persistent exec closure: {}",
+            self.id(),
+            id
+        );
+        self.clear_state();
+        let id = id.to_string();
+        let storage: Arc<dyn Storage> = Arc::new(storage);
+        let init = Arc::new(init);
+
+        self.init = Init::new(move |ctx: BastionContext| {
+            let id = format!("{}.{}", id, ctx.index());
+            let storage = storage.clone();
+            let init = init.clone();
+
+            async move {
+                let journal = match Journal::recover_with(&id, storage).await {
+                    Ok(journal) => journal,
+                    Err(err) => {
+                        error!("Journal({}): Couldn't recover: {}", id, err);
+                        return Err(());
+                    }
+                };
+
+                init(ctx, journal).await
+            }
+        });
+        self
+    }
+
+    /// Sets the name of this children group, which will be used
+    /// instead of its identifier when its path (or the path of
+    /// one of its elements) is formatted using
+    /// [`BastionPath::named`].
+    ///
+    /// Unlike the group's identifier, its name stays the same
+    /// when it is restarted. Note that the group's elements are
+    /// always named after their index in the group.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the children group, which shouldn't
+    ///   contain any `/`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_name("workers")
+    ///         .with_exec(|ctx: BastionContext| {
+    ///             async move {
+    ///                 // eg. "/<uuid>/workers/0"
+    ///                 println!("{}", ctx.signature().path().named());
+    ///                 Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionPath::named`]: ../path/struct.BastionPath.html#method.named
+    pub fn with_name(mut self, name: &str) -> Self {
+        trace!("Children({}): Setting name: {:?}", self.id(), name);
+        self.bcast.set_name(Some(name.to_string()));
+        self
+    }
+
     /// Sets the number of number of elements this children group will
     /// contain. Each element will call the closure passed in
     /// [`with_exec`] and run the returned future until it stops,
@@ -293,6 +726,103 @@ impl Children {
         self
     }
 
+    /// Sets the maximum number of messages each element of this
+    /// children group can stash at the same time using
+    /// [`BastionContext::stash`].
+    ///
+    /// The default stash capacity is `1000`.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of messages an element
+    ///   can stash.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_stash_capacity(16)
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionContext::stash`]: ../context/struct.BastionContext.html#method.stash
+    pub fn with_stash_capacity(mut self, capacity: usize) -> Self {
+        trace!(
+            "Children({}): Setting stash capacity: {}",
+            self.id(),
+            capacity
+        );
+        self.stash_capacity = capacity;
+        self
+    }
+
+    /// Makes the elements of this children group run on the
+    /// executor's blocking thread pool instead of on its
+    /// asynchronous one, so that they can block (e.g. while doing
+    /// synchronous I/O) without stalling the other elements of the
+    /// system.
+    ///
+    /// The elements are still supervised, receive messages and are
+    /// restarted like the elements of any other children group.
+    /// Sending them a message never blocks the sender, even when
+    /// every thread of the blocking thread pool is busy.
+    /// Note that they don't run on the deterministic scheduler
+    /// when it is enabled (see [`Config::with_deterministic_seed`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use std::io::Read;
+    /// # use std::net::TcpStream;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_blocking()
+    ///         .with_exec(|ctx: BastionContext| {
+    ///             async move {
+    ///                 loop {
+    ///                     msg! { ctx.recv().await?,
+    ///                         stream: TcpStream => {
+    ///                             let mut stream = stream;
+    ///                             let mut buf = vec![];
+    ///                             // Blocks the current thread of
+    ///                             // the blocking thread pool...
+    ///                             stream.read_to_end(&mut buf).ok();
+    ///                         };
+    ///                         _: _ => ();
+    ///                     }
+    ///                 }
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Config::with_deterministic_seed`]: ../struct.Config.html#method.with_deterministic_seed
+    pub fn with_blocking(mut self) -> Self {
+        trace!("Children({}): Running on the blocking pool.", self.id());
+        self.blocking = true;
+        self
+    }
+
     /// Sets the callbacks that will get called at this children group's
     /// different lifecycle events.
     ///
@@ -364,10 +894,12 @@ impl Children {
         debug!("Children({}): Killing.", self.id());
         self.bcast.kill_children();
 
+        let mut killed = Vec::with_capacity(self.launched.len());
         let mut children = FuturesOrdered::new();
-        for (_, (_, launched)) in self.launched.drain() {
+        for (id, (child, launched)) in self.launched.drain() {
             launched.cancel();
 
+            killed.push((id, child));
             children.push(launched);
         }
 
@@ -378,16 +910,52 @@ impl Children {
                 }
             })
             .await;
+
+        // NOTE: the children might have been cancelled before
+        //      handling the kill message and notifying their
+        //      watchers.
+        let system = self.bcast.system();
+        for (id, child) in killed {
+            system
+                .monitors()
+                .exited(&id, child.addr(), ExitReason::Killed);
+            system.tree().unregister(child.path());
+        }
     }
 
     fn stopped(&mut self) {
         debug!("Children({}): Stopped.", self.id());
         self.bcast.stopped();
+        self.exited(ExitReason::Stopped);
+    }
+
+    fn killed(&mut self) {
+        debug!("Children({}): Killed.", self.id());
+        self.bcast.stopped();
+        self.exited(ExitReason::Killed);
+    }
+
+    fn unregister_elems(&mut self) {
+        debug!("Children({}): Unregistering elements.", self.id());
+        let system = self.bcast.system();
+        for id in self.order.drain(..) {
+            system.registry().unregister_child(&id);
+            system.topics().unsubscribe_child(&id);
+        }
+
+        self.incarnation.stopped();
     }
 
     fn faulted(&mut self) {
         debug!("Children({}): Faulted.", self.id());
         self.bcast.faulted();
+        self.exited(ExitReason::Faulted);
+    }
+
+    fn exited(&self, reason: ExitReason) {
+        let system = self.bcast.system();
+        system.monitors().notify_terminated(self.id(), reason);
+        system.tree().unregister(self.bcast.path());
     }
 
     async fn handle(&mut self, env: Envelope) -> Result<(), ()> {
@@ -401,6 +969,7 @@ impl Children {
                 ..
             } => {
                 self.stop().await;
+                self.unregister_elems();
                 self.stopped();
 
                 return Err(());
@@ -409,8 +978,12 @@ impl Children {
                 msg: BastionMessage::Kill,
                 ..
             } => {
+                // NOTE: the elements' names and subscriptions are kept
+                //      because supervisors kill the groups they are about
+                //      to restart (they are dropped if the group exits
+                //      for good).
                 self.kill().await;
-                self.stopped();
+                self.killed();
 
                 return Err(());
             }
@@ -448,6 +1021,7 @@ impl Children {
                 if self.launched.contains_key(&id) {
                     debug!("Children({}): Child({}) stopped.", self.id(), id);
                     self.stop().await;
+                    self.unregister_elems();
                     self.stopped();
 
                     return Err(());
@@ -473,6 +1047,10 @@ impl Children {
 
     async fn run(mut self) -> Self {
         debug!("Children({}): Launched.", self.id());
+        let addr = RefAddr::new(self.bcast.path().clone(), self.bcast.sender().clone());
+        self.bcast.system().tree().register(addr.clone());
+        self.incarnation.group().update(addr);
+
         loop {
             for (_, launched) in self.launched.values_mut() {
                 let _ = poll!(launched);
@@ -540,23 +1118,58 @@ impl Children {
 
     pub(crate) fn launch_elems(&mut self) {
         debug!("Children({}): Launching elements.", self.id());
-        for _ in 0..self.redundancy {
-            let parent = Parent::children(self.as_ref());
-            let bcast = Broadcast::new(parent, BastionPathElement::Child(BastionId::new()));
+        let previous = self.order.drain(..).collect::<Vec<_>>();
+        let children = self.as_ref();
+        for index in 0..self.redundancy {
+            let parent = Parent::children(children.clone());
+            let mut bcast = Broadcast::new(parent, BastionPathElement::Child(BastionId::new()));
+            // Elements are named after their index, which stays
+            // the same when the group is restarted.
+            bcast.set_name(Some(index.to_string()));
 
             // TODO: clone or ref?
             let id = bcast.id().clone();
             let sender = bcast.sender().clone();
             let path = bcast.path().clone();
-            let child_ref = ChildRef::new(id.clone(), sender.clone(), path);
+            let system = self.bcast.system().clone();
+            let child_ref = ChildRef::new(id.clone(), sender, path, system);
+
+            // The names registered by the element this one replaces
+            // (if the group was restarted) now refer to it, while its
+            // subscriptions are dropped (they will be made again by
+            // the new element's future if needed).
+            if let Some(previous) = previous.get(index) {
+                let system = self.bcast.system();
+                system.registry().rebind(previous, &child_ref);
+                system.topics().unsubscribe_child(previous);
+            }
 
-            let children = self.as_ref();
             let supervisor = self.bcast.parent().clone().into_supervisor();
 
-            let state = ContextState::new();
+            let state = ContextState::new(self.stash_capacity, self.bcast.system().clone());
             let state = Qutex::new(state);
 
-            let ctx = BastionContext::new(id, child_ref, children, supervisor, state.clone());
+            // The state is only created once per slot, and then
+            // kept (with its last saved value) across restarts.
+            if let Some(state_init) = &self.state_init {
+                if self.checkpoints.len() <= index {
+                    self.checkpoints.push(state_init.checkpoint());
+                }
+            }
+            let checkpoint = self.checkpoints.get(index).cloned();
+            let tasks = Tasks::default();
+
+            let ctx = BastionContext::new(
+                id,
+                child_ref.clone(),
+                children.clone(),
+                supervisor,
+                state.clone(),
+                checkpoint,
+                index,
+                self.redundancy,
+                tasks.clone(),
+            );
             let exec = (self.init.0)(ctx);
 
             self.bcast.register(&bcast);
@@ -566,12 +1179,18 @@ impl Children {
                 self.id(),
                 bcast.id()
             );
-            let child = Child::new(exec, bcast, state);
+            let child = Child::new(exec, bcast, state, tasks);
             debug!("Children({}): Launching Child({}).", self.id(), child.id());
             let id = child.id().clone();
-            let launched = child.launch();
+            let launched = if self.blocking {
+                child.launch_blocking()
+            } else {
+                child.launch()
+            };
 
-            self.launched.insert(id, (sender, launched));
+            self.incarnation.elem(index).update(child_ref.addr());
+            self.launched.insert(id.clone(), (child_ref, launched));
+            self.order.push(id);
         }
     }
 
@@ -1,11 +1,14 @@
 //!
 //! Allows users to communicate with children through the mailboxes.
 use crate::broadcast::Sender;
-use crate::child_ref::ChildRef;
+use crate::child_ref::{ChildRef, StableChildRef};
 use crate::context::BastionId;
 use crate::envelope::Envelope;
+use crate::incarnation::GroupIncarnation;
 use crate::message::{BastionMessage, Message};
+use crate::monitor::Terminated;
 use crate::path::BastionPath;
+use crate::system::BastionSystem;
 use std::cmp::{Eq, PartialEq};
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -18,6 +21,8 @@ pub struct ChildrenRef {
     sender: Sender,
     path: Arc<BastionPath>,
     children: Vec<ChildRef>,
+    incarnation: Arc<GroupIncarnation>,
+    system: BastionSystem,
 }
 
 impl ChildrenRef {
@@ -26,12 +31,16 @@ impl ChildrenRef {
         sender: Sender,
         path: Arc<BastionPath>,
         children: Vec<ChildRef>,
+        incarnation: Arc<GroupIncarnation>,
+        system: BastionSystem,
     ) -> Self {
         ChildrenRef {
             id,
             sender,
             path,
             children,
+            incarnation,
+            system,
         }
     }
 
@@ -66,7 +75,8 @@ impl ChildrenRef {
     }
 
     /// Returns a list of [`ChildRef`] referencing the elements
-    /// of the children group this `ChildrenRef` is referencing.
+    /// of the children group this `ChildrenRef` is referencing,
+    /// in the order of their index (see [`BastionContext::index`]).
     ///
     /// # Example
     ///
@@ -86,10 +96,38 @@ impl ChildrenRef {
     /// ```
     ///
     /// [`ChildRef`]: children/struct.ChildRef.html
+    /// [`BastionContext::index`]: ../context/struct.BastionContext.html#method.index
     pub fn elems(&self) -> &[ChildRef] {
         &self.children
     }
 
+    /// Returns a [`StableChildrenRef`] referencing the children
+    /// group this `ChildrenRef` is referencing, which (unlike
+    /// this `ChildrenRef`) stays valid when the group is
+    /// restarted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    /// let stable: StableChildrenRef = children_ref.stable();
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`StableChildrenRef`]: struct.StableChildrenRef.html
+    pub fn stable(&self) -> StableChildrenRef {
+        StableChildrenRef::new(self.incarnation.clone(), self.system.clone())
+    }
+
     /// Sends a message to the children group this `ChildrenRef`
     /// is referencing which will then send it to all of its
     /// elements.
@@ -142,16 +180,16 @@ impl ChildrenRef {
     /// ```
     ///
     /// [`elems`]: #method.elems
-    pub fn broadcast<M: Message>(&self, msg: M) -> Result<(), M> {
+    pub fn broadcast<M: Message + Sync>(&self, msg: M) -> Result<(), M> {
         debug!(
             "ChildrenRef({}): Broadcasting message: {:?}",
             self.id(),
             msg
         );
         let msg = BastionMessage::broadcast(msg);
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         // FIXME: panics?
-        self.send(env).map_err(|err| err.into_msg().unwrap())
+        self.send(env).map_err(|err| err.into_broadcast().unwrap())
     }
 
     /// Sends a message to the children group this `ChildrenRef`
@@ -180,7 +218,7 @@ impl ChildrenRef {
     pub fn stop(&self) -> Result<(), ()> {
         debug!("ChildrenRef({}): Stopping.", self.id());
         let msg = BastionMessage::stop();
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         self.send(env).map_err(|_| ())
     }
 
@@ -210,10 +248,70 @@ impl ChildrenRef {
     pub fn kill(&self) -> Result<(), ()> {
         debug!("ChildrenRef({}): Killing.", self.id());
         let msg = BastionMessage::kill();
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         self.send(env).map_err(|_| ())
     }
 
+    /// Returns whether the children group this `ChildrenRef` is referencing
+    /// is still running (ie. it hasn't stopped, been killed or
+    /// faulted yet).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    /// assert!(children_ref.is_alive());
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    pub fn is_alive(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Returns a [`Terminated`] future which resolves to the
+    /// [`ExitReason`] of the children group this `ChildrenRef` is
+    /// referencing once it terminated.
+    ///
+    /// If the children group already terminated, the future
+    /// resolves to [`ExitReason::Unknown`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use futures::executor;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     # Bastion::start();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    /// let terminated = children_ref.terminated();
+    /// children_ref.stop().expect("Couldn't send the message.");
+    ///
+    /// assert_eq!(executor::block_on(terminated), ExitReason::Stopped);
+    /// assert!(!children_ref.is_alive());
+    ///     #
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Terminated`]: ../monitor/struct.Terminated.html
+    /// [`ExitReason`]: ../monitor/enum.ExitReason.html
+    /// [`ExitReason::Unknown`]: ../monitor/enum.ExitReason.html#variant.Unknown
+    pub fn terminated(&self) -> Terminated {
+        self.system.monitors().terminated(self.id(), &self.sender)
+    }
+
     pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
         trace!("ChildrenRef({}): Sending message: {:?}", self.id(), env);
         self.sender
@@ -228,6 +326,10 @@ impl ChildrenRef {
     pub(crate) fn sender(&self) -> &Sender {
         &self.sender
     }
+
+    pub(crate) fn system(&self) -> &BastionSystem {
+        &self.system
+    }
 }
 
 impl PartialEq for ChildrenRef {
@@ -237,3 +339,191 @@ impl PartialEq for ChildrenRef {
 }
 
 impl Eq for ChildrenRef {}
+
+#[derive(Debug, Clone)]
+/// A "reference" to a children group which, unlike
+/// [`ChildrenRef`], keeps referencing the group that replaces it
+/// each time it is restarted by its supervisor.
+///
+/// Messages sent while the group is restarting are kept until
+/// the new group is launched and then sent to it. They are
+/// dropped if the group stops instead.
+///
+/// A `StableChildrenRef` can be obtained using
+/// [`ChildrenRef::stable`].
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// # fn main() {
+///     # Bastion::init();
+///     #
+/// let children_ref = Bastion::children(|children| {
+///     // ...
+///     # children
+/// }).expect("Couldn't create the children group.");
+///
+/// let stable = children_ref.stable();
+/// // This will still work after the group was restarted...
+/// stable.broadcast("A message.").expect("Couldn't send the message.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`ChildrenRef`]: struct.ChildrenRef.html
+/// [`ChildrenRef::stable`]: struct.ChildrenRef.html#method.stable
+pub struct StableChildrenRef {
+    incarnation: Arc<GroupIncarnation>,
+    system: BastionSystem,
+}
+
+impl StableChildrenRef {
+    pub(crate) fn new(incarnation: Arc<GroupIncarnation>, system: BastionSystem) -> Self {
+        StableChildrenRef {
+            incarnation,
+            system,
+        }
+    }
+
+    /// Returns a [`ChildrenRef`] referencing the children group
+    /// currently running in place of the one this
+    /// `StableChildrenRef` is referencing, or `None` if it is
+    /// restarting or stopped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    ///     # let stable = children_ref.stable();
+    ///     # Bastion::start();
+    /// if let Some(current) = stable.current() {
+    ///     let children_id: &BastionId = current.id();
+    ///     // ...
+    /// }
+    ///     #
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`ChildrenRef`]: struct.ChildrenRef.html
+    pub fn current(&self) -> Option<ChildrenRef> {
+        let addr = self.incarnation.group().current()?;
+        let path = addr.path().clone();
+        let id = path.id().clone();
+        let children = self
+            .elems()
+            .iter()
+            .filter_map(StableChildRef::current)
+            .collect();
+        let incarnation = self.incarnation.clone();
+        let system = self.system.clone();
+
+        Some(ChildrenRef::new(
+            id,
+            addr.sender().clone(),
+            path,
+            children,
+            incarnation,
+            system,
+        ))
+    }
+
+    /// Returns a list of [`StableChildRef`] referencing the
+    /// elements of the children group this `StableChildrenRef`
+    /// is referencing, in the order of their index in the group.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let children_ref = Bastion::children(|children| children).unwrap();
+    ///     # let stable = children_ref.stable();
+    /// let elems: Vec<StableChildRef> = stable.elems();
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`StableChildRef`]: ../child_ref/struct.StableChildRef.html
+    pub fn elems(&self) -> Vec<StableChildRef> {
+        self.incarnation
+            .elems()
+            .into_iter()
+            .map(|incarnation| StableChildRef::new(incarnation, self.system.clone()))
+            .collect()
+    }
+
+    /// Sends a message to the children group currently running
+    /// in place of the one this `StableChildrenRef` is
+    /// referencing, or to the one replacing it if it is
+    /// restarting, which will then send it to all of its
+    /// elements.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)` if
+    /// the group stopped.
+    ///
+    /// See [`ChildrenRef::broadcast`].
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to send.
+    ///
+    /// [`ChildrenRef::broadcast`]: struct.ChildrenRef.html#method.broadcast
+    pub fn broadcast<M: Message + Sync>(&self, msg: M) -> Result<(), M> {
+        debug!("StableChildrenRef: Broadcasting message: {:?}", msg);
+        let msg = BastionMessage::broadcast(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
+        // FIXME: panics?
+        self.send(env).map_err(|err| err.into_broadcast().unwrap())
+    }
+
+    /// Sends a message to the children group currently running
+    /// in place of the one this `StableChildrenRef` is
+    /// referencing to tell it to stop all of its running
+    /// elements.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())`
+    /// otherwise.
+    pub fn stop(&self) -> Result<(), ()> {
+        debug!("StableChildrenRef: Stopping.");
+        let msg = BastionMessage::stop();
+        let env = Envelope::from_dead_letters(&self.system, msg);
+        self.send(env).map_err(|_| ())
+    }
+
+    /// Sends a message to the children group currently running
+    /// in place of the one this `StableChildrenRef` is
+    /// referencing to tell it to kill all of its running
+    /// elements.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())`
+    /// otherwise.
+    pub fn kill(&self) -> Result<(), ()> {
+        debug!("StableChildrenRef: Killing.");
+        let msg = BastionMessage::kill();
+        let env = Envelope::from_dead_letters(&self.system, msg);
+        self.send(env).map_err(|_| ())
+    }
+
+    pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
+        trace!("StableChildrenRef: Sending message: {:?}", env);
+        self.incarnation.group().send(env)
+    }
+}
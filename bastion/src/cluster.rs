@@ -0,0 +1,558 @@
+//!
+//! A membership service keeping track of the nodes of a cluster,
+//! built on top of the [`transport`] module.
+//!
+//! Nodes join a cluster through the addresses of seed nodes and
+//! periodically gossip their view of the cluster to the members
+//! they know of. Each node's entry in those views contains a
+//! heartbeat counter that it increments before gossiping, and
+//! nodes whose counter didn't increase for longer than a timeout
+//! are considered unreachable.
+//!
+//! Changes in the membership of the cluster are published as
+//! [`MemberUp`] and [`MemberDown`] messages to the
+//! [`MEMBER_UP`] and [`MEMBER_DOWN`] topics, which children can
+//! subscribe to (see [`BastionContext::subscribe`]).
+//!
+//! The membership service of a node runs as a supervisor (named
+//! `cluster@<node>`) supervising a children group (named
+//! `membership`), so it is restarted if it faults.
+//!
+//! This module is only available with the `distributed` feature.
+//!
+//! [`transport`]: ../transport/index.html
+//! [`MemberUp`]: struct.MemberUp.html
+//! [`MemberDown`]: struct.MemberDown.html
+//! [`MEMBER_UP`]: constant.MEMBER_UP.html
+//! [`MEMBER_DOWN`]: constant.MEMBER_DOWN.html
+//! [`BastionContext::subscribe`]: ../context/struct.BastionContext.html#method.subscribe
+use crate::context::BastionContext;
+use crate::path::BastionPath;
+use crate::supervisor::SupervisorRef;
+use crate::transport::{Node, NodeId};
+use bastion_executor::timer;
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The topic [`MemberUp`] messages are published to.
+///
+/// [`MemberUp`]: struct.MemberUp.html
+pub const MEMBER_UP: &str = "cluster.member.up";
+/// The topic [`MemberDown`] messages are published to.
+///
+/// [`MemberDown`]: struct.MemberDown.html
+pub const MEMBER_DOWN: &str = "cluster.member.down";
+
+// The tag `Gossip` messages are registered with (see `Node::listen`).
+pub(crate) const GOSSIP_TAG: &str = "bastion.cluster.gossip";
+
+const MEMBERSHIP_NAME: &str = "membership";
+
+#[derive(Debug, Clone)]
+/// A builder for the membership service of a node, which joins
+/// a cluster when [`join`] is called.
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::cluster::{Cluster, MemberDown, MemberUp};
+/// use bastion::codec::Codecs;
+/// use bastion::prelude::*;
+/// use bastion::transport::{Node, NodeId};
+/// use std::time::Duration;
+///
+/// # fn main() -> std::io::Result<()> {
+///     # Bastion::init();
+///     #
+/// let node = Node::listen("127.0.0.1:0", Codecs::new())?;
+/// let seed: NodeId = "127.0.0.1:4000".parse().unwrap();
+///
+/// let cluster = Cluster::new(&node)
+///     .with_seeds(&[seed])
+///     .with_gossip_interval(Duration::from_millis(500))
+///     .join()
+///     .expect("Couldn't join the cluster.");
+///
+/// Bastion::children(|children| {
+///     children.with_exec(|ctx: BastionContext| {
+///         async move {
+///             ctx.subscribe("cluster.member.*").expect("Couldn't subscribe.");
+///             loop {
+///                 msg! { ctx.recv().await?,
+///                     ref up: MemberUp => println!("{} is up.", up.node());
+///                     ref down: MemberDown => println!("{} is down.", down.node());
+///                     _: _ => ();
+///                 }
+///             }
+///         }
+///     })
+/// }).expect("Couldn't create the children group.");
+///     #
+///     # Bastion::start();
+///     # cluster.leave().unwrap();
+///     # node.shutdown();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+///     # Ok(())
+/// # }
+/// ```
+///
+/// [`join`]: #method.join
+pub struct Cluster {
+    node: Node,
+    seeds: Vec<NodeId>,
+    gossip_interval: Duration,
+    failure_timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+/// A "reference" to the membership service of a node, allowing
+/// to get its view of the cluster or to make it leave it.
+pub struct ClusterRef {
+    node: NodeId,
+    supervisor: SupervisorRef,
+    membership: Arc<Mutex<Membership>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The message published to the [`MEMBER_UP`] topic when a node
+/// joins the cluster, or becomes reachable again.
+///
+/// [`MEMBER_UP`]: constant.MEMBER_UP.html
+pub struct MemberUp {
+    node: NodeId,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The message published to the [`MEMBER_DOWN`] topic when a
+/// node becomes unreachable (ie. its heartbeats weren't received
+/// for longer than the failure timeout).
+///
+/// [`MEMBER_DOWN`]: constant.MEMBER_DOWN.html
+pub struct MemberDown {
+    node: NodeId,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+// A node's view of the cluster, containing its own heartbeat
+// and the ones of the nodes it considers up.
+pub(crate) struct Gossip {
+    members: Vec<(NodeId, Heartbeat)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct Heartbeat {
+    // The time the node started at, so that the heartbeats of a
+    // restarted node are newer than the ones it sent before.
+    generation: u64,
+    counter: u64,
+}
+
+#[derive(Debug)]
+// Sent to the membership service to make it gossip.
+struct Tick;
+
+#[derive(Debug)]
+struct Membership {
+    node: NodeId,
+    heartbeat: Heartbeat,
+    members: FxHashMap<NodeId, Member>,
+}
+
+#[derive(Debug)]
+struct Member {
+    heartbeat: Heartbeat,
+    // When the member's heartbeat last increased, according to the
+    // executor's clock.
+    updated: Duration,
+    up: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Up(NodeId),
+    Down(NodeId),
+}
+
+impl Cluster {
+    /// Creates a new membership service for the given node,
+    /// which gossips every second and considers nodes down if
+    /// their heartbeats weren't received for five seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node joining the cluster.
+    pub fn new(node: &Node) -> Self {
+        Cluster {
+            node: node.clone(),
+            seeds: vec![],
+            gossip_interval: Duration::from_secs(1),
+            failure_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Sets the nodes contacted to join the cluster, which don't
+    /// need to be up yet (eg. the first node of a cluster can
+    /// have no seed, or be its own seed).
+    ///
+    /// # Arguments
+    ///
+    /// * `seeds` - The identifiers of the seed nodes.
+    pub fn with_seeds(mut self, seeds: &[NodeId]) -> Self {
+        self.seeds = seeds.to_vec();
+        self
+    }
+
+    /// Sets the interval at which the node gossips its view of
+    /// the cluster (and thus sends its heartbeat).
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - The interval between two gossip rounds.
+    pub fn with_gossip_interval(mut self, interval: Duration) -> Self {
+        self.gossip_interval = interval;
+        self
+    }
+
+    /// Sets the time after which a node whose heartbeats weren't
+    /// received is considered down, which should be several
+    /// times longer than the gossip interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The time after which nodes are considered
+    ///   down.
+    pub fn with_failure_timeout(mut self, timeout: Duration) -> Self {
+        self.failure_timeout = timeout;
+        self
+    }
+
+    /// Starts the membership service, which joins the cluster
+    /// through the seed nodes.
+    ///
+    /// This method returns a [`ClusterRef`] referencing the
+    /// service if it succeeded, or `Err(())` otherwise.
+    ///
+    /// [`ClusterRef`]: struct.ClusterRef.html
+    pub fn join(self) -> Result<ClusterRef, ()> {
+        let node = self.node.id().clone();
+        debug!("Cluster({}): Joining through {:?}.", node, self.seeds);
+        let membership = Arc::new(Mutex::new(Membership::new(node.clone())));
+
+        let shared = membership.clone();
+        let system = self.node.system().clone();
+        let supervisor = system.supervisor(|sp| {
+            sp.with_name(&supervisor_name(&node)).children(|children| {
+                children
+                    .with_name(MEMBERSHIP_NAME)
+                    .with_exec(move |ctx: BastionContext| run(ctx, self.clone(), shared.clone()))
+            })
+        })?;
+
+        Ok(ClusterRef {
+            node,
+            supervisor,
+            membership,
+        })
+    }
+}
+
+impl ClusterRef {
+    /// Returns the identifier of the node this membership
+    /// service is running on.
+    pub fn node(&self) -> &NodeId {
+        &self.node
+    }
+
+    /// Returns the identifiers of the nodes this node considers
+    /// up (including itself), sorted by address.
+    pub fn members(&self) -> Vec<NodeId> {
+        // FIXME: panics?
+        self.membership.lock().unwrap().members()
+    }
+
+    /// Returns whether this node considers the given node up.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the node.
+    pub fn is_up(&self, node: &NodeId) -> bool {
+        self.members().contains(node)
+    }
+
+    /// Returns the supervisor the membership service is running
+    /// under.
+    pub fn supervisor(&self) -> &SupervisorRef {
+        &self.supervisor
+    }
+
+    /// Stops the membership service, which makes the other nodes
+    /// consider this node down once the failure timeout elapsed.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())`
+    /// otherwise.
+    pub fn leave(&self) -> Result<(), ()> {
+        debug!("Cluster({}): Leaving.", self.node);
+        self.supervisor.stop()
+    }
+}
+
+impl MemberUp {
+    /// Returns the identifier of the node which is up.
+    pub fn node(&self) -> &NodeId {
+        &self.node
+    }
+}
+
+impl MemberDown {
+    /// Returns the identifier of the node which is down.
+    pub fn node(&self) -> &NodeId {
+        &self.node
+    }
+}
+
+impl Membership {
+    fn new(node: NodeId) -> Self {
+        let generation = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+
+        Membership {
+            node,
+            heartbeat: Heartbeat {
+                generation,
+                counter: 0,
+            },
+            members: FxHashMap::default(),
+        }
+    }
+
+    // Increments this node's heartbeat and marks the members
+    // whose heartbeat didn't increase in time as down.
+    fn tick(&mut self, now: Duration, timeout: Duration) -> Vec<Event> {
+        self.heartbeat.counter += 1;
+
+        let mut events = vec![];
+        for (node, member) in self.members.iter_mut() {
+            if member.up && now.saturating_sub(member.updated) > timeout {
+                member.up = false;
+                events.push(Event::Down(node.clone()));
+            }
+        }
+
+        events
+    }
+
+    fn merge(&mut self, gossip: Gossip, now: Duration) -> Vec<Event> {
+        let mut events = vec![];
+        for (node, heartbeat) in gossip.members {
+            if node == self.node {
+                continue;
+            }
+
+            match self.members.get_mut(&node) {
+                Some(member) if heartbeat > member.heartbeat => {
+                    member.heartbeat = heartbeat;
+                    member.updated = now;
+                    if !member.up {
+                        member.up = true;
+                        events.push(Event::Up(node));
+                    }
+                }
+                Some(_) => (),
+                None => {
+                    let member = Member {
+                        heartbeat,
+                        updated: now,
+                        up: true,
+                    };
+
+                    self.members.insert(node.clone(), member);
+                    events.push(Event::Up(node));
+                }
+            }
+        }
+
+        events
+    }
+
+    fn gossip(&self) -> Gossip {
+        let mut members = vec![(self.node.clone(), self.heartbeat)];
+        for (node, member) in &self.members {
+            if member.up {
+                members.push((node.clone(), member.heartbeat));
+            }
+        }
+
+        Gossip { members }
+    }
+
+    // The nodes to gossip to, which are the members considered
+    // up and the seeds which aren't.
+    fn targets(&self, seeds: &[NodeId]) -> Vec<NodeId> {
+        let mut targets = self
+            .members
+            .iter()
+            .filter(|(_, member)| member.up)
+            .map(|(node, _)| node.clone())
+            .collect::<Vec<_>>();
+
+        for seed in seeds {
+            if *seed != self.node && !targets.contains(seed) {
+                targets.push(seed.clone());
+            }
+        }
+
+        targets
+    }
+
+    fn members(&self) -> Vec<NodeId> {
+        let mut members = vec![self.node.clone()];
+        for (node, member) in &self.members {
+            if member.up {
+                members.push(node.clone());
+            }
+        }
+
+        members.sort_by_key(|node| node.addr());
+        members
+    }
+}
+
+fn supervisor_name(node: &NodeId) -> String {
+    format!("cluster@{}", node)
+}
+
+// The path of the membership service of the given node.
+fn membership_path(node: &NodeId) -> BastionPath {
+    let path = format!("/{}/{}/0", supervisor_name(node), MEMBERSHIP_NAME);
+    // FIXME: panics? (names can't contain any `/`)
+    path.parse().unwrap()
+}
+
+async fn run(
+    ctx: BastionContext,
+    cluster: Cluster,
+    membership: Arc<Mutex<Membership>>,
+) -> Result<(), ()> {
+    let node = cluster.node.id().clone();
+    let ticker = ctx.current().clone();
+    let interval = cluster.gossip_interval;
+    // Using the executor's timers lets the gossip rounds follow the
+    // virtual time of a deterministic executor. The task stops once
+    // the element stopped (or restarted).
+    ctx.spawn(async move {
+        loop {
+            timer::sleep(interval).await;
+            if ticker.tell_anonymously(Tick).is_err() {
+                break;
+            }
+        }
+    });
+
+    loop {
+        let (msg, _) = ctx.recv().await?.extract();
+        let now = timer::now();
+
+        let events = if msg.is::<Tick>() {
+            // FIXME: panics?
+            let (events, gossip, targets) = {
+                let mut membership = membership.lock().unwrap();
+                let events = membership.tick(now, cluster.failure_timeout);
+                (
+                    events,
+                    membership.gossip(),
+                    membership.targets(&cluster.seeds),
+                )
+            };
+
+            for target in targets {
+                // Creating the address connects to the node, and
+                // messages are only sent once it is connected so
+                // that they don't pile up while it is unreachable.
+                let addr = cluster.node.remote(&target, &membership_path(&target));
+                if cluster.node.is_connected(&target) {
+                    ctx.tell(&addr, gossip.clone()).ok();
+                }
+            }
+
+            events
+        } else {
+            match msg.downcast::<Gossip>() {
+                // FIXME: panics?
+                Ok(gossip) => membership.lock().unwrap().merge(gossip, now),
+                Err(msg) => {
+                    debug!("Cluster({}): Ignoring {:?}.", node, msg);
+                    vec![]
+                }
+            }
+        };
+
+        for event in events {
+            match event {
+                Event::Up(member) => {
+                    info!("Cluster({}): {} is up.", node, member);
+                    ctx.system()
+                        .publish(MEMBER_UP, MemberUp { node: member })
+                        .ok();
+                }
+                Event::Down(member) => {
+                    info!("Cluster({}): {} is down.", node, member);
+                    ctx.system()
+                        .publish(MEMBER_DOWN, MemberDown { node: member })
+                        .ok();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(port: u16) -> NodeId {
+        format!("127.0.0.1:{}", port).parse().unwrap()
+    }
+
+    #[test]
+    fn merge_and_detect_failures() {
+        let timeout = Duration::from_secs(1);
+        let start = Duration::from_secs(10);
+        let mut first = Membership::new(node(1));
+        let mut second = Membership::new(node(2));
+        let third = Membership::new(node(3));
+
+        // Nodes are up once their heartbeats are gossiped...
+        second.merge(third.gossip(), start);
+        let events = first.merge(second.gossip(), start);
+        assert_eq!(events, vec![Event::Up(node(2)), Event::Up(node(3))]);
+        assert_eq!(first.members(), vec![node(1), node(2), node(3)]);
+        let mut targets = first.targets(&[node(1), node(4)]);
+        targets.sort_by_key(|id| id.addr());
+        assert_eq!(targets, vec![node(2), node(3), node(4)]);
+
+        // ...and stay up as long as their heartbeats increase...
+        second.tick(start, timeout);
+        let later = start + Duration::from_millis(800);
+        assert!(first.merge(second.gossip(), later).is_empty());
+
+        let events = first.tick(start + Duration::from_millis(1500), timeout);
+        assert_eq!(events, vec![Event::Down(node(3))]);
+        assert_eq!(first.members(), vec![node(1), node(2)]);
+        let gossiped = first.gossip().members;
+        assert!(gossiped.iter().all(|(id, _)| *id != node(3)));
+
+        // ...but stale heartbeats don't bring them back up...
+        assert!(first.merge(third.gossip(), later).is_empty());
+
+        // ...unlike new ones.
+        let mut restarted = Membership::new(node(3));
+        restarted.heartbeat.generation = third.heartbeat.generation + 1;
+        let events = first.merge(restarted.gossip(), later);
+        assert_eq!(events, vec![Event::Up(node(3))]);
+    }
+}
@@ -0,0 +1,441 @@
+//!
+//! Opt-in serialization of messages, allowing them to cross a
+//! process boundary (eg. to be persisted, recorded or sent over
+//! the network).
+//!
+//! Each message type that should be serializable is registered
+//! to a [`Codecs`] registry along with a type tag, which is
+//! written along with the encoded message and used to find the
+//! type the message should be decoded to. Messages are encoded
+//! using [`serde`] and a [`Format`], either [`Bincode`] (the
+//! default) or [`Json`].
+//!
+//! This module (as well as the modules built on top of it) is
+//! only available with the `distributed` feature.
+//!
+//! [`Codecs`]: struct.Codecs.html
+//! [`serde`]: https://docs.rs/serde
+//! [`Format`]: trait.Format.html
+//! [`Bincode`]: struct.Bincode.html
+//! [`Json`]: struct.Json.html
+use crate::envelope::SignedMessage;
+use crate::message::{Message, Msg};
+use crate::path::BastionPath;
+use fxhash::FxHashMap;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::fmt::{self, Debug, Formatter};
+use std::io;
+
+/// A serialization format used by [`Codecs`] to encode and
+/// decode messages and their wire representation.
+///
+/// [`Codecs`]: struct.Codecs.html
+pub trait Format: Send + Sync + 'static {
+    /// Encodes `value` to bytes.
+    fn encode<T: Serialize>(&self, value: &T) -> io::Result<Vec<u8>>;
+
+    /// Decodes a value of type `T` from `bytes`.
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> io::Result<T>;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+/// A compact binary [`Format`] using [`bincode`].
+///
+/// [`Format`]: trait.Format.html
+/// [`bincode`]: https://docs.rs/bincode
+pub struct Bincode;
+
+#[derive(Debug, Default, Clone, Copy)]
+/// A human-readable [`Format`] using [`serde_json`].
+///
+/// [`Format`]: trait.Format.html
+/// [`serde_json`]: https://docs.rs/serde_json
+pub struct Json;
+
+/// A registry of the message types that can be encoded and
+/// decoded, along with their type tag, using the [`Format`] `F`
+/// (which defaults to [`Bincode`]).
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::codec::{Codecs, Json};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Ping(u64);
+///
+/// let codecs = Codecs::with_format(Json).register::<Ping>("ping");
+/// assert!(codecs.is_registered::<Ping>());
+/// ```
+///
+/// [`Format`]: trait.Format.html
+/// [`Bincode`]: struct.Bincode.html
+pub struct Codecs<F: Format = Bincode> {
+    format: F,
+    by_tag: FxHashMap<String, Entry<F>>,
+    by_type: FxHashMap<TypeId, String>,
+}
+
+// The functions encoding and decoding a registered message type.
+struct Entry<F> {
+    encode: fn(&F, &Msg) -> Option<io::Result<Vec<u8>>>,
+    decode: fn(&F, Kind, &[u8]) -> io::Result<Msg>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Kind {
+    Broadcast,
+    Tell,
+    Ask,
+}
+
+#[derive(Serialize, Deserialize)]
+// The wire representation of a `Msg`.
+struct WireMsg {
+    kind: Kind,
+    tag: String,
+    payload: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+// The wire representation of a `SignedMessage`, whose
+// signature is only represented by its path.
+struct WireEnvelope {
+    sign: BastionPath,
+    msg: WireMsg,
+}
+
+impl Format for Bincode {
+    fn encode<T: Serialize>(&self, value: &T) -> io::Result<Vec<u8>> {
+        bincode::serialize(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> io::Result<T> {
+        bincode::deserialize(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl Format for Json {
+    fn encode<T: Serialize>(&self, value: &T) -> io::Result<Vec<u8>> {
+        serde_json::to_vec(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> io::Result<T> {
+        serde_json::from_slice(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+}
+
+impl Codecs<Bincode> {
+    /// Creates a new empty registry using the [`Bincode`]
+    /// format.
+    ///
+    /// [`Bincode`]: struct.Bincode.html
+    pub fn new() -> Self {
+        Codecs::with_format(Bincode)
+    }
+}
+
+impl<F: Format> Codecs<F> {
+    /// Creates a new empty registry using the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The format used to encode and decode
+    ///   messages.
+    pub fn with_format(format: F) -> Self {
+        Codecs {
+            format,
+            by_tag: FxHashMap::default(),
+            by_type: FxHashMap::default(),
+        }
+    }
+
+    /// Registers the message type `M` with the given type tag,
+    /// replacing any type previously registered with the same
+    /// tag.
+    ///
+    /// Messages of type `M` can be encoded whether they were
+    /// broadcasted, told or asked, but need to be `Sync` to be
+    /// decoded as broadcasted messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The type tag written along with the encoded
+    ///   messages of type `M`, which must be the same for the
+    ///   processes encoding and decoding them.
+    pub fn register<M>(mut self, tag: &str) -> Self
+    where
+        M: Message + Sync + Serialize + DeserializeOwned,
+    {
+        let entry = Entry {
+            encode: |format: &F, msg: &Msg| msg.map_ref(|msg: &M| format.encode(msg)),
+            decode: |format: &F, kind, bytes: &[u8]| {
+                let msg: M = format.decode(bytes)?;
+                let msg = match kind {
+                    Kind::Broadcast => Msg::broadcast(msg),
+                    Kind::Tell => Msg::tell(msg),
+                    Kind::Ask => Msg::restore(msg, true),
+                };
+
+                Ok(msg)
+            },
+        };
+
+        if let Some(tag) = self.by_type.insert(TypeId::of::<M>(), tag.to_string()) {
+            self.by_tag.remove(&tag);
+        }
+
+        if self.by_tag.insert(tag.to_string(), entry).is_some() {
+            let tag = tag.to_string();
+            self.by_type
+                .retain(|type_id, other| *type_id == TypeId::of::<M>() || *other != tag);
+        }
+
+        self
+    }
+
+    /// Returns whether the message type `M` is registered.
+    pub fn is_registered<M: Message>(&self) -> bool {
+        self.by_type.contains_key(&TypeId::of::<M>())
+    }
+
+    /// Encodes a message to its wire representation.
+    ///
+    /// Note that the sender of an asked message isn't encoded,
+    /// so the decoded message can't be answered directly.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to encode, whose type needs to be
+    ///   registered.
+    pub fn encode(&self, msg: &Msg) -> io::Result<Vec<u8>> {
+        self.format.encode(&self.encode_wire(msg)?)
+    }
+
+    /// Decodes a message from its wire representation, as it
+    /// was broadcasted, told or asked.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The wire representation of a message of a
+    ///   registered type.
+    pub fn decode(&self, bytes: &[u8]) -> io::Result<Msg> {
+        self.decode_wire(self.format.decode(bytes)?)
+    }
+
+    /// Encodes a signed message to its wire representation,
+    /// which contains the message and the path of its sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The signed message to encode, whose type needs
+    ///   to be registered.
+    pub fn encode_signed(&self, msg: &SignedMessage) -> io::Result<Vec<u8>> {
+        let wire = WireEnvelope {
+            sign: msg.signature().path().as_ref().clone(),
+            msg: self.encode_wire(&msg.msg)?,
+        };
+
+        self.format.encode(&wire)
+    }
+
+    /// Decodes a signed message from its wire representation,
+    /// returning the message and the path of its sender.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The wire representation of a signed message
+    ///   of a registered type.
+    pub fn decode_signed(&self, bytes: &[u8]) -> io::Result<(BastionPath, Msg)> {
+        let wire: WireEnvelope = self.format.decode(bytes)?;
+        Ok((wire.sign, self.decode_wire(wire.msg)?))
+    }
+
+    fn encode_wire(&self, msg: &Msg) -> io::Result<WireMsg> {
+        let kind = if msg.is_broadcast() {
+            Kind::Broadcast
+        } else if msg.is_tell() {
+            Kind::Tell
+        } else {
+            Kind::Ask
+        };
+
+        let tag = self.by_type.get(&msg.type_id()).ok_or_else(|| {
+            let err = format!("Unregistered message type: {:?}", msg);
+            io::Error::new(io::ErrorKind::InvalidInput, err)
+        })?;
+
+        let entry = &self.by_tag[tag];
+        // This can't be `None` since the type of `msg` matches
+        // the type registered with `tag`.
+        let payload = (entry.encode)(&self.format, msg).unwrap()?;
+
+        Ok(WireMsg {
+            kind,
+            tag: tag.clone(),
+            payload,
+        })
+    }
+
+    fn decode_wire(&self, wire: WireMsg) -> io::Result<Msg> {
+        let entry = self.by_tag.get(&wire.tag).ok_or_else(|| {
+            let err = format!("Unknown message type tag: {}", wire.tag);
+            io::Error::new(io::ErrorKind::InvalidData, err)
+        })?;
+
+        (entry.decode)(&self.format, wire.kind, &wire.payload)
+    }
+}
+
+// An object-safe view of `Codecs`, used when the format doesn't
+// matter (eg. by the `transport` module).
+pub(crate) trait MsgCodecs: Send + Sync + 'static {
+    fn encode(&self, msg: &Msg) -> io::Result<Vec<u8>>;
+
+    fn decode(&self, bytes: &[u8]) -> io::Result<Msg>;
+}
+
+impl<F: Format> MsgCodecs for Codecs<F> {
+    fn encode(&self, msg: &Msg) -> io::Result<Vec<u8>> {
+        Codecs::encode(self, msg)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> io::Result<Msg> {
+        Codecs::decode(self, bytes)
+    }
+}
+
+impl Default for Codecs<Bincode> {
+    fn default() -> Self {
+        Codecs::new()
+    }
+}
+
+impl<F: Format + Debug> Debug for Codecs<F> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        let mut tags = self.by_tag.keys().collect::<Vec<_>>();
+        tags.sort();
+
+        fmt.debug_struct("Codecs")
+            .field("format", &self.format)
+            .field("tags", &tags)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::BastionId;
+    use crate::envelope::RefAddr;
+    use crate::path::BastionPathElement;
+    use futures::channel::mpsc;
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Ping {
+        id: u64,
+        text: String,
+    }
+
+    fn ping() -> Ping {
+        Ping {
+            id: 42,
+            text: "ping".to_string(),
+        }
+    }
+
+    fn round_trip<F: Format>(codecs: &Codecs<F>) {
+        let bytes = codecs.encode(&Msg::tell(ping())).unwrap();
+        let msg = codecs.decode(&bytes).unwrap();
+        assert!(msg.is_tell());
+        assert_eq!(msg.downcast::<Ping>().unwrap(), ping());
+
+        let bytes = codecs.encode(&Msg::broadcast(ping())).unwrap();
+        let msg = codecs.decode(&bytes).unwrap();
+        assert!(msg.is_broadcast());
+        assert_eq!(*msg.downcast_ref::<Ping>().unwrap(), ping());
+
+        let (msg, _) = Msg::ask(ping());
+        let bytes = codecs.encode(&msg).unwrap();
+        let mut msg = codecs.decode(&bytes).unwrap();
+        assert!(msg.is_ask());
+        assert!(msg.take_sender().is_none());
+        assert_eq!(msg.downcast::<Ping>().unwrap(), ping());
+    }
+
+    #[test]
+    fn bincode_round_trip() {
+        round_trip(&Codecs::new().register::<Ping>("ping"));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let codecs = Codecs::with_format(Json).register::<Ping>("ping");
+        round_trip(&codecs);
+
+        let bytes = codecs.encode(&Msg::tell(ping())).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["kind"], "Tell");
+        assert_eq!(json["tag"], "ping");
+    }
+
+    #[test]
+    fn unregistered_types_and_unknown_tags() {
+        let codecs = Codecs::new().register::<Ping>("ping");
+        assert!(codecs.is_registered::<Ping>());
+        assert!(!codecs.is_registered::<u64>());
+
+        let err = codecs.encode(&Msg::tell(42u64)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let bytes = codecs.encode(&Msg::tell(ping())).unwrap();
+        let other = Codecs::new().register::<Ping>("pong");
+        let err = other.decode(&bytes).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn reregistering_a_tag() {
+        let codecs = Codecs::new().register::<Ping>("msg").register::<u64>("msg");
+        assert!(!codecs.is_registered::<Ping>());
+        assert!(codecs.is_registered::<u64>());
+
+        let bytes = codecs.encode(&Msg::tell(42u64)).unwrap();
+        assert_eq!(
+            codecs.decode(&bytes).unwrap().downcast::<u64>().unwrap(),
+            42
+        );
+    }
+
+    #[test]
+    fn signed_round_trip() {
+        let supervisor = BastionPathElement::Supervisor(BastionId::new());
+        let children = BastionPathElement::Children(BastionId::new());
+        let child = BastionPathElement::Child(BastionId::new());
+        let path = BastionPath::root()
+            .append(supervisor)
+            .unwrap()
+            .append(children)
+            .unwrap()
+            .append(child)
+            .unwrap();
+
+        let (sender, _) = mpsc::unbounded();
+        let sign = RefAddr::new(Arc::new(path), sender);
+        let signed = SignedMessage::new(Msg::tell(ping()), sign);
+
+        let codecs = Codecs::new().register::<Ping>("ping");
+        let bytes = codecs.encode_signed(&signed).unwrap();
+        let (path, msg) = codecs.decode_signed(&bytes).unwrap();
+        assert_eq!(
+            format!("{:?}", path),
+            format!("{:?}", signed.signature().path())
+        );
+        assert_eq!(path.elem(), signed.signature().path().elem());
+        assert_eq!(msg.downcast::<Ping>().unwrap(), ping());
+    }
+}
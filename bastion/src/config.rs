@@ -26,6 +26,7 @@
 /// [`Bastion::init_with`]: struct.Bastion.html#method.init_with
 pub struct Config {
     backtraces: Backtraces,
+    deterministic_seed: Option<u64>,
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -107,9 +108,69 @@ impl Config {
         self
     }
 
+    /// Makes Bastion (and every process spawned onto the executor's
+    /// thread pool) run on a single thread, in an order only
+    /// depending on the given seed, with the timers of the
+    /// executor (see `bastion_executor::timer`) using a virtual
+    /// time which advances instantly once nothing is ready to run.
+    ///
+    /// This is meant for tests: a failing run can be replayed by
+    /// using the same seed again. Note that messages received from
+    /// other threads or from the network (see [`transport`]) can
+    /// still make runs differ.
+    ///
+    /// Some parts of Bastion don't run on the deterministic
+    /// scheduler and still use their own threads (and real time):
+    /// - the elements of [blocking children groups], which run on
+    ///   the executor's blocking thread pool,
+    /// - the timers of processes which don't run on the scheduler,
+    ///   which use the executor's timer thread,
+    /// - the links between nodes (see [`transport`] and
+    ///   [`cluster`]), which read and write on their own threads.
+    ///
+    /// This only works if nothing was spawned onto the executor's
+    /// thread pool before, and initializing the system panics
+    /// otherwise (or if another seed was already used).
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - The seed used to pick the next process to run
+    ///   among the ready ones.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::prelude::*;
+    ///
+    /// fn main() {
+    ///     let config = Config::new().with_deterministic_seed(42);
+    ///
+    ///     Bastion::init_with(config);
+    ///
+    ///     // You can now use bastion and everything will run in
+    ///     // the same order every time...
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// }
+    /// ```
+    ///
+    /// [blocking children groups]: children/struct.Children.html#method.with_blocking
+    /// [`transport`]: transport/index.html
+    /// [`cluster`]: cluster/index.html
+    pub fn with_deterministic_seed(mut self, seed: u64) -> Self {
+        self.deterministic_seed = Some(seed);
+        self
+    }
+
     pub(crate) fn backtraces(&self) -> &Backtraces {
         &self.backtraces
     }
+
+    pub(crate) fn deterministic_seed(&self) -> Option<u64> {
+        self.deterministic_seed
+    }
 }
 
 impl Backtraces {
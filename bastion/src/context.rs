@@ -2,21 +2,34 @@
 //! A context allows a child's future to access its received
 //! messages, parent and supervisor.
 
+use crate::checkpoint::Checkpoint;
 use crate::child_ref::ChildRef;
 use crate::children_ref::ChildrenRef;
 use crate::envelope::{Envelope, RefAddr, SignedMessage};
 use crate::message::{Answer, BastionMessage, Message, Msg};
 use crate::supervisor::SupervisorRef;
+use crate::system::BastionSystem;
+use bastion_executor::pool;
+use futures::channel::oneshot;
 use futures::pending;
+use lightproc::prelude::*;
 use qutex::{Guard, Qutex};
+#[cfg(feature = "distributed")]
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::fmt::{self, Display, Formatter};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 use uuid::Uuid;
 
 /// Identifier for a root supervisor and dead-letters children.
 pub const NIL_ID: BastionId = BastionId(Uuid::nil());
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "distributed", derive(Serialize, Deserialize))]
 /// An identifier used by supervisors, children groups and
 /// their elements to identify themselves, using a v4 UUID.
 ///
@@ -103,11 +116,86 @@ pub struct BastionContext {
     children: ChildrenRef,
     supervisor: Option<SupervisorRef>,
     state: Qutex<ContextState>,
+    checkpoint: Option<Checkpoint>,
+    // The element's index in its group and the number of
+    // elements in the group.
+    index: usize,
+    group_size: usize,
+    tasks: Tasks,
 }
 
+#[derive(Debug)]
+/// A handle to a task spawned using [`BastionContext::spawn`],
+/// which resolves to the task's output once it completed, or to
+/// `None` if it panicked or was cancelled (because the element
+/// that spawned it stopped, was killed or faulted).
+///
+/// Dropping a `TaskHandle` doesn't cancel its task.
+///
+/// [`BastionContext::spawn`]: struct.BastionContext.html#method.spawn
+pub struct TaskHandle<T>(oneshot::Receiver<T>);
+
+/// The state of an element of a children group created using
+/// [`Children::with_state`], which is handed to the element's
+/// future.
+///
+/// The state is kept when the group is restarted: the element
+/// replacing another one is handed the last state it saved
+/// (or its initial state if it didn't save any).
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// # fn main() {
+///     # Bastion::init();
+///     #
+/// Bastion::children(|children| {
+///     children.with_state(
+///         || vec!["initial"],
+///         |ctx: BastionContext, state: State<Vec<&str>>| {
+///             async move {
+///                 let mut events = state.get();
+///                 // ...
+///                 events.push("processed");
+///                 state.save(events);
+///
+///                 Ok(())
+///             }
+///         },
+///     )
+/// }).expect("Couldn't create the children group.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`Children::with_state`]: ../children/struct.Children.html#method.with_state
+pub struct State<S> {
+    checkpoint: Checkpoint,
+    _state: PhantomData<fn() -> S>,
+}
+
+#[derive(Debug, Clone, Default)]
+// The tasks spawned by a child's future, which are polled by the
+// child to know whether one of them panicked, and cancelled once
+// it exits.
+pub(crate) struct Tasks(Arc<Mutex<Vec<RecoverableHandle<()>>>>);
+
 #[derive(Debug)]
 pub(crate) struct ContextState {
     msgs: VecDeque<SignedMessage>,
+    // The messages stashed by the child's future, in the order
+    // they were stashed, which will be pushed back in front of
+    // `msgs` when unstashed.
+    stash: VecDeque<SignedMessage>,
+    stash_capacity: usize,
+    // The system the child belongs to, whose dead letters
+    // receive the messages left in the stash.
+    system: BastionSystem,
 }
 
 impl BastionId {
@@ -116,15 +204,24 @@ impl BastionId {
 
         BastionId(uuid)
     }
+
+    pub(crate) fn parse(id: &str) -> Option<Self> {
+        Uuid::parse_str(id).ok().map(BastionId)
+    }
 }
 
 impl BastionContext {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         id: BastionId,
         child: ChildRef,
         children: ChildrenRef,
         supervisor: Option<SupervisorRef>,
         state: Qutex<ContextState>,
+        checkpoint: Option<Checkpoint>,
+        index: usize,
+        group_size: usize,
+        tasks: Tasks,
     ) -> Self {
         debug!("BastionContext({}): Creating.", id);
         BastionContext {
@@ -133,9 +230,63 @@ impl BastionContext {
             children,
             supervisor,
             state,
+            checkpoint,
+            index,
+            group_size,
+            tasks,
         }
     }
 
+    /// Returns the index of the children group's element that is
+    /// linked to this `BastionContext`, in `0..group_size()`.
+    ///
+    /// Unlike the element's identifier, its index stays the same
+    /// when it is restarted, and can thus be used to split work
+    /// between the elements of a group (see
+    /// [`Children::with_exec_indexed`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children
+    ///         .with_redundancy(4)
+    ///         .with_exec(|ctx: BastionContext| {
+    ///             async move {
+    ///                 let index: usize = ctx.index();
+    ///                 assert!(index < ctx.group_size());
+    ///                 // ...
+    ///
+    ///                 Ok(())
+    ///             }
+    ///         })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Children::with_exec_indexed`]: ../children/struct.Children.html#method.with_exec_indexed
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the number of elements of the children group of
+    /// the element that is linked to this `BastionContext` (see
+    /// [`Children::with_redundancy`]).
+    ///
+    /// [`Children::with_redundancy`]: ../children/struct.Children.html#method.with_redundancy
+    pub fn group_size(&self) -> usize {
+        self.group_size
+    }
+
     /// Returns a [`ChildRef`] referencing the children group's
     /// element that is linked to this `BastionContext`.
     ///
@@ -261,6 +412,408 @@ impl BastionContext {
         self.supervisor.as_ref()
     }
 
+    /// Returns the [`BastionSystem`] the element this
+    /// `BastionContext` is linked to belongs to, allowing it to
+    /// create elements, send messages or find other elements in
+    /// this system only.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             let system: &BastionSystem = ctx.system();
+    ///             system.children(|children| {
+    ///                 // ...
+    /// #               children
+    ///             }).expect("Couldn't create the children group.");
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionSystem`]: struct.BastionSystem.html
+    pub fn system(&self) -> &BastionSystem {
+        self.child.system()
+    }
+
+    /// Registers the element this `BastionContext` is linked to
+    /// under the specified name, allowing it to be found using
+    /// [`Bastion::whereis`].
+    ///
+    /// The name stays registered when the element is restarted
+    /// by its supervisor (it then references the new instance of
+    /// the element) and is unregistered when it stops.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())` if
+    /// the name is already used by another running child.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to register the element under.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             ctx.register("cache").expect("Couldn't register the child.");
+    ///             assert_eq!(Bastion::whereis("cache").as_ref(), Some(ctx.current()));
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Bastion::whereis`]: ../struct.Bastion.html#method.whereis
+    pub fn register(&self, name: &str) -> Result<(), ()> {
+        debug!("BastionContext({}): Registering as {:?}.", self.id, name);
+        self.system().registry().register(name, self.current())
+    }
+
+    /// Subscribes the element this `BastionContext` is linked to
+    /// to the topics matching the specified pattern, making it
+    /// receive the messages published about them using
+    /// [`Bastion::publish`] or [`publish`].
+    ///
+    /// Topics are made of segments separated by dots, and
+    /// patterns can contain wildcards: `*` matches exactly one
+    /// segment while `#` matches zero or more segments (and can
+    /// only be used as the pattern's last segment).
+    ///
+    /// Subscriptions are removed when the element stops or is
+    /// restarted.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())` if
+    /// the pattern is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern of the topics to subscribe to.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             // Receive messages published about created orders...
+    ///             ctx.subscribe("orders.*.created").expect("Couldn't subscribe.");
+    ///             // ...and about anything related to invoices.
+    ///             ctx.subscribe("invoices.#").expect("Couldn't subscribe.");
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Bastion::publish`]: ../struct.Bastion.html#method.publish
+    /// [`publish`]: #method.publish
+    pub fn subscribe(&self, pattern: &str) -> Result<(), ()> {
+        debug!("BastionContext({}): Subscribing to {:?}.", self.id, pattern);
+        self.system().topics().subscribe(pattern, self.current())
+    }
+
+    /// Unsubscribes the element this `BastionContext` is linked
+    /// to from the specified pattern, previously passed to
+    /// [`subscribe`].
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern to unsubscribe from.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             ctx.subscribe("orders.#").expect("Couldn't subscribe.");
+    ///             // ...
+    ///             ctx.unsubscribe("orders.#");
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`subscribe`]: #method.subscribe
+    pub fn unsubscribe(&self, pattern: &str) {
+        debug!(
+            "BastionContext({}): Unsubscribing from {:?}.",
+            self.id, pattern
+        );
+        self.system().topics().unsubscribe(pattern, &self.id)
+    }
+
+    /// Publishes a message on behalf of the element this
+    /// `BastionContext` is linked to, to every running child
+    /// subscribed to a pattern matching the specified topic
+    /// (see [`subscribe`]).
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)` if
+    /// no child is subscribed to the topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic to publish the message about.
+    /// * `msg` - The message to publish.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             ctx.subscribe("pings").expect("Couldn't subscribe.");
+    ///             ctx.publish("pings", "Ping!").expect("Couldn't publish.");
+    ///
+    ///             msg! { ctx.recv().await?,
+    ///                 ref msg: &'static str => {
+    ///                     assert_eq!(msg, &"Ping!");
+    ///                     // The signature identifies the publisher...
+    ///                     assert_eq!(signature!().path().elem(), ctx.signature().path().elem());
+    ///                 };
+    ///                 _: _ => ();
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`subscribe`]: #method.subscribe
+    pub fn publish<M: Message + Sync>(&self, topic: &str, msg: M) -> Result<(), M> {
+        debug!(
+            "{:?}: Publishing message about {:?}: {:?}",
+            self.current().path(),
+            topic,
+            msg
+        );
+        let msg = BastionMessage::broadcast(msg);
+        let env = Envelope::new_with_sign(msg, self.signature());
+        // FIXME: panics?
+        self.system()
+            .topics()
+            .publish(topic, env)
+            .map_err(|env| env.into_broadcast().unwrap())
+    }
+
+    /// Starts monitoring the child referenced by the specified
+    /// [`ChildRef`], making the element this `BastionContext` is
+    /// linked to receive a [`Down`] message (with the monitored
+    /// child's signature) once it terminates, whatever the reason.
+    ///
+    /// A monitor only triggers once: if the monitored child is
+    /// restarted by its supervisor, the new instance needs to be
+    /// monitored again. If the child is already gone, a [`Down`]
+    /// message is received right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - A reference to the child to monitor.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// let worker = Bastion::children(|children| {
+    ///     // ...
+    ///     # children
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// Bastion::children(|children| {
+    ///     children.with_exec(move |ctx: BastionContext| {
+    ///         let worker = worker.elems()[0].clone();
+    ///         async move {
+    ///             ctx.monitor(&worker);
+    ///
+    ///             msg! { ctx.recv().await?,
+    ///                 down: Down => {
+    ///                     println!("Worker terminated: {:?}", down.reason());
+    ///                 };
+    ///                 _: _ => ();
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`ChildRef`]: ../child_ref/struct.ChildRef.html
+    /// [`Down`]: ../monitor/struct.Down.html
+    pub fn monitor(&self, child: &ChildRef) {
+        debug!(
+            "BastionContext({}): Monitoring Child({}).",
+            self.id,
+            child.id()
+        );
+        self.system().monitors().monitor(self.current(), child)
+    }
+
+    /// Stops monitoring the child referenced by the specified
+    /// [`ChildRef`], previously passed to [`monitor`].
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - A reference to the child to stop monitoring.
+    ///
+    /// [`ChildRef`]: ../child_ref/struct.ChildRef.html
+    /// [`monitor`]: #method.monitor
+    pub fn demonitor(&self, child: &ChildRef) {
+        debug!(
+            "BastionContext({}): Demonitoring Child({}).",
+            self.id,
+            child.id()
+        );
+        self.system().monitors().demonitor(&self.id, child.id())
+    }
+
+    /// Links the element this `BastionContext` is linked to with
+    /// the child referenced by the specified [`ChildRef`], making
+    /// each of them fault when the other one faults (either
+    /// because its future returned `Err(())` or panicked), even
+    /// if they aren't supervised by the same supervisor.
+    ///
+    /// Stopping or being killed isn't propagated. Like a monitor,
+    /// a link doesn't survive restarts. If the child is already
+    /// gone, the element will fault.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - A reference to the child to link with.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// let connection = Bastion::children(|children| {
+    ///     // ...
+    ///     # children
+    /// }).expect("Couldn't create the children group.");
+    ///
+    /// Bastion::supervisor(|sp| {
+    ///     sp.children(|children| {
+    ///         children.with_exec(move |ctx: BastionContext| {
+    ///             let connection = connection.elems()[0].clone();
+    ///             async move {
+    ///                 // If the connection faults, this child will fault
+    ///                 // too and get restarted by its supervisor...
+    ///                 ctx.link(&connection);
+    ///                 // ...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    ///     })
+    /// }).expect("Couldn't create the supervisor.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`ChildRef`]: ../child_ref/struct.ChildRef.html
+    pub fn link(&self, child: &ChildRef) {
+        debug!(
+            "BastionContext({}): Linking to Child({}).",
+            self.id,
+            child.id()
+        );
+        self.system().monitors().link(self.current(), child)
+    }
+
+    /// Removes the link between the element this `BastionContext`
+    /// is linked to and the child referenced by the specified
+    /// [`ChildRef`], previously created using [`link`].
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - A reference to the child to unlink from.
+    ///
+    /// [`ChildRef`]: ../child_ref/struct.ChildRef.html
+    /// [`link`]: #method.link
+    pub fn unlink(&self, child: &ChildRef) {
+        debug!(
+            "BastionContext({}): Unlinking from Child({}).",
+            self.id,
+            child.id()
+        );
+        self.system().monitors().unlink(&self.id, child.id())
+    }
+
     /// Tries to retrieve asynchronously a message received by
     /// the element this `BastionContext` is linked to.
     ///
@@ -366,6 +919,179 @@ impl BastionContext {
         }
     }
 
+    /// Stashes a message received by the element this
+    /// `BastionContext` is linked to, so that it can be handled
+    /// later (eg. once the element finished initializing) after
+    /// calling [`unstash_all`].
+    ///
+    /// The number of messages that can be stashed at the same time
+    /// is bounded (see [`Children::with_stash_capacity`]). The
+    /// messages that are still stashed when the element stops (or
+    /// is killed or faults) are sent to dead letters.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)`
+    /// if the stash is full.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to stash.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             // Stash the messages received while the element
+    ///             // is initializing...
+    ///             let msg: SignedMessage = ctx.recv().await?;
+    ///             ctx.stash(msg).await.map_err(|_| ())?;
+    ///             // ...
+    ///
+    ///             // ...and then receive them again, in the same order.
+    ///             ctx.unstash_all().await?;
+    ///             let msg: SignedMessage = ctx.recv().await?;
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`unstash_all`]: #method.unstash_all
+    /// [`Children::with_stash_capacity`]: ../children/struct.Children.html#method.with_stash_capacity
+    pub async fn stash(&self, msg: SignedMessage) -> Result<(), SignedMessage> {
+        debug!("BastionContext({}): Stashing message: {:?}", self.id, msg);
+        // TODO: Err(Error)
+        let mut state = match self.state.clone().lock_async().await {
+            Ok(state) => state,
+            Err(_) => return Err(msg),
+        };
+
+        if state.stash.len() >= state.stash_capacity {
+            warn!(
+                "BastionContext({}): Couldn't stash message: the stash is full.",
+                self.id
+            );
+            return Err(msg);
+        }
+
+        state.stash.push_back(msg);
+        Ok(())
+    }
+
+    /// Unstashes all the messages stashed using [`stash`], which
+    /// will then be retrieved by [`recv`] or [`try_recv`] before
+    /// any other message, in the order they were stashed.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())`
+    /// otherwise.
+    ///
+    /// # Example
+    ///
+    /// See [`stash`].
+    ///
+    /// [`stash`]: #method.stash
+    /// [`recv`]: #method.recv
+    /// [`try_recv`]: #method.try_recv
+    pub async fn unstash_all(&self) -> Result<(), ()> {
+        debug!("BastionContext({}): Unstashing messages.", self.id);
+        // TODO: Err(Error)
+        let mut state = self.state.clone().lock_async().await.map_err(|_| ())?;
+
+        let ContextState { msgs, stash, .. } = &mut *state;
+        trace!(
+            "BastionContext({}): Unstashing {} messages.",
+            self.id,
+            stash.len()
+        );
+        while let Some(msg) = stash.pop_back() {
+            msgs.push_front(msg);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn checkpoint(&self) -> Option<Checkpoint> {
+        self.checkpoint.clone()
+    }
+
+    /// Spawns a task running the specified future concurrently
+    /// with the future of the children group's element that is
+    /// linked to this `BastionContext`, and whose lifetime is tied
+    /// to it.
+    ///
+    /// The task is cancelled when the element stops, is killed or
+    /// faults, and the element faults if the task panics (like it
+    /// would if its own future panicked).
+    ///
+    /// This method returns a [`TaskHandle`] resolving to the
+    /// task's output once it completed.
+    ///
+    /// # Arguments
+    ///
+    /// * `fut` - The future that the task will run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             // Both tasks run concurrently...
+    ///             let first: TaskHandle<u64> = ctx.spawn(async { 1 + 1 });
+    ///             let second: TaskHandle<u64> = ctx.spawn(async { 2 + 2 });
+    ///
+    ///             // ...and resolve to their output once completed.
+    ///             assert_eq!(first.await, Some(2));
+    ///             assert_eq!(second.await, Some(4));
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`TaskHandle`]: struct.TaskHandle.html
+    pub fn spawn<F, T>(&self, fut: F) -> TaskHandle<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        debug!("BastionContext({}): Spawning a task.", self.id);
+        let (sender, recver) = oneshot::channel();
+        let task = async move {
+            let output = fut.await;
+            // The task's handle might have been dropped.
+            sender.send(output).ok();
+        };
+
+        let handle = pool::spawn(task, ProcStack::default());
+        self.tasks.push(handle);
+
+        TaskHandle(recver)
+    }
+
     /// Returns [`RefAddr`] of the current `BastionContext`
     ///
     /// # Example
@@ -447,9 +1173,7 @@ impl BastionContext {
         let msg = BastionMessage::tell(msg);
         let env = Envelope::new_with_sign(msg, self.signature());
         // FIXME: panics?
-        to.sender()
-            .unbounded_send(env)
-            .map_err(|err| err.into_inner().into_msg().unwrap())
+        to.send(env).map_err(|env| env.into_msg().unwrap())
     }
 
     /// Sends a message from behalf of current context to the addr,
@@ -538,19 +1262,23 @@ impl BastionContext {
         let (msg, answer) = BastionMessage::ask(msg);
         let env = Envelope::new_with_sign(msg, self.signature());
         // FIXME: panics?
-        to.sender()
-            .unbounded_send(env)
-            .map_err(|err| err.into_inner().into_msg().unwrap())?;
+        to.send(env).map_err(|env| env.into_msg().unwrap())?;
 
         Ok(answer)
     }
 }
 
 impl ContextState {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(stash_capacity: usize, system: BastionSystem) -> Self {
         let msgs = VecDeque::new();
+        let stash = VecDeque::new();
 
-        ContextState { msgs }
+        ContextState {
+            msgs,
+            stash,
+            stash_capacity,
+            system,
+        }
     }
 
     pub(crate) fn push_msg(&mut self, msg: Msg, sign: RefAddr) {
@@ -558,6 +1286,131 @@ impl ContextState {
     }
 }
 
+impl Drop for ContextState {
+    fn drop(&mut self) {
+        if self.stash.is_empty() {
+            return;
+        }
+
+        // The messages that were stashed but never unstashed
+        // are sent to dead letters once the child is gone.
+        debug!(
+            "ContextState: Sending {} stashed messages to dead letters.",
+            self.stash.len()
+        );
+        let elems = match self.system.dead_letters() {
+            Some(dead_letters) => dead_letters.stable().elems(),
+            None => return,
+        };
+        let dead_letters = match elems.first() {
+            Some(dead_letters) => dead_letters,
+            None => return,
+        };
+
+        for smsg in self.stash.drain(..) {
+            let env = Envelope::new_with_sign(BastionMessage::Message(smsg.msg), smsg.sign);
+            // FIXME: handle errors
+            dead_letters.send(env).ok();
+        }
+    }
+}
+
+impl Tasks {
+    pub(crate) fn push(&self, handle: RecoverableHandle<()>) {
+        // FIXME: panics?
+        self.0.lock().unwrap().push(handle);
+    }
+
+    // Polls the tasks, returning `Poll::Ready(())` if one of them
+    // panicked, and forgetting the ones that completed.
+    pub(crate) fn poll_panicked(&self, ctx: &mut Context) -> Poll<()> {
+        // FIXME: panics?
+        let mut tasks = self.0.lock().unwrap();
+        let mut panicked = false;
+
+        let mut index = 0;
+        while index < tasks.len() {
+            match Pin::new(&mut tasks[index]).poll(ctx) {
+                Poll::Ready(Some(())) => {
+                    tasks.swap_remove(index);
+                }
+                Poll::Ready(None) => {
+                    tasks.swap_remove(index);
+                    panicked = true;
+                }
+                Poll::Pending => index += 1,
+            }
+        }
+
+        if panicked {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    pub(crate) fn cancel(&self) {
+        // FIXME: panics?
+        let mut tasks = self.0.lock().unwrap();
+        trace!("Cancelling {} tasks.", tasks.len());
+        for task in tasks.drain(..) {
+            task.cancel();
+        }
+    }
+}
+
+impl<S: Clone + Send + 'static> State<S> {
+    // The checkpoint must have been created by a `StateInit`
+    // returning an `S`.
+    pub(crate) fn new(checkpoint: Checkpoint) -> Self {
+        State {
+            checkpoint,
+            _state: PhantomData,
+        }
+    }
+
+    /// Returns the last state saved using [`save`] by the element
+    /// this `State` was handed to (or by the elements it replaced
+    /// since its children group was created), or its initial
+    /// state if none was saved yet.
+    ///
+    /// [`save`]: #method.save
+    pub fn get(&self) -> S {
+        trace!("State: Loading.");
+        // FIXME: panics? (the type can't change)
+        self.checkpoint.load().unwrap()
+    }
+
+    /// Saves the state of the element this `State` was handed
+    /// to, which will be handed to the element replacing it if
+    /// its children group is restarted (eg. after it panicked)
+    /// instead of its initial state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state to save.
+    pub fn save(&self, state: S) {
+        trace!("State: Saving.");
+        self.checkpoint.save(state);
+    }
+}
+
+impl<S> fmt::Debug for State<S> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("State")
+            .field("checkpoint", &self.checkpoint)
+            .finish()
+    }
+}
+
+impl<T> Future for TaskHandle<T> {
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(ctx).map(Result::ok)
+    }
+}
+
 impl Display for BastionId {
     fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
         self.0.fmt(fmt)
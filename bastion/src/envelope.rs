@@ -5,7 +5,9 @@
 use crate::broadcast::Sender;
 use crate::message::{BastionMessage, Message, Msg};
 use crate::path::BastionPath;
-use crate::system::SYSTEM;
+use crate::system::BastionSystem;
+#[cfg(feature = "distributed")]
+use crate::transport::Link;
 use std::sync::Arc;
 
 #[derive(Debug)]
@@ -83,6 +85,42 @@ impl SignedMessage {
     pub fn signature(&self) -> &RefAddr {
         &self.sign
     }
+
+    /// Returns whether the message is of type `M`, without
+    /// consuming it (eg. to decide whether to handle it now or
+    /// to [`stash`] it).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             let msg: SignedMessage = ctx.recv().await?;
+    ///             if !msg.is::<&'static str>() {
+    ///                 ctx.stash(msg).await.map_err(|_| ())?;
+    ///             }
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`stash`]: ../context/struct.BastionContext.html#method.stash
+    pub fn is<M: Message>(&self) -> bool {
+        self.msg.is::<M>()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -116,18 +154,31 @@ impl SignedMessage {
 pub struct RefAddr {
     path: Arc<BastionPath>,
     sender: Sender,
+    // The link to the node the element is running on, if it
+    // is a remote element (in which case `sender` is the one
+    // of the dead letters children group).
+    #[cfg(feature = "distributed")]
+    link: Option<Link>,
 }
 
 impl RefAddr {
     pub(crate) fn new(path: Arc<BastionPath>, sender: Sender) -> Self {
-        RefAddr { path, sender }
+        RefAddr {
+            path,
+            sender,
+            #[cfg(feature = "distributed")]
+            link: None,
+        }
     }
 
-    pub(crate) fn dead_letters() -> Self {
-        Self::new(
-            SYSTEM.dead_letters().path().clone(),
-            SYSTEM.dead_letters().sender().clone(),
-        )
+    #[cfg(feature = "distributed")]
+    pub(crate) fn remote(path: BastionPath, link: Link) -> Self {
+        let path = path.with_node(Some(link.peer().clone()));
+        RefAddr {
+            path: Arc::new(path),
+            sender: link.system().dead_letters_addr().sender().clone(),
+            link: Some(link),
+        }
     }
 
     /// Checks whether the sender is identified.
@@ -217,6 +268,21 @@ impl RefAddr {
     pub(crate) fn sender(&self) -> &Sender {
         &self.sender
     }
+
+    /// Sends an envelope to the element, either directly or
+    /// over the link to the node it is running on.
+    pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
+        #[cfg(feature = "distributed")]
+        {
+            if let Some(link) = &self.link {
+                return link.send(&self.path, env);
+            }
+        }
+
+        self.sender
+            .unbounded_send(env)
+            .map_err(|err| err.into_inner())
+    }
 }
 
 impl Envelope {
@@ -231,10 +297,10 @@ impl Envelope {
         Envelope { msg, sign }
     }
 
-    pub(crate) fn from_dead_letters(msg: BastionMessage) -> Self {
+    pub(crate) fn from_dead_letters(system: &BastionSystem, msg: BastionMessage) -> Self {
         Envelope {
             msg,
-            sign: RefAddr::dead_letters(),
+            sign: system.dead_letters_addr(),
         }
     }
 
@@ -248,4 +314,8 @@ impl Envelope {
     pub(crate) fn into_msg<M: Message>(self) -> Option<M> {
         self.msg.into_msg()
     }
+
+    pub(crate) fn into_broadcast<M: Message + Sync>(self) -> Option<M> {
+        self.msg.into_broadcast()
+    }
 }
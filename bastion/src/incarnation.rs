@@ -0,0 +1,140 @@
+//!
+//! An incarnation keeps track of the element currently running
+//! in place of a children group or one of its elements, which
+//! changes each time it is restarted, and buffers the messages
+//! sent to it while it is restarting.
+use crate::envelope::{Envelope, RefAddr};
+use std::sync::{Arc, Mutex, RwLock};
+
+#[derive(Debug)]
+pub(crate) struct Incarnation {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct GroupIncarnation {
+    group: Incarnation,
+    // The incarnations of the group's elements, in the order
+    // of their index in the group.
+    elems: RwLock<Vec<Arc<Incarnation>>>,
+}
+
+#[derive(Debug)]
+enum State {
+    Running(RefAddr),
+    // The messages sent while the element is restarting,
+    // which will be sent to its next incarnation.
+    Restarting(Vec<Envelope>),
+    Stopped,
+}
+
+impl Incarnation {
+    pub(crate) fn new() -> Self {
+        let state = Mutex::new(State::Restarting(vec![]));
+
+        Incarnation { state }
+    }
+
+    pub(crate) fn update(&self, addr: RefAddr) {
+        trace!("Incarnation: Updating to {:?}.", addr.path());
+        // FIXME: panics?
+        let mut state = self.state.lock().unwrap();
+        let previous = std::mem::replace(&mut *state, State::Running(addr.clone()));
+
+        if let State::Restarting(buffered) = previous {
+            debug!(
+                "Incarnation: Sending {} buffered messages to {:?}.",
+                buffered.len(),
+                addr.path()
+            );
+            for env in buffered {
+                // FIXME: handle errors
+                addr.sender().unbounded_send(env).ok();
+            }
+        }
+    }
+
+    pub(crate) fn stopped(&self) {
+        // FIXME: panics?
+        let mut state = self.state.lock().unwrap();
+        if let State::Restarting(buffered) = &*state {
+            if !buffered.is_empty() {
+                warn!(
+                    "Incarnation: Dropping {} buffered messages.",
+                    buffered.len()
+                );
+            }
+        }
+
+        *state = State::Stopped;
+    }
+
+    pub(crate) fn current(&self) -> Option<RefAddr> {
+        // FIXME: panics?
+        let state = self.state.lock().unwrap();
+        match &*state {
+            State::Running(addr) if !addr.sender().is_closed() => Some(addr.clone()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
+        // FIXME: panics?
+        let mut state = self.state.lock().unwrap();
+        match &mut *state {
+            State::Running(addr) => {
+                if let Err(err) = addr.sender().unbounded_send(env) {
+                    // The element is gone and its next incarnation
+                    // will receive the message once it's launched.
+                    trace!("Incarnation: {:?} is restarting.", addr.path());
+                    *state = State::Restarting(vec![err.into_inner()]);
+                }
+
+                Ok(())
+            }
+            State::Restarting(buffered) => {
+                buffered.push(env);
+                Ok(())
+            }
+            State::Stopped => Err(env),
+        }
+    }
+}
+
+impl GroupIncarnation {
+    pub(crate) fn new() -> Self {
+        GroupIncarnation::default()
+    }
+
+    pub(crate) fn group(&self) -> &Incarnation {
+        &self.group
+    }
+
+    pub(crate) fn elem(&self, index: usize) -> Arc<Incarnation> {
+        // FIXME: panics?
+        let mut elems = self.elems.write().unwrap();
+        while elems.len() <= index {
+            elems.push(Arc::new(Incarnation::new()));
+        }
+
+        elems[index].clone()
+    }
+
+    pub(crate) fn elems(&self) -> Vec<Arc<Incarnation>> {
+        // FIXME: panics?
+        self.elems.read().unwrap().clone()
+    }
+
+    pub(crate) fn stopped(&self) {
+        self.group.stopped();
+        for elem in self.elems() {
+            elem.stopped();
+        }
+    }
+}
+
+impl Default for Incarnation {
+    fn default() -> Self {
+        Incarnation::new()
+    }
+}
@@ -64,36 +64,57 @@ extern crate bastion_qutex as qutex;
 pub use self::bastion::Bastion;
 pub use self::callbacks::Callbacks;
 pub use self::config::Config;
+pub use self::system::BastionSystem;
 
 mod bastion;
 mod broadcast;
 mod callbacks;
+mod checkpoint;
 mod child;
 mod config;
+mod incarnation;
+mod pubsub;
+mod registry;
 mod system;
+mod tree;
 
+pub mod actor;
 pub mod child_ref;
 pub mod children;
 pub mod children_ref;
+#[cfg(feature = "distributed")]
+pub mod cluster;
+#[cfg(feature = "distributed")]
+pub mod codec;
 pub mod context;
 pub mod envelope;
 pub mod message;
+pub mod monitor;
 pub mod path;
+pub mod persistence;
 pub mod supervisor;
+pub mod testkit;
+#[cfg(feature = "distributed")]
+pub mod transport;
+pub mod typed;
 
 ///
 /// Prelude of Bastion
 pub mod prelude {
+    pub use crate::actor::{Actor, Handler, Handlers, Request};
     pub use crate::bastion::Bastion;
     pub use crate::callbacks::Callbacks;
-    pub use crate::child_ref::ChildRef;
+    pub use crate::child_ref::{ChildRef, StableChildRef};
     pub use crate::children::Children;
-    pub use crate::children_ref::ChildrenRef;
+    pub use crate::children_ref::{ChildrenRef, StableChildrenRef};
     pub use crate::config::Config;
-    pub use crate::context::{BastionContext, BastionId, NIL_ID};
+    pub use crate::context::{BastionContext, BastionId, State, TaskHandle, NIL_ID};
     pub use crate::envelope::{RefAddr, SignedMessage};
     pub use crate::message::{Answer, AnswerSender, Message, Msg};
+    pub use crate::monitor::{Down, ExitReason, Terminated};
     pub use crate::msg;
     pub use crate::path::{BastionPath, BastionPathElement};
     pub use crate::supervisor::{SupervisionStrategy, Supervisor, SupervisorRef};
+    pub use crate::system::BastionSystem;
+    pub use crate::typed::{TypedChildRef, TypedChildren, TypedChildrenRef, TypedContext};
 }
@@ -11,24 +11,28 @@ use crate::context::BastionId;
 use crate::envelope::{RefAddr, SignedMessage};
 use crate::supervisor::{SupervisionStrategy, Supervisor};
 use futures::channel::oneshot::{self, Receiver};
-use std::any::{type_name, Any};
+use std::any::{type_name, Any, TypeId};
 use std::fmt::Debug;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 
 /// A trait that any message sent needs to implement (it is
 /// already automatically implemented but forces message to
-/// implement the following traits: [`Any`], [`Send`],
-/// [`Sync`] and [`Debug`]).
+/// implement the following traits: [`Any`], [`Send`] and
+/// [`Debug`]).
+///
+/// Messages that are broadcasted (or published) also need to
+/// implement [`Sync`], because every recipient receives a
+/// reference to the same message.
 ///
 /// [`Any`]: https://doc.rust-lang.org/std/any/trait.Any.html
 /// [`Send`]: https://doc.rust-lang.org/std/marker/trait.Send.html
 /// [`Sync`]: https://doc.rust-lang.org/std/marker/trait.Sync.html
 /// [`Debug`]: https://doc.rust-lang.org/std/fmt/trait.Debug.html
-pub trait Message: Any + Send + Sync + Debug {}
-impl<T> Message for T where T: Any + Send + Sync + Debug {}
+pub trait Message: Any + Send + Debug {}
+impl<T> Message for T where T: Any + Send + Debug {}
 
 #[derive(Debug)]
 #[doc(hidden)]
@@ -176,13 +180,24 @@ pub struct Msg(MsgInner);
 #[derive(Debug)]
 enum MsgInner {
     Broadcast(Arc<dyn Any + Send + Sync + 'static>),
-    Tell(Box<dyn Any + Send + Sync + 'static>),
+    Tell(Payload),
     Ask {
-        msg: Box<dyn Any + Send + Sync + 'static>,
+        msg: Payload,
         sender: Option<AnswerSender>,
     },
 }
 
+// A message that was told or asked, which only needs to be `Send`.
+// It is kept behind a `Mutex` (which is only locked to serialize
+// the message, since it is otherwise only accessed once owned)
+// so that `Msg` stays `Sync`, along with its `TypeId` so that it
+// can be checked without locking.
+#[derive(Debug)]
+struct Payload {
+    type_id: TypeId,
+    msg: Mutex<Box<dyn Any + Send + 'static>>,
+}
+
 #[derive(Debug)]
 pub(crate) enum BastionMessage {
     Start,
@@ -212,23 +227,29 @@ impl AnswerSender {
         trace!("{:?}: Sending message: {:?}", self, msg);
         self.0
             .send(SignedMessage::new(msg, sign))
-            .map_err(|smsg| smsg.msg.try_unwrap().unwrap())
+            .map_err(|smsg| smsg.msg.downcast().unwrap())
+    }
+
+    #[cfg(feature = "distributed")]
+    pub(crate) fn send_signed(self, msg: SignedMessage) -> Result<(), SignedMessage> {
+        debug!("{:?}: Sending answer: {:?}", self, msg);
+        self.0.send(msg)
     }
 }
 
 impl Msg {
-    pub(crate) fn broadcast<M: Message>(msg: M) -> Self {
+    pub(crate) fn broadcast<M: Message + Sync>(msg: M) -> Self {
         let inner = MsgInner::Broadcast(Arc::new(msg));
         Msg(inner)
     }
 
     pub(crate) fn tell<M: Message>(msg: M) -> Self {
-        let inner = MsgInner::Tell(Box::new(msg));
+        let inner = MsgInner::Tell(Payload::new(msg));
         Msg(inner)
     }
 
     pub(crate) fn ask<M: Message>(msg: M) -> (Self, Answer) {
-        let msg = Box::new(msg);
+        let msg = Payload::new(msg);
         let (sender, recver) = oneshot::channel();
         let sender = AnswerSender(sender);
         let answer = Answer(recver);
@@ -266,6 +287,33 @@ impl Msg {
         }
     }
 
+    // NOTE: this is used by the `msg!` macro to put back a
+    //      message that was downcasted but didn't match the
+    //      guard or pattern of an arm.
+    #[doc(hidden)]
+    pub fn restore<M: Message>(msg: M, ask: bool) -> Self {
+        if ask {
+            let msg = Payload::new(msg);
+            let inner = MsgInner::Ask { msg, sender: None };
+            Msg(inner)
+        } else {
+            Msg::tell(msg)
+        }
+    }
+
+    // Gives a new sender to an asked message which was decoded
+    // (see `codec`), returning the answer it will send.
+    #[cfg(feature = "distributed")]
+    pub(crate) fn attach_sender(&mut self) -> Option<Answer> {
+        if let MsgInner::Ask { sender, .. } = &mut self.0 {
+            let (new, recver) = oneshot::channel();
+            *sender = Some(AnswerSender(new));
+            Some(Answer(recver))
+        } else {
+            None
+        }
+    }
+
     #[doc(hidden)]
     pub fn take_sender(&mut self) -> Option<AnswerSender> {
         debug!("{:?}: Taking sender.", self);
@@ -289,30 +337,26 @@ impl Msg {
     pub fn downcast<M: Message>(self) -> Result<M, Self> {
         trace!("{:?}: Downcasting to {}.", self, type_name::<M>());
         match self.0 {
-            MsgInner::Tell(msg) => {
-                if msg.is::<M>() {
-                    let msg: Box<dyn Any + 'static> = msg;
-                    Ok(*msg.downcast().unwrap())
-                } else {
+            MsgInner::Tell(msg) => match msg.downcast() {
+                Ok(msg) => Ok(msg),
+                Err(msg) => {
                     let inner = MsgInner::Tell(msg);
                     Err(Msg(inner))
                 }
-            }
-            MsgInner::Ask { msg, sender } => {
-                if msg.is::<M>() {
-                    let msg: Box<dyn Any + 'static> = msg;
-                    Ok(*msg.downcast().unwrap())
-                } else {
+            },
+            MsgInner::Ask { msg, sender } => match msg.downcast() {
+                Ok(msg) => Ok(msg),
+                Err(msg) => {
                     let inner = MsgInner::Ask { msg, sender };
                     Err(Msg(inner))
                 }
-            }
+            },
             _ => Err(self),
         }
     }
 
     #[doc(hidden)]
-    pub fn downcast_ref<M: Message>(&self) -> Option<Arc<M>> {
+    pub fn downcast_ref<M: Message + Sync>(&self) -> Option<Arc<M>> {
         trace!("{:?}: Downcasting to ref of {}.", self, type_name::<M>());
         if let MsgInner::Broadcast(msg) = &self.0 {
             if msg.is::<M>() {
@@ -323,6 +367,27 @@ impl Msg {
         None
     }
 
+    // Calls `f` with a reference to the message if it is of
+    // type `M`.
+    #[cfg(feature = "distributed")]
+    pub(crate) fn map_ref<M: Message, R>(&self, f: impl FnOnce(&M) -> R) -> Option<R> {
+        match &self.0 {
+            MsgInner::Broadcast(msg) => msg.downcast_ref().map(f),
+            MsgInner::Tell(msg) => msg.map_ref(f),
+            MsgInner::Ask { msg, .. } => msg.map_ref(f),
+        }
+    }
+
+    pub(crate) fn type_id(&self) -> TypeId {
+        // NOTE: `type_id` needs to be called on the message itself
+        //      and not on the `Box` or `Arc` containing it.
+        match &self.0 {
+            MsgInner::Broadcast(msg) => (**msg).type_id(),
+            MsgInner::Tell(msg) => msg.type_id,
+            MsgInner::Ask { msg, .. } => msg.type_id,
+        }
+    }
+
     pub(crate) fn try_clone(&self) -> Option<Self> {
         trace!("{:?}: Trying to clone.", self);
         if let MsgInner::Broadcast(msg) = &self.0 {
@@ -333,7 +398,7 @@ impl Msg {
         }
     }
 
-    pub(crate) fn try_unwrap<M: Message>(self) -> Result<M, Self> {
+    pub(crate) fn try_unwrap<M: Message + Sync>(self) -> Result<M, Self> {
         debug!("{:?}: Trying to unwrap.", self);
         if let MsgInner::Broadcast(msg) = self.0 {
             match msg.downcast() {
@@ -355,6 +420,38 @@ impl Msg {
     }
 }
 
+impl Payload {
+    fn new<M: Message>(msg: M) -> Self {
+        let type_id = TypeId::of::<M>();
+        let msg = Mutex::new(Box::new(msg) as Box<dyn Any + Send>);
+
+        Payload { type_id, msg }
+    }
+
+    fn is<M: Message>(&self) -> bool {
+        self.type_id == TypeId::of::<M>()
+    }
+
+    #[cfg(feature = "distributed")]
+    fn map_ref<M: Message, R>(&self, f: impl FnOnce(&M) -> R) -> Option<R> {
+        let msg = self.msg.lock().unwrap_or_else(|err| err.into_inner());
+        msg.downcast_ref().map(f)
+    }
+
+    fn downcast<M: Message>(self) -> Result<M, Self> {
+        if !self.is::<M>() {
+            return Err(self);
+        }
+
+        // The mutex is only poisoned if serializing the message
+        // panicked, in which case it is still usable.
+        let msg = self.msg.into_inner().unwrap_or_else(|err| err.into_inner());
+        let msg: Box<dyn Any + 'static> = msg;
+        // FIXME: panics?
+        Ok(*msg.downcast().unwrap())
+    }
+}
+
 impl BastionMessage {
     pub(crate) fn start() -> Self {
         BastionMessage::Start
@@ -388,7 +485,7 @@ impl BastionMessage {
         BastionMessage::SuperviseWith(strategy)
     }
 
-    pub(crate) fn broadcast<M: Message>(msg: M) -> Self {
+    pub(crate) fn broadcast<M: Message + Sync>(msg: M) -> Self {
         let msg = Msg::broadcast(msg);
         BastionMessage::Message(msg)
     }
@@ -432,6 +529,14 @@ impl BastionMessage {
     }
 
     pub(crate) fn into_msg<M: Message>(self) -> Option<M> {
+        if let BastionMessage::Message(msg) = self {
+            msg.downcast().ok()
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn into_broadcast<M: Message + Sync>(self) -> Option<M> {
         if let BastionMessage::Message(msg) = self {
             msg.try_unwrap().ok()
         } else {
@@ -455,25 +560,36 @@ impl Future for Answer {
 ///
 /// Each case is defined as:
 /// - an optional `ref` which will make the case only match
-///   if the message was broadcasted
-/// - a variable name for the message if it matched this case
+///   if the message was broadcasted, or `any` which will make
+///   it match if the message was either broadcasted or "told"
+/// - a pattern for the message if it matched this case (eg. a
+///   variable name or a tuple or struct pattern)
 /// - a colon
-/// - a type that the message must be of to match this case
-///   (note that if the message was broadcasted, the actual
-///   type of the variable will be a reference to this type)
+/// - a type that the message must be of to match this case, or
+///   several types separated by `|` (in which case the code will
+///   be used for each of them)
+///   (note that if the message was broadcasted or if the case
+///   starts with `any`, the actual type of the variable will be a
+///   reference to this type)
+/// - an optional guard (`if` followed by a condition) which
+///   must be true for the case to match
 /// - an arrow (`=>`) with an optional bang (`!`) between
 ///   the equal and greater-than signs which will make the
 ///   case only match if the message can be answered
 /// - code that will be executed if the case matches
 ///
-/// If the message can be answered (when using `=!>` instead
-/// of `=>` as said above), an answer can be sent by passing
-/// it to the `answer!` macro that will be generated for this
-/// use.
+/// The cases are tried in order and the first one matching the
+/// message is used.
+///
+/// If the message can be answered, an answer can be sent by
+/// passing it to the `answer!` macro that will be generated for
+/// this use, which returns `Err(answer)` if it can't (eg. when
+/// the message was "told").
 ///
 /// A default case is required, which is defined in the same
 /// way as any other case but with its type set as `_` (note
-/// that it doesn't has the optional `ref` or `=!>`).
+/// that it doesn't has the optional `ref`, `any`, guard or
+/// `=!>`).
 ///
 /// # Example
 ///
@@ -533,145 +649,259 @@ impl Future for Answer {
 /// # }
 /// ```
 ///
+/// The cases can also use patterns, guards and several types:
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// #[derive(Debug)]
+/// struct Point {
+///     x: i32,
+///     y: i32,
+/// }
+///
+/// # fn main() {
+///     # Bastion::init();
+/// Bastion::children(|children| {
+///     children.with_exec(|ctx: BastionContext| {
+///         async move {
+///             loop {
+///                 msg! { ctx.recv().await?,
+///                     // We match the `Point`s on the x axis...
+///                     Point { x, y: 0 }: Point => {
+///                         println!("x = {}", x);
+///                     };
+///                     // ...the big `u32`s and `u64`s...
+///                     n: u32 | u64 if n > 1_000 => {
+///                         println!("n = {}", n);
+///                     };
+///                     // ...and the `String`s which were either broadcasted
+///                     // or "told" to this child, as `&String`s.
+///                     any msg: String => {
+///                         println!("msg = {}", msg);
+///                     };
+///                     _: _ => ();
+///                 }
+///             }
+///         }
+///     })
+/// }).expect("Couldn't start the children group.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
 /// [`Msg`]: children/struct.Msg.html
 /// [`BastionContext::recv`]: context/struct.BastionContext.html#method.recv
 /// [`BastionContext::try_recv`]: context/struct.BastionContext.html#method.try_recv
 macro_rules! msg {
-    ($msg:expr, $($tokens:tt)+) => {
-        msg!(@internal $msg, (), (), (), $($tokens)+)
+    ($msg:expr, $($tokens:tt)+) => { {
+        let signed = $msg;
+
+        let (mut msg, sign) = signed.extract();
+
+        macro_rules! signature {
+            () => {
+                sign
+            };
+        }
+
+        #[allow(unused_mut)]
+        let mut sender = msg.take_sender();
+
+        macro_rules! answer {
+            ($ctx:expr, $answer:expr) => {
+                {
+                    let answer = $answer;
+                    let sign = $ctx.signature();
+                    match sender.take() {
+                        Some(sender) => sender.send(answer, sign),
+                        None => Err(answer),
+                    }
+                }
+            };
+        }
+
+        let is_broadcast = msg.is_broadcast();
+        let is_ask = sender.is_some();
+        let mut msg = Some(msg);
+        let mut handled = None;
+
+        msg!(@arm (msg, handled, is_broadcast, is_ask), $($tokens)+)
+    } };
+
+    // The default case, which must be the last one.
+    (@arm $state:tt, _: _ => $handle:expr;) => {
+        msg!(@default $state, _msg, $handle)
     };
 
-    (@internal
-        $msg:expr,
-        ($($bvar:ident, $bty:ty, $bhandle:expr,)*),
-        ($($tvar:ident, $tty:ty, $thandle:expr,)*),
-        ($($avar:ident, $aty:ty, $ahandle:expr,)*),
-        ref $var:ident: $ty:ty => $handle:expr;
-        $($rest:tt)+
-    ) => {
-        msg!(@internal $msg,
-            ($($bvar, $bty, $bhandle,)* $var, $ty, $handle,),
-            ($($tvar, $tty, $thandle,)*),
-            ($($avar, $aty, $ahandle,)*),
-            $($rest)+
-        )
+    (@arm $state:tt, $var:ident: _ => $handle:expr;) => {
+        msg!(@default $state, $var, $handle)
     };
 
-    (@internal
-        $msg:expr,
-        ($($bvar:ident, $bty:ty, $bhandle:expr,)*),
-        ($($tvar:ident, $tty:ty, $thandle:expr,)*),
-        ($($avar:ident, $aty:ty, $ahandle:expr,)*),
-        $var:ident: $ty:ty => $handle:expr;
-        $($rest:tt)+
-    ) => {
-        msg!(@internal $msg,
-            ($($bvar, $bty, $bhandle,)*),
-            ($($tvar, $tty, $thandle,)* $var, $ty, $handle,),
-            ($($avar, $aty, $ahandle,)*),
-            $($rest)+
-        )
+    // A case only matching broadcasted messages...
+    (@arm $state:tt, ref $($rest:tt)+) => {
+        msg!(@pat $state, broadcast, (), $($rest)+)
     };
 
-    (@internal
-        $msg:expr,
-        ($($bvar:ident, $bty:ty, $bhandle:expr,)*),
-        ($($tvar:ident, $tty:ty, $thandle:expr,)*),
-        ($($avar:ident, $aty:ty, $ahandle:expr,)*),
-        $var:ident: $ty:ty =!> $handle:expr;
-        $($rest:tt)+
-    ) => {
-        msg!(@internal $msg,
-            ($($bvar, $bty, $bhandle,)*),
-            ($($tvar, $tty, $thandle,)*),
-            ($($avar, $aty, $ahandle,)* $var, $ty, $handle,),
-            $($rest)+
-        )
+    // ...a case matching variables named `any`...
+    (@arm $state:tt, any: $($rest:tt)+) => {
+        msg!(@pat $state, tell, (), any: $($rest)+)
     };
 
-    (@internal
-        $msg:expr,
-        ($($bvar:ident, $bty:ty, $bhandle:expr,)*),
-        ($($tvar:ident, $tty:ty, $thandle:expr,)*),
-        ($($avar:ident, $aty:ty, $ahandle:expr,)*),
-        _: _ => $handle:expr;
-    ) => {
-        msg!(@internal $msg,
-            ($($bvar, $bty, $bhandle,)*),
-            ($($tvar, $tty, $thandle,)*),
-            ($($avar, $aty, $ahandle,)*),
-            msg: _ => $handle;
-        )
+    // ...a case matching broadcasted or "told" messages...
+    (@arm $state:tt, any $($rest:tt)+) => {
+        msg!(@pat $state, any, (), $($rest)+)
     };
 
-    (@internal
-        $msg:expr,
-        ($($bvar:ident, $bty:ty, $bhandle:expr,)*),
-        ($($tvar:ident, $tty:ty, $thandle:expr,)*),
-        ($($avar:ident, $aty:ty, $ahandle:expr,)*),
-        $var:ident: _ => $handle:expr;
-    ) => { {
-        let mut signed = $msg;
+    // ...and a case matching "told" or "asked" messages.
+    (@arm $state:tt, $($rest:tt)+) => {
+        msg!(@pat $state, tell, (), $($rest)+)
+    };
 
-        let (mut $var, sign) = signed.extract();
+    // The pattern of a case is everything before its colon.
+    (@pat $state:tt, $kind:ident, ($($pat:tt)+), : $($rest:tt)+) => {
+        msg!(@ty $state, $kind, ($($pat)+), (), $($rest)+)
+    };
 
-        macro_rules! signature {
-            () => {
-                sign
-            };
-        }
+    (@pat $state:tt, $kind:ident, ($($pat:tt)*), $tok:tt $($rest:tt)+) => {
+        msg!(@pat $state, $kind, ($($pat)* $tok), $($rest)+)
+    };
 
-        let sender = $var.take_sender();
-        if $var.is_broadcast() {
-            if false {
-                unreachable!();
-            }
-            $(
-                else if $var.is::<$bty>() {
-                    let $bvar = &*$var.downcast_ref::<$bty>().unwrap();
-                    { $bhandle }
+    // The types of a case are separated by `|` and followed by
+    // an optional guard and by `=>` (or `=!>`).
+    (@ty $state:tt, $kind:ident, $pat:tt, ($($tys:tt)*), $ty:ty => $handle:expr; $($rest:tt)+) => {
+        msg!(@case $state, $kind, $pat, ($($tys)* ($ty)), (true), $handle, $($rest)+)
+    };
+
+    (@ty $state:tt, $kind:ident, $pat:tt, ($($tys:tt)*), $ty:ty =!> $handle:expr; $($rest:tt)+) => {
+        msg!(@case $state, ask, $pat, ($($tys)* ($ty)), (true), $handle, $($rest)+)
+    };
+
+    (@ty $state:tt, $kind:ident, $pat:tt, ($($tys:tt)*), $ty:ty | $($rest:tt)+) => {
+        msg!(@ty $state, $kind, $pat, ($($tys)* ($ty)), $($rest)+)
+    };
+
+    (@ty $state:tt, $kind:ident, $pat:tt, $tys:tt, $($rest:tt)+) => {
+        msg!(@guarded_ty $state, $kind, $pat, $tys, (), $($rest)+)
+    };
+
+    // NOTE: types can't be followed by `if` so the tokens of the
+    //      last type of a guarded case are gathered one by one.
+    (@guarded_ty $state:tt, $kind:ident, $pat:tt, ($($tys:tt)*), ($($ty:tt)+), if $($rest:tt)+) => {
+        msg!(@guard $state, $kind, $pat, ($($tys)* ($($ty)+)), (), $($rest)+)
+    };
+
+    (@guarded_ty $state:tt, $kind:ident, $pat:tt, $tys:tt, ($($ty:tt)*), $tok:tt $($rest:tt)+) => {
+        msg!(@guarded_ty $state, $kind, $pat, $tys, ($($ty)* $tok), $($rest)+)
+    };
+
+    (@guard $state:tt, $kind:ident, $pat:tt, $tys:tt, ($($guard:tt)+), => $handle:expr; $($rest:tt)+) => {
+        msg!(@case $state, $kind, $pat, $tys, ($($guard)+), $handle, $($rest)+)
+    };
+
+    (@guard $state:tt, $kind:ident, $pat:tt, $tys:tt, ($($guard:tt)+), =!> $handle:expr; $($rest:tt)+) => {
+        msg!(@case $state, ask, $pat, $tys, ($($guard)+), $handle, $($rest)+)
+    };
+
+    (@guard $state:tt, $kind:ident, $pat:tt, $tys:tt, ($($guard:tt)*), $tok:tt $($rest:tt)+) => {
+        msg!(@guard $state, $kind, $pat, $tys, ($($guard)* $tok), $($rest)+)
+    };
+
+    // Each case is tried for each of its types, in order, and
+    // then the next cases are tried if it didn't match.
+    (@case $state:tt, $kind:ident, $pat:tt, ($($ty:tt)+), $guard:tt, $handle:expr, $($rest:tt)+) => { {
+        $(
+            msg!(@try $state, $kind, $pat, $ty, $guard, $handle);
+        )+
+
+        msg!(@arm $state, $($rest)+)
+    } };
+
+    (@try ($msg:ident, $handled:ident, $is_broadcast:ident, $is_ask:ident), broadcast, ($($pat:tt)+), ($ty:ty), ($($guard:tt)+), $handle:expr) => {
+        if $handled.is_none() && $is_broadcast {
+            if let Some(msg) = $msg.as_ref().and_then(|msg| msg.downcast_ref::<$ty>()) {
+                match &*msg {
+                    #[allow(unreachable_code)]
+                    $($pat)+ if $($guard)+ => $handled = Some($handle),
+                    _ => (),
                 }
-            )*
-            else {
-                { $handle }
-            }
-        } else if sender.is_some() {
-            let sender = sender.unwrap();
-
-            macro_rules! answer {
-                ($ctx:expr, $answer:expr) => {
-                    {
-                        let sign = $ctx.signature();
-                        sender.send($answer, sign)
-                    }
-                };
             }
+        }
+    };
 
-            if false {
-                unreachable!();
-            }
-            $(
-                else if $var.is::<$aty>() {
-                    let $avar = $var.downcast::<$aty>().unwrap();
-                    { $ahandle }
+    (@try ($msg:ident, $handled:ident, $is_broadcast:ident, $is_ask:ident), tell, ($($pat:tt)+), ($ty:ty), ($($guard:tt)+), $handle:expr) => {
+        if $handled.is_none() && !$is_broadcast && !$is_ask {
+            if let Some(msg) = $msg.take() {
+                match msg.downcast::<$ty>() {
+                    Ok(msg) => match msg {
+                        #[allow(unreachable_code)]
+                        $($pat)+ if $($guard)+ => $handled = Some($handle),
+                        msg => $msg = Some($crate::message::Msg::restore(msg, false)),
+                    },
+                    Err(msg) => $msg = Some(msg),
                 }
-            )*
-            else {
-                { $handle }
             }
-        } else {
-            if false {
-                unreachable!();
+        }
+    };
+
+    (@try ($msg:ident, $handled:ident, $is_broadcast:ident, $is_ask:ident), ask, ($($pat:tt)+), ($ty:ty), ($($guard:tt)+), $handle:expr) => {
+        if $handled.is_none() && $is_ask {
+            if let Some(msg) = $msg.take() {
+                match msg.downcast::<$ty>() {
+                    Ok(msg) => match msg {
+                        #[allow(unreachable_code)]
+                        $($pat)+ if $($guard)+ => $handled = Some($handle),
+                        msg => $msg = Some($crate::message::Msg::restore(msg, true)),
+                    },
+                    Err(msg) => $msg = Some(msg),
+                }
             }
-            $(
-                else if $var.is::<$tty>() {
-                    let $tvar = $var.downcast::<$tty>().unwrap();
-                    { $thandle }
+        }
+    };
+
+    (@try ($msg:ident, $handled:ident, $is_broadcast:ident, $is_ask:ident), any, $pat:tt, $ty:tt, $guard:tt, $handle:expr) => {
+        if $is_broadcast {
+            msg!(@try ($msg, $handled, $is_broadcast, $is_ask), broadcast, $pat, $ty, $guard, $handle);
+        } else if $handled.is_none() && !$is_ask {
+            msg!(@try_ref ($msg, $handled), $pat, $ty, $guard, $handle);
+        }
+    };
+
+    // Matches a "told" message by reference, like a broadcasted
+    // one.
+    (@try_ref ($msg:ident, $handled:ident), ($($pat:tt)+), ($ty:ty), ($($guard:tt)+), $handle:expr) => {
+        if let Some(msg) = $msg.take() {
+            match msg.downcast::<$ty>() {
+                Ok(msg) => {
+                    let matched = match &msg {
+                        #[allow(unreachable_code)]
+                        $($pat)+ if $($guard)+ => {
+                            $handled = Some($handle);
+                            true
+                        }
+                        _ => false,
+                    };
+
+                    if !matched {
+                        $msg = Some($crate::message::Msg::restore(msg, false));
+                    }
                 }
-            )*
-            else {
-                { $handle }
+                Err(msg) => $msg = Some(msg),
             }
         }
-    } };
+    };
+
+    (@default ($msg:ident, $handled:ident, $is_broadcast:ident, $is_ask:ident), $var:ident, $handle:expr) => {
+        match $handled {
+            Some(handled) => handled,
+            None => {
+                #[allow(unused_mut)]
+                let mut $var = $msg.take().unwrap();
+                $handle
+            }
+        }
+    };
 }
@@ -0,0 +1,371 @@
+//!
+//! Monitors and links allow children to be notified of, or
+//! to share, the termination of other children, even when
+//! they are not supervised by the same supervisor.
+//!
+//! The termination of children, children groups and
+//! supervisors can also be awaited from outside of the
+//! system (see [`Terminated`]).
+//!
+//! [`Terminated`]: struct.Terminated.html
+use crate::broadcast::Sender;
+use crate::child_ref::ChildRef;
+use crate::context::BastionId;
+use crate::envelope::{Envelope, RefAddr};
+use crate::message::BastionMessage;
+use futures::channel::oneshot::{self, Receiver};
+use fxhash::FxHashMap;
+#[cfg(feature = "distributed")]
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Mutex, RwLock};
+use std::task::{Context, Poll};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "distributed", derive(Serialize, Deserialize))]
+/// The reason why a child terminated.
+pub enum ExitReason {
+    /// The child's future returned `Ok(())` or the child was
+    /// asked to stop.
+    Stopped,
+    /// The child was killed, either explicitly or because
+    /// another element of its children group stopped or
+    /// faulted.
+    Killed,
+    /// The child's future returned `Err(())` or panicked, or
+    /// a child it was linked to faulted.
+    Faulted,
+    /// The child was already gone when it started being
+    /// monitored (or awaited), so the reason of its
+    /// termination isn't known.
+    Unknown,
+}
+
+#[derive(Debug)]
+/// The message received by a child monitoring another one
+/// (using [`BastionContext::monitor`]) when the latter
+/// terminates.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// # fn main() {
+///     # Bastion::init();
+///     #
+/// let worker = Bastion::children(|children| {
+///     // ...
+///     # children
+/// }).expect("Couldn't create the children group.");
+///
+/// Bastion::children(|children| {
+///     children.with_exec(move |ctx: BastionContext| {
+///         let worker = worker.elems()[0].clone();
+///         async move {
+///             ctx.monitor(&worker);
+///
+///             msg! { ctx.recv().await?,
+///                 down: Down => {
+///                     assert_eq!(down.id(), worker.id());
+///                     // Handle the worker's termination...
+///                 };
+///                 _: _ => ();
+///             }
+///
+///             Ok(())
+///         }
+///     })
+/// }).expect("Couldn't create the children group.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`BastionContext::monitor`]: ../context/struct.BastionContext.html#method.monitor
+pub struct Down {
+    id: BastionId,
+    reason: ExitReason,
+}
+
+#[derive(Debug)]
+/// A [`Future`] returned by [`ChildRef::terminated`],
+/// [`ChildrenRef::terminated`] and [`SupervisorRef::terminated`]
+/// which resolves to the [`ExitReason`] of the child, children
+/// group or supervisor once it terminated.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// # use futures::executor;
+/// #
+/// # fn main() {
+///     # Bastion::init();
+///     # Bastion::start();
+/// let children_ref = Bastion::children(|children| {
+///     children.with_exec(|ctx: BastionContext| {
+///         async move {
+///             // ...
+///             # Ok(())
+///         }
+///     })
+/// }).expect("Couldn't create the children group.");
+///
+/// let terminated: Terminated = children_ref.terminated();
+/// let reason = executor::block_on(terminated);
+/// assert_eq!(reason, ExitReason::Stopped);
+///     #
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`Future`]: https://doc.rust-lang.org/std/future/trait.Future.html
+/// [`ChildRef::terminated`]: ../child_ref/struct.ChildRef.html#method.terminated
+/// [`ChildrenRef::terminated`]: ../children_ref/struct.ChildrenRef.html#method.terminated
+/// [`SupervisorRef::terminated`]: ../supervisor/struct.SupervisorRef.html#method.terminated
+/// [`ExitReason`]: enum.ExitReason.html
+pub struct Terminated(Receiver<ExitReason>);
+
+#[derive(Debug, Default)]
+pub(crate) struct Monitors {
+    // The children watching each child.
+    watchers: RwLock<FxHashMap<BastionId, Vec<Watcher>>>,
+    // The `Terminated` futures waiting for each child,
+    // children group or supervisor.
+    waiting: Mutex<FxHashMap<BastionId, Vec<oneshot::Sender<ExitReason>>>>,
+}
+
+#[derive(Debug)]
+enum Watcher {
+    Monitor(ChildRef),
+    Link(ChildRef),
+}
+
+impl Down {
+    pub(crate) fn new(id: BastionId, reason: ExitReason) -> Self {
+        Down { id, reason }
+    }
+
+    /// Returns the identifier of the child that terminated.
+    pub fn id(&self) -> &BastionId {
+        &self.id
+    }
+
+    /// Returns the reason why the child terminated.
+    pub fn reason(&self) -> ExitReason {
+        self.reason
+    }
+}
+
+impl ExitReason {
+    /// Returns whether the child terminated because of a fault.
+    pub fn is_faulted(&self) -> bool {
+        *self == ExitReason::Faulted
+    }
+}
+
+impl Monitors {
+    pub(crate) fn new() -> Self {
+        Monitors::default()
+    }
+
+    pub(crate) fn monitor(&self, watcher: &ChildRef, target: &ChildRef) {
+        // FIXME: panics?
+        let mut watchers = self.watchers.write().unwrap();
+        if !target.is_alive() {
+            debug!(
+                "Monitors: Child({}) is already gone, notifying Child({}).",
+                target.id(),
+                watcher.id()
+            );
+            Watcher::Monitor(watcher.clone()).notify(
+                target.id(),
+                target.addr(),
+                ExitReason::Unknown,
+            );
+            return;
+        }
+
+        debug!(
+            "Monitors: Child({}) monitoring Child({}).",
+            watcher.id(),
+            target.id()
+        );
+        watchers
+            .entry(target.id().clone())
+            .or_default()
+            .push(Watcher::Monitor(watcher.clone()));
+    }
+
+    pub(crate) fn demonitor(&self, watcher: &BastionId, target: &BastionId) {
+        trace!(
+            "Monitors: Child({}) demonitoring Child({}).",
+            watcher,
+            target
+        );
+        // FIXME: panics?
+        let mut watchers = self.watchers.write().unwrap();
+        if let Some(target) = watchers.get_mut(target) {
+            target.retain(|w| !(w.is_monitor() && w.child().id() == watcher));
+        }
+    }
+
+    pub(crate) fn link(&self, child: &ChildRef, other: &ChildRef) {
+        // FIXME: panics?
+        let mut watchers = self.watchers.write().unwrap();
+        if !other.is_alive() {
+            debug!(
+                "Monitors: Child({}) is already gone, faulting Child({}).",
+                other.id(),
+                child.id()
+            );
+            Watcher::Link(child.clone()).notify(other.id(), other.addr(), ExitReason::Faulted);
+            return;
+        }
+
+        debug!(
+            "Monitors: Linking Child({}) and Child({}).",
+            child.id(),
+            other.id()
+        );
+        watchers
+            .entry(other.id().clone())
+            .or_default()
+            .push(Watcher::Link(child.clone()));
+        watchers
+            .entry(child.id().clone())
+            .or_default()
+            .push(Watcher::Link(other.clone()));
+    }
+
+    pub(crate) fn unlink(&self, child: &BastionId, other: &BastionId) {
+        trace!("Monitors: Unlinking Child({}) and Child({}).", child, other);
+        // FIXME: panics?
+        let mut watchers = self.watchers.write().unwrap();
+        if let Some(watchers) = watchers.get_mut(other) {
+            watchers.retain(|w| !(w.is_link() && w.child().id() == child));
+        }
+
+        if let Some(watchers) = watchers.get_mut(child) {
+            watchers.retain(|w| !(w.is_link() && w.child().id() == other));
+        }
+    }
+
+    pub(crate) fn terminated(&self, id: &BastionId, sender: &Sender) -> Terminated {
+        let (notifier, terminated) = oneshot::channel();
+        // FIXME: panics?
+        let mut waiting = self.waiting.lock().unwrap();
+        // NOTE: elements close their mailbox before notifying
+        //      that they terminated, so checking it while holding
+        //      the lock ensures that the notification isn't missed.
+        if sender.is_closed() {
+            trace!("Monitors: Element({}) is already gone.", id);
+            notifier.send(ExitReason::Unknown).ok();
+        } else {
+            waiting.entry(id.clone()).or_default().push(notifier);
+        }
+
+        Terminated(terminated)
+    }
+
+    pub(crate) fn notify_terminated(&self, id: &BastionId, reason: ExitReason) {
+        // FIXME: panics?
+        let notifiers = self.waiting.lock().unwrap().remove(id);
+        if let Some(notifiers) = notifiers {
+            debug!(
+                "Monitors: Element({}) terminated ({:?}), notifying {} futures.",
+                id,
+                reason,
+                notifiers.len()
+            );
+            for notifier in notifiers {
+                // The future might have been dropped.
+                notifier.send(reason).ok();
+            }
+        }
+    }
+
+    pub(crate) fn exited(&self, id: &BastionId, sign: RefAddr, reason: ExitReason) {
+        self.notify_terminated(id, reason);
+
+        let exited = {
+            // FIXME: panics?
+            let mut watchers = self.watchers.write().unwrap();
+            // The child won't be watching anything anymore.
+            watchers.retain(|_, watchers| {
+                watchers.retain(|w| w.child().id() != id);
+                !watchers.is_empty()
+            });
+
+            watchers.remove(id)
+        };
+
+        if let Some(exited) = exited {
+            debug!(
+                "Monitors: Child({}) exited ({:?}), notifying {} watchers.",
+                id,
+                reason,
+                exited.len()
+            );
+            for watcher in exited {
+                watcher.notify(id, sign.clone(), reason);
+            }
+        }
+    }
+}
+
+impl Future for Terminated {
+    type Output = ExitReason;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        Pin::new(&mut self.get_mut().0)
+            .poll(ctx)
+            .map(|reason| reason.unwrap_or(ExitReason::Unknown))
+    }
+}
+
+impl Watcher {
+    fn child(&self) -> &ChildRef {
+        match self {
+            Watcher::Monitor(child) => child,
+            Watcher::Link(child) => child,
+        }
+    }
+
+    fn is_monitor(&self) -> bool {
+        matches!(self, Watcher::Monitor(_))
+    }
+
+    fn is_link(&self) -> bool {
+        matches!(self, Watcher::Link(_))
+    }
+
+    fn notify(self, id: &BastionId, sign: RefAddr, reason: ExitReason) {
+        match self {
+            Watcher::Monitor(child) => {
+                let msg = BastionMessage::tell(Down::new(id.clone(), reason));
+                let env = Envelope::new_with_sign(msg, sign);
+                // FIXME: handle errors
+                child.send(env).ok();
+            }
+            // Links only propagate faults.
+            Watcher::Link(child) if reason.is_faulted() => {
+                debug!(
+                    "Monitors: Faulting Child({}) linked to Child({}).",
+                    child.id(),
+                    id
+                );
+                let msg = BastionMessage::faulted(id.clone());
+                let env = Envelope::new_with_sign(msg, sign);
+                // FIXME: handle errors
+                child.send(env).ok();
+            }
+            Watcher::Link(_) => (),
+        }
+    }
+}
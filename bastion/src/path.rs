@@ -3,10 +3,20 @@
 //! later will be used to route messages to them
 
 use crate::context::{BastionId, NIL_ID};
+#[cfg(feature = "distributed")]
+use crate::transport::NodeId;
+#[cfg(feature = "distributed")]
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::result::Result;
+use std::str::FromStr;
+
+const SEPARATOR: char = '/';
+const KIND_SEPARATOR: char = '#';
+const WILDCARD: &str = "*";
 
 #[derive(Clone)]
+#[cfg_attr(feature = "distributed", derive(Serialize, Deserialize))]
 /// Represents a Path for a System, Supervisor, Children or Child.
 ///
 /// BastionPath can be used to identify message senders.
@@ -47,6 +57,14 @@ pub struct BastionPath {
     // TODO: possibly more effective collection depending on how we'll use it in routing
     parent_chain: Vec<BastionId>,
     this: Option<BastionPathElement>,
+    // The name of each element (if it has one), in the same
+    // order as `iter`.
+    names: Vec<Option<String>>,
+    // The node the element is running on, if it was received
+    // from or is addressed to another node.
+    #[cfg(feature = "distributed")]
+    #[serde(default)]
+    node: Option<NodeId>,
 }
 
 impl BastionPath {
@@ -55,6 +73,9 @@ impl BastionPath {
         BastionPath {
             parent_chain: vec![],
             this: None,
+            names: vec![],
+            #[cfg(feature = "distributed")]
+            node: None,
         }
     }
 
@@ -64,6 +85,153 @@ impl BastionPath {
         parent_iter.chain(self.this.iter().map(|e| e.id()))
     }
 
+    /// iterates over path elements' names
+    pub(crate) fn names(&self) -> impl Iterator<Item = Option<&str>> {
+        self.names
+            .iter()
+            .map(|name| name.as_ref().map(String::as_str))
+    }
+
+    /// Returns the last element's name, if it was given one
+    /// (see [`Supervisor::with_name`] and
+    /// [`Children::with_name`]) or if it is a child, which is
+    /// named after its index in its children group.
+    ///
+    /// Names, unlike identifiers, stay the same when an element
+    /// is restarted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::supervisor(|sp| {
+    ///     sp.with_name("ingest").children(|children| {
+    ///         children
+    ///             .with_name("workers")
+    ///             .with_redundancy(4)
+    ///             .with_exec(|ctx: BastionContext| {
+    ///                 async move {
+    ///                     let signature = ctx.signature();
+    ///                     let path = signature.path();
+    ///                     // eg. "3"
+    ///                     let index: &str = path.name().unwrap();
+    ///                     // eg. "/ingest/workers/3"
+    ///                     println!("{}", path.named());
+    ///                     # Ok(())
+    ///                 }
+    ///             })
+    ///     })
+    /// }).expect("Couldn't create the supervisor.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Supervisor::with_name`]: ../supervisor/struct.Supervisor.html#method.with_name
+    /// [`Children::with_name`]: ../children/struct.Children.html#method.with_name
+    pub fn name(&self) -> Option<&str> {
+        self.this.as_ref()?;
+        self.names.last()?.as_ref().map(String::as_str)
+    }
+
+    /// Returns a value formatting this path using the name of
+    /// each element which has one (see [`BastionPath::name`]),
+    /// eg. `/ingest/workers/3`, and the identifier of the
+    /// others, unlike its `Display` implementation which uses
+    /// identifiers.
+    ///
+    /// Because names stay the same when elements are restarted,
+    /// the formatted path can refer to successive incarnations
+    /// of the same element once parsed and resolved (see
+    /// [`Bastion::resolve`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::supervisor(|sp| {
+    ///     sp.with_name("ingest").children(|children| {
+    ///         children
+    ///             .with_name("workers")
+    ///             .with_exec(|ctx: BastionContext| {
+    ///                 async move {
+    ///                     let signature = ctx.signature();
+    ///                     let named = signature.path().named().to_string();
+    ///                     assert_eq!(named, "/ingest/workers/0");
+    ///                     # Ok(())
+    ///                 }
+    ///             })
+    ///     })
+    /// }).expect("Couldn't create the supervisor.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionPath::name`]: #method.name
+    /// [`Bastion::resolve`]: ../struct.Bastion.html#method.resolve
+    pub fn named(&self) -> NamedPath<'_> {
+        NamedPath(self)
+    }
+
+    /// Returns the identifier of the node the element is
+    /// running on if it is a remote element (see
+    /// [`Node::remote`]), or `None` if it is running on this
+    /// node.
+    ///
+    /// [`Node::remote`]: ../transport/struct.Node.html#method.remote
+    #[cfg(feature = "distributed")]
+    pub fn node(&self) -> Option<&NodeId> {
+        self.node.as_ref()
+    }
+
+    /// Sets the node the element is running on.
+    #[cfg(feature = "distributed")]
+    pub(crate) fn with_node(mut self, node: Option<NodeId>) -> Self {
+        self.node = node;
+        self
+    }
+
+    /// Sets the last element's name.
+    pub(crate) fn with_name(mut self, name: Option<String>) -> Self {
+        if let Some(last) = self.names.last_mut() {
+            *last = name;
+        }
+
+        self
+    }
+
+    // The elements of the path, including their kinds.
+    fn elems(&self) -> Vec<BastionPathElement> {
+        let parent_len = self.parent_chain.len();
+        let is_child = self.this.as_ref().map(|e| e.is_child()).unwrap_or(false);
+
+        self.parent_chain
+            .iter()
+            .enumerate()
+            .map(|(i, id)| {
+                if is_child && i == parent_len - 1 {
+                    BastionPathElement::Children(id.clone())
+                } else {
+                    BastionPathElement::Supervisor(id.clone())
+                }
+            })
+            .chain(self.this.clone())
+            .collect()
+    }
+
     /// Returns the last element's id.
     /// If it's root or a dead_letters then &NIL_ID is returned.
     ///
@@ -190,12 +358,22 @@ impl BastionPath {
 }
 
 impl fmt::Display for BastionPath {
+    /// Formats the path using the identifier of each element
+    /// (eg. `/<uuid>/<uuid>/<uuid>`), except for the elements
+    /// which are only known by their name (like the ones of a
+    /// parsed path, see [`BastionPath::named`]).
+    ///
+    /// [`BastionPath::named`]: #method.named
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
             "/{}",
             self.iter()
-                .map(|id| format!("{}", id))
+                .zip(self.names())
+                .map(|(id, name)| match name {
+                    Some(name) if id == &NIL_ID => name.to_string(),
+                    _ => format!("{}", id),
+                })
                 .collect::<Vec<String>>()
                 .join("/")
         )
@@ -203,59 +381,135 @@ impl fmt::Display for BastionPath {
 }
 
 impl fmt::Debug for BastionPath {
+    /// Formats the path using the name of each element which has
+    /// one, and the kind and identifier of the others (eg.
+    /// `/supervisor#<uuid>/workers/3`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "/{}",
+            self.elems()
+                .iter()
+                .zip(self.names())
+                .map(|(el, name)| match name {
+                    Some(name) => name.to_string(),
+                    None => format!("{:?}", el),
+                })
+                .collect::<Vec<String>>()
+                .join("/")
+        )
+    }
+}
+
+/// Formats a [`BastionPath`] using the name of each element
+/// which has one, and the identifier of the others.
+///
+/// This is returned by [`BastionPath::named`].
+///
+/// [`BastionPath`]: struct.BastionPath.html
+/// [`BastionPath::named`]: struct.BastionPath.html#method.named
+#[derive(Debug)]
+pub struct NamedPath<'a>(&'a BastionPath);
+
+impl<'a> fmt::Display for NamedPath<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.this {
-            Some(this @ BastionPathElement::Supervisor(_)) => write!(
-                f,
-                "/{}",
-                self.parent_chain
-                    .iter()
-                    .map(|id| BastionPathElement::Supervisor(id.clone()))
-                    .chain(vec![this.clone()])
-                    .map(|el| format!("{:?}", el))
-                    .collect::<Vec<String>>()
-                    .join("/")
-            ),
-            // TODO: combine with the pattern above when or-patterns become stable
-            Some(this @ BastionPathElement::Children(_)) => write!(
-                f,
-                "/{}",
-                self.parent_chain
-                    .iter()
-                    .map(|id| BastionPathElement::Supervisor(id.clone()))
-                    .chain(vec![this.clone()])
-                    .map(|el| format!("{:?}", el))
-                    .collect::<Vec<String>>()
-                    .join("/")
-            ),
-            Some(this @ BastionPathElement::Child(_)) => {
-                let parent_len = self.parent_chain.len();
-
-                write!(
-                    f,
-                    "/{}",
-                    self.parent_chain
-                        .iter()
-                        .enumerate()
-                        .map(|(i, id)| {
-                            if i == parent_len - 1 {
-                                BastionPathElement::Children(id.clone())
-                            } else {
-                                BastionPathElement::Supervisor(id.clone())
-                            }
-                        })
-                        .chain(vec![this.clone()])
-                        .map(|el| format!("{:?}", el))
-                        .collect::<Vec<String>>()
-                        .join("/")
-                )
-            }
-            None => write!(f, "/"),
+        write!(
+            f,
+            "/{}",
+            self.0
+                .iter()
+                .zip(self.0.names())
+                .map(|(id, name)| match name {
+                    Some(name) => name.to_string(),
+                    None => format!("{}", id),
+                })
+                .collect::<Vec<String>>()
+                .join("/")
+        )
+    }
+}
+
+impl FromStr for BastionPath {
+    type Err = ();
+
+    /// Parses a path formatted either using `{}` (eg.
+    /// `/<uuid>/<uuid>/<uuid>`) or `{:?}` (eg.
+    /// `/supervisor#<uuid>/children#<uuid>/child#<uuid>`),
+    /// where elements can also be given by their name (eg.
+    /// `/ingest/workers/3`, as formatted by
+    /// [`BastionPath::named`]).
+    ///
+    /// Because the former doesn't contain the kind of each
+    /// element, a path made of at least three elements is
+    /// assumed to be a child's (like message signatures are)
+    /// and a shorter path is assumed to be a supervisor's.
+    ///
+    /// Named elements don't have an identifier once parsed, but
+    /// are found using their name when the path is resolved
+    /// (see [`Bastion::resolve`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::children(|children| {
+    ///     children.with_exec(|ctx: BastionContext| {
+    ///         async move {
+    ///             let path = ctx.signature().path().to_string();
+    ///             // Later...
+    ///             let path: BastionPath = path.parse().expect("Couldn't parse the path.");
+    ///             assert_eq!(path.to_string(), ctx.signature().path().to_string());
+    ///
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionPath::named`]: #method.named
+    /// [`Bastion::resolve`]: ../struct.Bastion.html#method.resolve
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let segments = split(path)?
+            .into_iter()
+            .map(parse_segment)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let len = segments.len();
+        let mut path = BastionPath::root();
+        for (i, (kind, segment)) in segments.into_iter().enumerate() {
+            // Named elements are found using their name when
+            // resolving the path.
+            let (id, name) = match segment {
+                Segment::Id(id) => (id, None),
+                Segment::Name(name) => (NIL_ID, Some(name)),
+                Segment::Any => return Err(()),
+            };
+
+            let elem = match kind {
+                Some(kind) => kind(id),
+                None if len >= 3 && i == len - 1 => BastionPathElement::Child(id),
+                None if len >= 3 && i == len - 2 => BastionPathElement::Children(id),
+                None => BastionPathElement::Supervisor(id),
+            };
+
+            path = path.append(elem).map_err(|_| ())?.with_name(name);
         }
+
+        Ok(path)
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "distributed", derive(Serialize, Deserialize))]
 /// Represents BastionPath element
 ///
 /// # Example
@@ -431,25 +685,49 @@ impl fmt::Debug for AppendError {
 }
 
 impl BastionPath {
+    /// Returns the element containing this path's element, or
+    /// `None` if it's at the root.
+    pub(crate) fn parent(&self) -> Option<BastionPathElement> {
+        let id = self.parent_chain.last()?.clone();
+        match self.this {
+            Some(BastionPathElement::Child(_)) => Some(BastionPathElement::Children(id)),
+            _ => Some(BastionPathElement::Supervisor(id)),
+        }
+    }
+
     pub(crate) fn append(self, el: BastionPathElement) -> Result<BastionPath, AppendError> {
         match el {
             sv @ BastionPathElement::Supervisor(_) => match self.this {
-                None => Ok(BastionPath {
-                    parent_chain: self.parent_chain,
-                    this: Some(sv),
-                }),
+                None => {
+                    let mut path = BastionPath {
+                        parent_chain: self.parent_chain,
+                        this: Some(sv),
+                        names: self.names,
+                        #[cfg(feature = "distributed")]
+                        node: self.node,
+                    };
+                    path.names.push(None);
+                    Ok(path)
+                }
                 Some(BastionPathElement::Supervisor(id)) => {
                     let mut path = BastionPath {
                         parent_chain: self.parent_chain,
                         this: Some(sv),
+                        names: self.names,
+                        #[cfg(feature = "distributed")]
+                        node: self.node,
                     };
                     path.parent_chain.push(id);
+                    path.names.push(None);
                     Ok(path)
                 }
                 this => Err(AppendError {
                     path: BastionPath {
                         parent_chain: self.parent_chain,
                         this,
+                        names: self.names,
+                        #[cfg(feature = "distributed")]
+                        node: self.node,
                     },
                     element: sv,
                 }),
@@ -459,14 +737,21 @@ impl BastionPath {
                     let mut path = BastionPath {
                         parent_chain: self.parent_chain,
                         this: Some(children),
+                        names: self.names,
+                        #[cfg(feature = "distributed")]
+                        node: self.node,
                     };
                     path.parent_chain.push(id);
+                    path.names.push(None);
                     Ok(path)
                 }
                 this => Err(AppendError {
                     path: BastionPath {
                         parent_chain: self.parent_chain,
                         this,
+                        names: self.names,
+                        #[cfg(feature = "distributed")]
+                        node: self.node,
                     },
                     element: children,
                 }),
@@ -476,14 +761,21 @@ impl BastionPath {
                     let mut path = BastionPath {
                         parent_chain: self.parent_chain,
                         this: Some(child),
+                        names: self.names,
+                        #[cfg(feature = "distributed")]
+                        node: self.node,
                     };
                     path.parent_chain.push(id);
+                    path.names.push(None);
                     Ok(path)
                 }
                 this => Err(AppendError {
                     path: BastionPath {
                         parent_chain: self.parent_chain,
                         this,
+                        names: self.names,
+                        #[cfg(feature = "distributed")]
+                        node: self.node,
                     },
                     element: child,
                 }),
@@ -492,10 +784,166 @@ impl BastionPath {
     }
 }
 
+type ElementKind = fn(BastionId) -> BastionPathElement;
+
+#[derive(Debug, Clone, PartialEq)]
+/// An element of a path or of a pattern, as parsed.
+pub(crate) enum Segment {
+    // `*`
+    Any,
+    Id(BastionId),
+    Name(String),
+}
+
+/// Parses a pattern formatted like a path (see
+/// `BastionPath::from_str`) but where any element can be
+/// replaced by a `*`.
+pub(crate) fn parse_pattern(pattern: &str) -> Result<Vec<Segment>, ()> {
+    split(pattern)?
+        .into_iter()
+        .map(|segment| parse_segment(segment).map(|(_, segment)| segment))
+        .collect()
+}
+
+fn split(path: &str) -> Result<Vec<&str>, ()> {
+    if !path.starts_with(SEPARATOR) {
+        return Err(());
+    }
+
+    let path = &path[1..];
+    if path.is_empty() {
+        return Ok(vec![]);
+    }
+
+    Ok(path.split(SEPARATOR).collect())
+}
+
+fn parse_segment(segment: &str) -> Result<(Option<ElementKind>, Segment), ()> {
+    if segment.is_empty() {
+        return Err(());
+    } else if segment == WILDCARD {
+        return Ok((None, Segment::Any));
+    }
+
+    if let Some(i) = segment.find(KIND_SEPARATOR) {
+        let kind: Option<ElementKind> = match &segment[..i] {
+            "supervisor" => Some(BastionPathElement::Supervisor),
+            "children" => Some(BastionPathElement::Children),
+            "child" => Some(BastionPathElement::Child),
+            _ => None,
+        };
+
+        if let (Some(kind), Some(id)) = (kind, BastionId::parse(&segment[i + 1..])) {
+            return Ok((Some(kind), Segment::Id(id)));
+        }
+    }
+
+    match BastionId::parse(segment) {
+        Some(id) => Ok((None, Segment::Id(id))),
+        None => Ok((None, Segment::Name(segment.to_string()))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // Parsing
+
+    #[test]
+    fn parse_root() {
+        let path: BastionPath = "/".parse().unwrap();
+        assert!(path.elem().is_none());
+        assert!("".parse::<BastionPath>().is_err());
+    }
+
+    #[test]
+    fn parse_display_and_debug() {
+        let sv_id = BastionId::new();
+        let children_id = BastionId::new();
+        let child_id = BastionId::new();
+        let path = BastionPath::root()
+            .append(BastionPathElement::Supervisor(sv_id))
+            .unwrap()
+            .append(BastionPathElement::Children(children_id))
+            .unwrap()
+            .append(BastionPathElement::Child(child_id))
+            .unwrap();
+
+        for formatted in &[format!("{}", path), format!("{:?}", path)] {
+            let parsed: BastionPath = formatted.parse().unwrap();
+            assert_eq!(
+                parsed.iter().collect::<Vec<_>>(),
+                path.iter().collect::<Vec<_>>()
+            );
+            assert_eq!(parsed.elem(), path.elem());
+        }
+    }
+
+    #[test]
+    fn parse_names() {
+        let path: BastionPath = "/ingest/workers/3".parse().unwrap();
+        assert_eq!(path.to_string(), "/ingest/workers/3");
+        assert_eq!(path.name(), Some("3"));
+        assert!(path.elem().as_ref().unwrap().is_child());
+
+        let id = BastionId::new();
+        let path: BastionPath = format!("/supervisor#{}/workers", id).parse().unwrap();
+        assert_eq!(path.iter().next(), Some(&id));
+        assert_eq!(path.name(), Some("workers"));
+        assert!(path.elem().as_ref().unwrap().is_supervisor());
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let id = BastionId::new();
+        assert!(format!("{}", id).parse::<BastionPath>().is_err());
+        assert!(format!("/child#{}", id).parse::<BastionPath>().is_err());
+        assert!(format!("/{}/", id).parse::<BastionPath>().is_err());
+        assert!("/workers/*".parse::<BastionPath>().is_err());
+    }
+
+    #[test]
+    fn parse_patterns() {
+        let id = BastionId::new();
+        let pattern = parse_pattern(&format!("/supervisor#{}/*/workers", id)).unwrap();
+        assert_eq!(
+            pattern,
+            vec![
+                Segment::Id(id),
+                Segment::Any,
+                Segment::Name("workers".to_string())
+            ]
+        );
+        assert!(parse_pattern("/workers//*").is_err());
+    }
+
+    #[test]
+    fn display_names() {
+        let sv_id = BastionId::new();
+        let children_id = BastionId::new();
+        let child_id = BastionId::new();
+        let path = BastionPath::root()
+            .append(BastionPathElement::Supervisor(sv_id.clone()))
+            .unwrap()
+            .append(BastionPathElement::Children(children_id.clone()))
+            .unwrap()
+            .with_name(Some("workers".to_string()))
+            .append(BastionPathElement::Child(child_id.clone()))
+            .unwrap()
+            .with_name(Some("3".to_string()));
+
+        assert_eq!(path.named().to_string(), format!("/{}/workers/3", sv_id));
+        assert_eq!(
+            path.to_string(),
+            format!("/{}/{}/{}", sv_id, children_id, child_id)
+        );
+        assert_eq!(
+            format!("{:?}", path),
+            format!("/supervisor#{}/workers/3", sv_id)
+        );
+    }
+
     // SYSTEM + smth
 
     #[test]
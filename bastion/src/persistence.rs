@@ -0,0 +1,675 @@
+//!
+//! Event-sourced persistence for children group elements, which
+//! persist the events changing their state to a journal before
+//! applying them, and replay them when they are started or
+//! restarted.
+//!
+//! The journal of an element is identified by a persistence
+//! identifier and stored using a [`Storage`], either a
+//! [`FileStorage`] (an append-only file per persistence
+//! identifier, with optional snapshots) or a [`MemoryStorage`]
+//! (eg. for tests).
+//!
+//! [`Storage`]: trait.Storage.html
+//! [`FileStorage`]: struct.FileStorage.html
+//! [`MemoryStorage`]: struct.MemoryStorage.html
+use bastion_executor::blocking;
+use fxhash::FxHashMap;
+use lightproc::proc_stack::ProcStack;
+use std::convert::TryInto;
+use std::fmt::{self, Debug, Formatter};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// The state of a persistent element, which is changed by
+/// applying events to it.
+///
+/// The events (and optionally, snapshots of the state) are
+/// encoded before being persisted to a [`Storage`] and decoded
+/// when replayed.
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::persistence::Persistent;
+/// use std::convert::TryInto;
+///
+/// #[derive(Default)]
+/// struct Counter(u64);
+///
+/// impl Persistent for Counter {
+///     type Event = u64;
+///
+///     fn apply(&mut self, added: &u64) {
+///         self.0 += added;
+///     }
+///
+///     fn encode_event(added: &u64) -> Vec<u8> {
+///         added.to_le_bytes().to_vec()
+///     }
+///
+///     fn decode_event(bytes: &[u8]) -> Option<u64> {
+///         Some(u64::from_le_bytes(bytes.try_into().ok()?))
+///     }
+/// }
+/// ```
+///
+/// [`Storage`]: trait.Storage.html
+pub trait Persistent: Default + Send + 'static {
+    /// The type of the events changing the state.
+    type Event: Send + 'static;
+
+    /// Applies an event to the state.
+    fn apply(&mut self, event: &Self::Event);
+
+    /// Encodes an event before it is persisted.
+    fn encode_event(event: &Self::Event) -> Vec<u8>;
+
+    /// Decodes a persisted event, returning `None` if it is
+    /// invalid.
+    fn decode_event(bytes: &[u8]) -> Option<Self::Event>;
+
+    /// Encodes a snapshot of the state, returning `None` if the
+    /// state doesn't support snapshots (the default).
+    fn encode_snapshot(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Decodes a snapshot of the state, returning `None` if it
+    /// is invalid or if the state doesn't support snapshots (the
+    /// default), in which case all the events are replayed.
+    fn decode_snapshot(_bytes: &[u8]) -> Option<Self> {
+        None
+    }
+}
+
+/// The storage of the journals of persistent elements, which
+/// contain the events (encoded) that were persisted by an
+/// element and optionally the last snapshot of its state.
+///
+/// The events are numbered starting at `0` in the order they
+/// were appended.
+pub trait Storage: Send + Sync + 'static {
+    /// Appends an event to the journal identified by `id`,
+    /// creating it if needed.
+    fn append(&self, id: &str, event: &[u8]) -> io::Result<()>;
+
+    /// Returns the events of the journal identified by `id`,
+    /// starting at the event numbered `from`.
+    fn events(&self, id: &str, from: u64) -> io::Result<Vec<Vec<u8>>>;
+
+    /// Saves a snapshot of the state resulting of the `seq`
+    /// first events of the journal identified by `id`,
+    /// replacing the previous one.
+    fn save_snapshot(&self, id: &str, seq: u64, snapshot: &[u8]) -> io::Result<()>;
+
+    /// Returns the last snapshot saved for the journal
+    /// identified by `id`, along with the number of events it
+    /// includes.
+    fn snapshot(&self, id: &str) -> io::Result<Option<(u64, Vec<u8>)>>;
+}
+
+#[derive(Debug, Clone)]
+/// A [`Storage`] keeping each journal in an append-only file
+/// (`<id>.journal`) and its last snapshot in another
+/// (`<id>.snapshot`), in a directory.
+///
+/// The characters of the persistence identifiers other than
+/// ASCII letters, digits, `-`, `_` and `.` are percent-encoded
+/// in the files' names, so that all the files stay in the
+/// directory.
+///
+/// Each event is synced to the disk before [`Journal::persist`]
+/// returns.
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::persistence::FileStorage;
+///
+/// let dir = std::env::temp_dir().join("bastion-journals");
+/// let storage = FileStorage::new(dir).expect("Couldn't create the storage.");
+/// ```
+///
+/// [`Storage`]: trait.Storage.html
+/// [`Journal::persist`]: struct.Journal.html#method.persist
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+#[derive(Debug, Default, Clone)]
+/// A [`Storage`] keeping the journals in memory, which is
+/// mainly useful for tests.
+///
+/// Clones of a `MemoryStorage` share the same journals.
+///
+/// [`Storage`]: trait.Storage.html
+pub struct MemoryStorage {
+    journals: Arc<Mutex<FxHashMap<String, MemoryJournal>>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryJournal {
+    events: Vec<Vec<u8>>,
+    snapshot: Option<(u64, Vec<u8>)>,
+}
+
+/// The journal of a persistent element, which allows it to
+/// persist events before applying them to its state.
+///
+/// A `Journal` is created using [`Journal::recover`], which
+/// restores the element's state from the last snapshot (if any)
+/// and the events persisted after it, or is handed to the future
+/// of the elements of a children group created using
+/// [`Children::with_persistence`].
+///
+/// Because a [`Storage`] can block (eg. a [`FileStorage`]
+/// writes to files), it is used on the executor's blocking
+/// thread pool, and the methods using it return futures.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// use bastion::persistence::{Journal, MemoryStorage, Persistent};
+/// # use std::convert::TryInto;
+/// #
+/// # #[derive(Default)]
+/// # struct Counter(u64);
+/// #
+/// # impl Persistent for Counter {
+/// #     type Event = u64;
+/// #     fn apply(&mut self, added: &u64) { self.0 += added; }
+/// #     fn encode_event(added: &u64) -> Vec<u8> { added.to_le_bytes().to_vec() }
+/// #     fn decode_event(bytes: &[u8]) -> Option<u64> {
+/// #         Some(u64::from_le_bytes(bytes.try_into().ok()?))
+/// #     }
+/// # }
+///
+/// # fn main() {
+///     # Bastion::init();
+///     #
+/// let storage = MemoryStorage::default();
+///
+/// Bastion::children(|children| {
+///     children.with_exec(move |ctx: BastionContext| {
+///         let storage = storage.clone();
+///         async move {
+///             // Replays the persisted events...
+///             let mut journal: Journal<Counter> = Journal::recover("counter", storage)
+///                 .await
+///                 .map_err(|_| ())?;
+///             let count: u64 = journal.state().0;
+///
+///             // ...and persists then applies a new one.
+///             journal.persist(1).await.map_err(|_| ())?;
+///
+///             Ok(())
+///         }
+///     })
+/// }).expect("Couldn't create the children group.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`Journal::recover`]: #method.recover
+/// [`Children::with_persistence`]: ../children/struct.Children.html#method.with_persistence
+/// [`Storage`]: trait.Storage.html
+/// [`FileStorage`]: struct.FileStorage.html
+pub struct Journal<P: Persistent> {
+    id: String,
+    storage: Arc<dyn Storage>,
+    state: P,
+    // The number of events persisted to the journal.
+    seq: u64,
+}
+
+impl<P: Persistent> Journal<P> {
+    /// Restores the state of the element whose journal is
+    /// identified by `id` by decoding its last snapshot (if any)
+    /// and applying the events persisted after it (or its
+    /// default state and all the events if there is no valid
+    /// snapshot).
+    ///
+    /// This method returns the `Journal` if it succeeded, or an
+    /// error if the storage failed or if an event couldn't be
+    /// decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The persistence identifier of the journal.
+    /// * `storage` - The storage of the journal.
+    pub async fn recover<S: Storage>(id: &str, storage: S) -> io::Result<Self> {
+        Journal::recover_with(id, Arc::new(storage)).await
+    }
+
+    pub(crate) async fn recover_with(id: &str, storage: Arc<dyn Storage>) -> io::Result<Self> {
+        let id = id.to_string();
+        on_blocking_pool(move || Journal::replay(&id, storage)).await
+    }
+
+    fn replay(id: &str, storage: Arc<dyn Storage>) -> io::Result<Self> {
+        debug!("Journal({}): Recovering.", id);
+        let snapshot = storage
+            .snapshot(id)?
+            .and_then(|(seq, bytes)| Some((seq, P::decode_snapshot(&bytes)?)));
+        let (mut seq, mut state) = snapshot.unwrap_or_default();
+
+        let events = storage.events(id, seq)?;
+        trace!(
+            "Journal({}): Replaying {} events from {}.",
+            id,
+            events.len(),
+            seq
+        );
+        for bytes in events {
+            let event = P::decode_event(&bytes).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid event {} in journal {}", seq, id),
+                )
+            })?;
+
+            state.apply(&event);
+            seq += 1;
+        }
+
+        let id = id.to_string();
+
+        Ok(Journal {
+            id,
+            storage,
+            state,
+            seq,
+        })
+    }
+
+    /// Returns the persistence identifier of this journal.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the current state of the element, resulting of
+    /// all the events persisted to this journal.
+    pub fn state(&self) -> &P {
+        &self.state
+    }
+
+    /// Returns the number of events persisted to this journal.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Persists an event to this journal and then applies it to
+    /// the element's state.
+    ///
+    /// This method returns `()` if it succeeded, or an error if
+    /// the storage failed (in which case the event isn't
+    /// applied).
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to persist and apply.
+    pub async fn persist(&mut self, event: P::Event) -> io::Result<()> {
+        trace!("Journal({}): Persisting event {}.", self.id, self.seq);
+        let id = self.id.clone();
+        let storage = self.storage.clone();
+        let bytes = P::encode_event(&event);
+        on_blocking_pool(move || storage.append(&id, &bytes)).await?;
+        self.seq += 1;
+        self.state.apply(&event);
+
+        Ok(())
+    }
+
+    /// Saves a snapshot of the element's current state, which
+    /// will be restored instead of replaying all the events
+    /// persisted until now when the journal is recovered.
+    ///
+    /// This method returns `true` if a snapshot was saved,
+    /// `false` if the state doesn't support snapshots (see
+    /// [`Persistent::encode_snapshot`]), or an error if the
+    /// storage failed.
+    ///
+    /// [`Persistent::encode_snapshot`]: trait.Persistent.html#method.encode_snapshot
+    pub async fn snapshot(&self) -> io::Result<bool> {
+        let snapshot = match self.state.encode_snapshot() {
+            Some(snapshot) => snapshot,
+            None => return Ok(false),
+        };
+
+        debug!("Journal({}): Saving snapshot at {}.", self.id, self.seq);
+        let id = self.id.clone();
+        let seq = self.seq;
+        let storage = self.storage.clone();
+        on_blocking_pool(move || storage.save_snapshot(&id, seq, &snapshot)).await?;
+
+        Ok(true)
+    }
+}
+
+// Runs a closure using a storage on the executor's blocking
+// thread pool.
+// NOTE: `io::Error::other` is more recent than the oldest
+//      supported version of Rust.
+#[allow(clippy::io_other_error)]
+async fn on_blocking_pool<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let handle = blocking::spawn_blocking(async move { f() }, ProcStack::default());
+    match handle.await {
+        Some(res) => res,
+        None => Err(io::Error::new(io::ErrorKind::Other, "the storage panicked")),
+    }
+}
+
+impl FileStorage {
+    /// Creates a new `FileStorage` keeping its files in the
+    /// given directory, which is created if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory where the files will be kept.
+    pub fn new<D: Into<PathBuf>>(dir: D) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(FileStorage { dir })
+    }
+
+    fn file(&self, id: &str, ext: &str) -> PathBuf {
+        // The identifier is encoded so that it can't contain any
+        // path separator.
+        let mut name = String::with_capacity(id.len());
+        for byte in id.bytes() {
+            match byte {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' => {
+                    name.push(byte as char)
+                }
+                _ => name.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+
+        self.dir.join(format!("{}.{}", name, ext))
+    }
+}
+
+impl Storage for FileStorage {
+    fn append(&self, id: &str, event: &[u8]) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file(id, "journal"))?;
+        let len = file.metadata()?.len();
+
+        // Each event is prefixed by its length.
+        let mut record = Vec::with_capacity(4 + event.len());
+        record.extend_from_slice(&(event.len() as u32).to_le_bytes());
+        record.extend_from_slice(event);
+        let appended = file.write_all(&record).and_then(|_| file.sync_data());
+
+        // The event might have been partially written, in which
+        // case it is removed so that the next events are appended
+        // after the last complete one (if this fails too, an
+        // incomplete event is removed when the events are read).
+        if appended.is_err() {
+            warn!(
+                "FileStorage: Removing an event which couldn't be appended to journal {}.",
+                id
+            );
+            file.set_len(len).ok();
+        }
+
+        appended
+    }
+
+    fn events(&self, id: &str, from: u64) -> io::Result<Vec<Vec<u8>>> {
+        let mut bytes = vec![];
+        match File::open(self.file(id, "journal")) {
+            Ok(mut file) => file.read_to_end(&mut bytes)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+
+        let mut events = vec![];
+        let mut rest = &bytes[..];
+        while !rest.is_empty() {
+            let len = match rest.get(..4) {
+                Some(len) => u32::from_le_bytes(len.try_into().unwrap()) as usize,
+                None => break,
+            };
+            let event = match rest.get(4..4 + len) {
+                Some(event) => event,
+                None => break,
+            };
+
+            events.push(event.to_vec());
+            rest = &rest[4 + len..];
+        }
+
+        // A record can only be incomplete if writing it failed,
+        // in which case it was never applied. It is removed so
+        // that the next events are appended after the last
+        // complete one.
+        if !rest.is_empty() {
+            warn!(
+                "FileStorage: Removing an incomplete event at the end of journal {}.",
+                id
+            );
+            let len = bytes.len() - rest.len();
+            OpenOptions::new()
+                .write(true)
+                .open(self.file(id, "journal"))?
+                .set_len(len as u64)?;
+        }
+
+        Ok(events.into_iter().skip(from as usize).collect())
+    }
+
+    fn save_snapshot(&self, id: &str, seq: u64, snapshot: &[u8]) -> io::Result<()> {
+        // The snapshot is written to a temporary file first so
+        // that the previous one stays valid if writing it fails.
+        let tmp = self.file(id, "snapshot.tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(&seq.to_le_bytes())?;
+        file.write_all(snapshot)?;
+        file.sync_data()?;
+
+        fs::rename(tmp, self.file(id, "snapshot"))
+    }
+
+    fn snapshot(&self, id: &str) -> io::Result<Option<(u64, Vec<u8>)>> {
+        let mut bytes = vec![];
+        match File::open(self.file(id, "snapshot")) {
+            Ok(mut file) => file.read_to_end(&mut bytes)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        if bytes.len() < 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid snapshot for journal {}", id),
+            ));
+        }
+
+        let seq = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        Ok(Some((seq, bytes.split_off(8))))
+    }
+}
+
+impl MemoryStorage {
+    /// Creates a new empty `MemoryStorage`.
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn append(&self, id: &str, event: &[u8]) -> io::Result<()> {
+        // FIXME: panics?
+        let mut journals = self.journals.lock().unwrap();
+        journals
+            .entry(id.to_string())
+            .or_default()
+            .events
+            .push(event.to_vec());
+
+        Ok(())
+    }
+
+    fn events(&self, id: &str, from: u64) -> io::Result<Vec<Vec<u8>>> {
+        // FIXME: panics?
+        let journals = self.journals.lock().unwrap();
+        let events = journals
+            .get(id)
+            .map(|journal| journal.events.iter().skip(from as usize).cloned().collect())
+            .unwrap_or_default();
+
+        Ok(events)
+    }
+
+    fn save_snapshot(&self, id: &str, seq: u64, snapshot: &[u8]) -> io::Result<()> {
+        // FIXME: panics?
+        let mut journals = self.journals.lock().unwrap();
+        journals.entry(id.to_string()).or_default().snapshot = Some((seq, snapshot.to_vec()));
+
+        Ok(())
+    }
+
+    fn snapshot(&self, id: &str) -> io::Result<Option<(u64, Vec<u8>)>> {
+        // FIXME: panics?
+        let journals = self.journals.lock().unwrap();
+        Ok(journals
+            .get(id)
+            .and_then(|journal| journal.snapshot.clone()))
+    }
+}
+
+impl<P: Persistent + Debug> Debug for Journal<P> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Journal")
+            .field("id", &self.id)
+            .field("state", &self.state)
+            .field("seq", &self.seq)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::BastionId;
+    use futures::executor::block_on;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Log(Vec<u8>);
+
+    impl Persistent for Log {
+        type Event = u8;
+
+        fn apply(&mut self, event: &u8) {
+            self.0.push(*event);
+        }
+
+        fn encode_event(event: &u8) -> Vec<u8> {
+            vec![*event]
+        }
+
+        fn decode_event(bytes: &[u8]) -> Option<u8> {
+            bytes.first().cloned()
+        }
+
+        fn encode_snapshot(&self) -> Option<Vec<u8>> {
+            Some(self.0.clone())
+        }
+
+        fn decode_snapshot(bytes: &[u8]) -> Option<Self> {
+            Some(Log(bytes.to_vec()))
+        }
+    }
+
+    fn file_storage() -> FileStorage {
+        let dir = std::env::temp_dir().join(format!("bastion-{}", BastionId::new()));
+        FileStorage::new(dir).unwrap()
+    }
+
+    fn recover_replays_events<S: Storage + Clone>(storage: S) {
+        let mut journal = block_on(Journal::<Log>::recover("log", storage.clone())).unwrap();
+        assert_eq!(journal.seq(), 0);
+        block_on(journal.persist(1)).unwrap();
+        block_on(journal.persist(2)).unwrap();
+
+        let mut journal = block_on(Journal::<Log>::recover("log", storage.clone())).unwrap();
+        assert_eq!(journal.state(), &Log(vec![1, 2]));
+        assert_eq!(journal.seq(), 2);
+
+        assert!(block_on(journal.snapshot()).unwrap());
+        block_on(journal.persist(3)).unwrap();
+        assert_eq!(storage.snapshot("log").unwrap(), Some((2, vec![1, 2])));
+        assert_eq!(storage.events("log", 2).unwrap(), vec![vec![3]]);
+
+        let journal = block_on(Journal::<Log>::recover("log", storage.clone())).unwrap();
+        assert_eq!(journal.state(), &Log(vec![1, 2, 3]));
+        assert_eq!(journal.seq(), 3);
+
+        let other = block_on(Journal::<Log>::recover("other", storage)).unwrap();
+        assert_eq!(other.state(), &Log(vec![]));
+    }
+
+    #[test]
+    fn memory_storage() {
+        recover_replays_events(MemoryStorage::new());
+    }
+
+    #[test]
+    fn file_storage_recover() {
+        let storage = file_storage();
+        recover_replays_events(storage.clone());
+        fs::remove_dir_all(&storage.dir).unwrap();
+    }
+
+    #[test]
+    fn file_storage_incomplete_event() {
+        let storage = file_storage();
+        storage.append("log", &[1]).unwrap();
+
+        // A record whose write was interrupted...
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(storage.file("log", "journal"))
+            .unwrap();
+        file.write_all(&[4, 0, 0, 0, 2]).unwrap();
+
+        // ...is ignored and removed.
+        let mut journal = block_on(Journal::<Log>::recover("log", storage.clone())).unwrap();
+        assert_eq!(journal.state(), &Log(vec![1]));
+        block_on(journal.persist(3)).unwrap();
+
+        let journal = block_on(Journal::<Log>::recover("log", storage.clone())).unwrap();
+        assert_eq!(journal.state(), &Log(vec![1, 3]));
+
+        fs::remove_dir_all(&storage.dir).unwrap();
+    }
+
+    #[test]
+    fn file_storage_encodes_ids() {
+        let storage = file_storage();
+        let file = storage.file("../orders/0", "journal");
+        assert_eq!(file, storage.dir.join("..%2Forders%2F0.journal"));
+
+        let mut journal =
+            block_on(Journal::<Log>::recover("../orders/0", storage.clone())).unwrap();
+        block_on(journal.persist(1)).unwrap();
+        assert!(file.exists());
+
+        fs::remove_dir_all(&storage.dir).unwrap();
+    }
+}
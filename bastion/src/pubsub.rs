@@ -0,0 +1,175 @@
+//!
+//! Topics allow children to receive the messages published
+//! about what they are interested in instead of every message
+//! broadcasted to their subtree.
+//!
+//! A topic is made of segments separated by dots (eg.
+//! `orders.eu.created`). Subscriptions can use wildcards:
+//! * `*` matches exactly one segment (eg. `orders.*.created`).
+//! * `#` matches zero or more segments and can only be used as
+//!   the last segment (eg. `orders.#`).
+use crate::child_ref::ChildRef;
+use crate::context::BastionId;
+use crate::envelope::Envelope;
+use fxhash::FxHashMap;
+use std::sync::RwLock;
+
+const SEPARATOR: char = '.';
+const ONE: &str = "*";
+const REST: &str = "#";
+
+#[derive(Debug, Default)]
+pub(crate) struct Topics {
+    // The children subscribed to each pattern.
+    subscriptions: RwLock<FxHashMap<String, FxHashMap<BastionId, ChildRef>>>,
+}
+
+impl Topics {
+    pub(crate) fn new() -> Self {
+        Topics::default()
+    }
+
+    pub(crate) fn subscribe(&self, pattern: &str, child: &ChildRef) -> Result<(), ()> {
+        if !is_valid(pattern) {
+            return Err(());
+        }
+
+        debug!(
+            "Topics: Subscribing Child({}) to {:?}.",
+            child.id(),
+            pattern
+        );
+        // FIXME: panics?
+        let mut subscriptions = self.subscriptions.write().unwrap();
+        subscriptions
+            .entry(pattern.to_string())
+            .or_default()
+            .insert(child.id().clone(), child.clone());
+
+        Ok(())
+    }
+
+    pub(crate) fn unsubscribe(&self, pattern: &str, id: &BastionId) {
+        debug!("Topics: Unsubscribing Child({}) from {:?}.", id, pattern);
+        // FIXME: panics?
+        let mut subscriptions = self.subscriptions.write().unwrap();
+        if let Some(children) = subscriptions.get_mut(pattern) {
+            children.remove(id);
+            if children.is_empty() {
+                subscriptions.remove(pattern);
+            }
+        }
+    }
+
+    pub(crate) fn unsubscribe_child(&self, id: &BastionId) {
+        trace!("Topics: Unsubscribing Child({}) from every topic.", id);
+        // FIXME: panics?
+        let mut subscriptions = self.subscriptions.write().unwrap();
+        subscriptions.retain(|_, children| {
+            children.remove(id);
+            !children.is_empty()
+        });
+    }
+
+    pub(crate) fn subscribers(&self, topic: &str) -> Vec<ChildRef> {
+        // FIXME: panics?
+        let subscriptions = self.subscriptions.read().unwrap();
+
+        let mut subscribers: FxHashMap<&BastionId, &ChildRef> = FxHashMap::default();
+        for (pattern, children) in subscriptions.iter() {
+            if matches(pattern, topic) {
+                for (id, child) in children {
+                    if child.is_alive() {
+                        subscribers.insert(id, child);
+                    }
+                }
+            }
+        }
+
+        subscribers.into_values().cloned().collect()
+    }
+
+    pub(crate) fn publish(&self, topic: &str, env: Envelope) -> Result<(), Envelope> {
+        let subscribers = self.subscribers(topic);
+        if subscribers.is_empty() {
+            debug!("Topics: No subscribers for {:?}.", topic);
+            return Err(env);
+        }
+
+        debug!(
+            "Topics: Publishing to {} subscribers of {:?}.",
+            subscribers.len(),
+            topic
+        );
+        for child in subscribers {
+            // FIXME: Err(Error) if None
+            if let Some(env) = env.try_clone() {
+                // FIXME: handle errors
+                child.send(env).ok();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_valid(pattern: &str) -> bool {
+    let segments = pattern.split(SEPARATOR).collect::<Vec<_>>();
+    segments
+        .iter()
+        .enumerate()
+        .all(|(i, segment)| !segment.is_empty() && (*segment != REST || i == segments.len() - 1))
+}
+
+fn matches(pattern: &str, topic: &str) -> bool {
+    let mut pattern = pattern.split(SEPARATOR);
+    let mut topic = topic.split(SEPARATOR);
+
+    loop {
+        match (pattern.next(), topic.next()) {
+            (Some(REST), _) => return true,
+            (Some(ONE), Some(_)) => (),
+            (Some(expected), Some(segment)) if expected == segment => (),
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid, matches};
+
+    #[test]
+    fn valid_patterns() {
+        assert!(is_valid("orders"));
+        assert!(is_valid("orders.*.created"));
+        assert!(is_valid("orders.#"));
+        assert!(is_valid("#"));
+
+        assert!(!is_valid(""));
+        assert!(!is_valid("orders..created"));
+        assert!(!is_valid("orders.#.created"));
+    }
+
+    #[test]
+    fn exact_matches() {
+        assert!(matches("orders.created", "orders.created"));
+        assert!(!matches("orders.created", "orders.deleted"));
+        assert!(!matches("orders", "orders.created"));
+        assert!(!matches("orders.created", "orders"));
+    }
+
+    #[test]
+    fn wildcard_matches() {
+        assert!(matches("orders.*", "orders.created"));
+        assert!(!matches("orders.*", "orders"));
+        assert!(!matches("orders.*", "orders.eu.created"));
+        assert!(matches("orders.*.created", "orders.eu.created"));
+
+        assert!(matches("orders.#", "orders"));
+        assert!(matches("orders.#", "orders.eu.created"));
+        assert!(matches("#", "anything.at.all"));
+        assert!(!matches("orders.#", "invoices.created"));
+    }
+}
@@ -0,0 +1,201 @@
+//!
+//! A registry allows children to be found by a name that
+//! stays the same across their restarts.
+use crate::child_ref::ChildRef;
+use crate::context::BastionId;
+use fxhash::{FxHashMap, FxHashSet};
+use std::sync::RwLock;
+
+#[derive(Debug, Default)]
+pub(crate) struct Registry {
+    inner: RwLock<RegistryInner>,
+}
+
+#[derive(Debug, Default)]
+struct RegistryInner {
+    // The children currently registered under each name.
+    names: FxHashMap<String, ChildRef>,
+    // The names each child is currently registered under.
+    // This is used to unregister or rebind all the names of
+    // a child at once.
+    owners: FxHashMap<BastionId, FxHashSet<String>>,
+}
+
+impl Registry {
+    pub(crate) fn new() -> Self {
+        Registry::default()
+    }
+
+    pub(crate) fn register(&self, name: &str, child: &ChildRef) -> Result<(), ()> {
+        // FIXME: panics?
+        let mut inner = self.inner.write().unwrap();
+
+        if let Some(registered) = inner.names.get(name) {
+            if registered == child {
+                return Ok(());
+            }
+
+            // Names held by children that are not running
+            // anymore can be taken over.
+            if registered.is_alive() {
+                return Err(());
+            }
+
+            let id = registered.id().clone();
+            inner.remove_owned(&id, name);
+        }
+
+        debug!("Registry: Registering Child({}) as {:?}.", child.id(), name);
+        inner.names.insert(name.to_string(), child.clone());
+        inner
+            .owners
+            .entry(child.id().clone())
+            .or_default()
+            .insert(name.to_string());
+
+        Ok(())
+    }
+
+    pub(crate) fn unregister(&self, name: &str) -> Option<ChildRef> {
+        // FIXME: panics?
+        let mut inner = self.inner.write().unwrap();
+
+        let child = inner.names.remove(name)?;
+        debug!("Registry: Unregistering {:?}.", name);
+        inner.remove_owned(child.id(), name);
+
+        Some(child)
+    }
+
+    pub(crate) fn unregister_child(&self, id: &BastionId) {
+        // FIXME: panics?
+        let mut inner = self.inner.write().unwrap();
+
+        if let Some(names) = inner.owners.remove(id) {
+            debug!("Registry: Unregistering Child({}): {:?}", id, names);
+            for name in names {
+                inner.names.remove(&name);
+            }
+        }
+    }
+
+    pub(crate) fn rebind(&self, old: &BastionId, new: &ChildRef) {
+        // FIXME: panics?
+        let mut inner = self.inner.write().unwrap();
+
+        if let Some(names) = inner.owners.remove(old) {
+            debug!(
+                "Registry: Rebinding Child({}) to Child({}): {:?}",
+                old,
+                new.id(),
+                names
+            );
+            for name in &names {
+                inner.names.insert(name.clone(), new.clone());
+            }
+
+            inner.owners.insert(new.id().clone(), names);
+        }
+    }
+
+    pub(crate) fn whereis(&self, name: &str) -> Option<ChildRef> {
+        // FIXME: panics?
+        let inner = self.inner.read().unwrap();
+
+        inner
+            .names
+            .get(name)
+            .filter(|child| child.is_alive())
+            .cloned()
+    }
+}
+
+impl RegistryInner {
+    fn remove_owned(&mut self, id: &BastionId, name: &str) {
+        if let Some(names) = self.owners.get_mut(id) {
+            names.remove(name);
+            if names.is_empty() {
+                self.owners.remove(id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+    use crate::bastion::Bastion;
+    use crate::child_ref::ChildRef;
+    use crate::context::BastionId;
+    use crate::path::{BastionPath, BastionPathElement};
+    use futures::channel::mpsc;
+    use std::sync::Arc;
+
+    fn child_ref() -> (ChildRef, mpsc::UnboundedReceiver<crate::envelope::Envelope>) {
+        let id = BastionId::new();
+        let (sender, recver) = mpsc::unbounded();
+        let path = BastionPath::root()
+            .append(BastionPathElement::Supervisor(BastionId::new()))
+            .unwrap()
+            .append(BastionPathElement::Children(BastionId::new()))
+            .unwrap()
+            .append(BastionPathElement::Child(id.clone()))
+            .unwrap();
+
+        (
+            ChildRef::new(id, sender, Arc::new(path), Bastion::system()),
+            recver,
+        )
+    }
+
+    #[test]
+    fn register_and_whereis() {
+        let registry = Registry::new();
+        let (child, _recver) = child_ref();
+
+        assert!(registry.whereis("worker").is_none());
+        registry.register("worker", &child).unwrap();
+        assert_eq!(registry.whereis("worker"), Some(child.clone()));
+
+        // Registering the same child twice is allowed...
+        registry.register("worker", &child).unwrap();
+        // ...but not another child while the first one is alive.
+        let (other, _other_recver) = child_ref();
+        assert!(registry.register("worker", &other).is_err());
+
+        assert_eq!(registry.unregister("worker"), Some(child));
+        assert!(registry.whereis("worker").is_none());
+    }
+
+    #[test]
+    fn dead_children_are_not_found() {
+        let registry = Registry::new();
+        let (child, recver) = child_ref();
+
+        registry.register("worker", &child).unwrap();
+        drop(recver);
+        assert!(registry.whereis("worker").is_none());
+
+        let (other, _other_recver) = child_ref();
+        registry.register("worker", &other).unwrap();
+        assert_eq!(registry.whereis("worker"), Some(other));
+    }
+
+    #[test]
+    fn rebind_and_unregister_child() {
+        let registry = Registry::new();
+        let (child, _recver) = child_ref();
+        let (restarted, _restarted_recver) = child_ref();
+
+        registry.register("a", &child).unwrap();
+        registry.register("b", &child).unwrap();
+
+        registry.rebind(child.id(), &restarted);
+        assert_eq!(registry.whereis("a"), Some(restarted.clone()));
+        assert_eq!(registry.whereis("b"), Some(restarted.clone()));
+
+        registry.unregister_child(restarted.id());
+        assert!(registry.whereis("a").is_none());
+        assert!(registry.whereis("b").is_none());
+    }
+}
@@ -6,9 +6,14 @@ use crate::callbacks::Callbacks;
 use crate::children::Children;
 use crate::children_ref::ChildrenRef;
 use crate::context::BastionId;
-use crate::envelope::Envelope;
+use crate::envelope::{Envelope, RefAddr};
 use crate::message::{BastionMessage, Deployment, Message};
+use crate::monitor::{ExitReason, Terminated};
 use crate::path::{BastionPath, BastionPathElement};
+use crate::system::BastionSystem;
+#[cfg(feature = "distributed")]
+use crate::transport::{Node, NodeId};
+use crate::typed::{TypedChildren, TypedChildrenRef};
 use bastion_executor::pool;
 use futures::prelude::*;
 use futures::stream::FuturesOrdered;
@@ -86,6 +91,11 @@ pub struct Supervisor {
     // is received.
     pre_start_msgs: Vec<Envelope>,
     started: bool,
+    // Whether the supervised children and supervisors should be
+    // restarted when they fault (which isn't the case for the
+    // supervisor hosting the children groups deployed by other
+    // nodes, whose own supervisors restart them instead).
+    restarts: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -97,6 +107,7 @@ pub struct SupervisorRef {
     id: BastionId,
     sender: Sender,
     path: Arc<BastionPath>,
+    system: BastionSystem,
 }
 
 #[derive(Debug, Clone)]
@@ -144,6 +155,7 @@ impl Supervisor {
         let is_system_supervisor = false;
         let pre_start_msgs = Vec::new();
         let started = false;
+        let restarts = true;
 
         Supervisor {
             bcast,
@@ -156,6 +168,7 @@ impl Supervisor {
             is_system_supervisor,
             pre_start_msgs,
             started,
+            restarts,
         }
     }
 
@@ -192,7 +205,9 @@ impl Supervisor {
         // TODO: stop or kill?
         self.kill(0..).await;
 
-        if let Some(bcast) = bcast {
+        if let Some(mut bcast) = bcast {
+            // The name of the supervisor stays the same.
+            bcast.set_name(self.bcast.path().name().map(str::to_string));
             self.bcast = bcast;
         } else {
             self.bcast.clear_children();
@@ -264,6 +279,15 @@ impl Supervisor {
         &self.callbacks
     }
 
+    // Called by the supervisor's parent once it exited and won't
+    // be restarted, as its supervised elements won't be either.
+    pub(crate) fn exited_for_good(&self) {
+        debug!("Supervisor({}): Exited for good.", self.id());
+        for supervised in self.stopped.values().chain(self.killed.values()) {
+            supervised.exited_for_good();
+        }
+    }
+
     pub(crate) fn as_ref(&self) -> SupervisorRef {
         trace!(
             "Supervisor({}): Creating new SupervisorRef({}).",
@@ -274,8 +298,9 @@ impl Supervisor {
         let id = self.bcast.id().clone();
         let sender = self.bcast.sender().clone();
         let path = self.bcast.path().clone();
+        let system = self.bcast.system().clone();
 
-        SupervisorRef::new(id, sender, path)
+        SupervisorRef::new(id, sender, path, system)
     }
 
     /// Creates a new supervisor, passes it through the specified
@@ -564,6 +589,47 @@ impl Supervisor {
         children_ref
     }
 
+    /// Sets the name of this supervisor, which will be used
+    /// instead of its identifier when its path (or the path of
+    /// one of its supervised elements) is formatted using
+    /// [`BastionPath::named`].
+    ///
+    /// Unlike the supervisor's identifier, its name stays the
+    /// same when it is restarted. Note that this should be
+    /// called before creating the supervisors and children
+    /// groups that it will supervise.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the supervisor, which shouldn't
+    ///   contain any `/`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// Bastion::supervisor(|sp| {
+    ///     sp.with_name("ingest")
+    ///         .children(|children| children.with_name("workers"))
+    /// }).expect("Couldn't create the supervisor");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`BastionPath::named`]: ../path/struct.BastionPath.html#method.named
+    pub fn with_name(mut self, name: &str) -> Self {
+        trace!("Supervisor({}): Setting name: {:?}", self.id(), name);
+        self.bcast.set_name(Some(name.to_string()));
+        self
+    }
+
     /// Sets the strategy the supervisor should use when one
     /// of its supervised children groups or supervisors dies
     /// (in the case of a children group, it could be because one
@@ -665,6 +731,13 @@ impl Supervisor {
         self
     }
 
+    #[cfg(feature = "distributed")]
+    pub(crate) fn without_restarts(mut self) -> Self {
+        trace!("Supervisor({}): Disabling restarts.", self.id());
+        self.restarts = false;
+        self
+    }
+
     async fn restart(&mut self, range: RangeFrom<usize>) {
         debug!("Supervisor({}): Restarting range: {:?}", self.id(), range);
         // TODO: stop or kill?
@@ -823,11 +896,37 @@ impl Supervisor {
     fn stopped(&mut self) {
         debug!("Supervisor({}): Stopped.", self.id());
         self.bcast.stopped();
+        self.exited(ExitReason::Stopped);
+    }
+
+    fn killed(&mut self) {
+        debug!("Supervisor({}): Killed.", self.id());
+        self.bcast.stopped();
+        self.exited(ExitReason::Killed);
     }
 
     fn faulted(&mut self) {
         debug!("Supervisor({}): Faulted.", self.id());
         self.bcast.faulted();
+        self.exited(ExitReason::Faulted);
+    }
+
+    fn exited(&self, reason: ExitReason) {
+        let system = self.bcast.system();
+        system.monitors().notify_terminated(self.id(), reason);
+        system.tree().unregister(self.bcast.path());
+    }
+
+    // Removes the supervised element at the given position from
+    // the order (because it won't be restarted), moving the ones
+    // added after it.
+    fn forget(&mut self, order: usize) {
+        self.order.remove(order);
+        for (other, _) in self.launched.values_mut() {
+            if *other > order {
+                *other -= 1;
+            }
+        }
     }
 
     async fn recover(&mut self, id: BastionId) -> Result<(), ()> {
@@ -836,6 +935,18 @@ impl Supervisor {
             self.id(),
             self.strategy
         );
+        if !self.restarts {
+            let (order, launched) = self.launched.remove(&id).ok_or(())?;
+            self.forget(order);
+            // FIXME: panics?
+            let supervised = launched.await.unwrap();
+            supervised.callbacks().after_stop();
+            supervised.exited_for_good();
+
+            self.bcast.unregister(supervised.id());
+            return Ok(());
+        }
+
         match self.strategy {
             SupervisionStrategy::OneForOne => {
                 let (order, launched) = self.launched.remove(&id).ok_or(())?;
@@ -915,7 +1026,7 @@ impl Supervisor {
                 ..
             } => {
                 self.kill(0..).await;
-                self.stopped();
+                self.killed();
 
                 return Err(());
             }
@@ -995,15 +1106,22 @@ impl Supervisor {
                 ..
             } => {
                 // FIXME: Err if None?
-                if let Some((_, launched)) = self.launched.remove(&id) {
+                if let Some((order, launched)) = self.launched.remove(&id) {
                     debug!("Supervisor({}): Supervised({}) stopped.", self.id(), id);
                     // TODO: add a "waiting" list an poll from it instead of awaiting
                     // FIXME: panics?
                     let supervised = launched.await.unwrap();
                     supervised.callbacks().after_stop();
+                    // NOTE: it is only restarted again if one of
+                    //      its siblings faults.
+                    supervised.exited_for_good();
 
                     self.bcast.unregister(&id);
-                    self.stopped.insert(id, supervised);
+                    if self.restarts {
+                        self.stopped.insert(id, supervised);
+                    } else {
+                        self.forget(order);
+                    }
                 }
             }
             Envelope {
@@ -1029,6 +1147,9 @@ impl Supervisor {
 
     async fn run(mut self) -> Self {
         debug!("Supervisor({}): Launched.", self.id());
+        let addr = RefAddr::new(self.bcast.path().clone(), self.bcast.sender().clone());
+        self.bcast.system().tree().register(addr);
+
         loop {
             match poll!(&mut self.bcast.next()) {
                 // TODO: Err if started == true?
@@ -1098,8 +1219,18 @@ impl Supervisor {
 }
 
 impl SupervisorRef {
-    pub(crate) fn new(id: BastionId, sender: Sender, path: Arc<BastionPath>) -> Self {
-        SupervisorRef { id, sender, path }
+    pub(crate) fn new(
+        id: BastionId,
+        sender: Sender,
+        path: Arc<BastionPath>,
+        system: BastionSystem,
+    ) -> Self {
+        SupervisorRef {
+            id,
+            sender,
+            path,
+            system,
+        }
     }
 
     /// Returns the identifier of the supervisor this `SupervisorRef`
@@ -1248,6 +1379,144 @@ impl SupervisorRef {
         self.children_with_id(BastionId::new(), init)
     }
 
+    /// Creates a new [`TypedChildren`], whose elements only handle
+    /// messages of type `P`, passes it through the specified `init`
+    /// closure and then sends it to the supervisor this
+    /// `SupervisorRef` is referencing to supervise it.
+    ///
+    /// This methods returns a [`TypedChildrenRef`] referencing the
+    /// newly created children group if it succeeded, or `Err(())`
+    /// otherwise.
+    ///
+    /// See [`children`].
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure taking the new [`TypedChildren`] as
+    ///   an argument and returning it once configured.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let sp_ref = Bastion::supervisor(|sp| sp).unwrap();
+    /// let children_ref: TypedChildrenRef<u64> = sp_ref.typed_children(|children| {
+    ///     children.with_exec(|ctx: TypedContext<u64>| {
+    ///         async move {
+    ///             let (number, req): (u64, Request) = ctx.recv().await?;
+    ///             // Handle the message...
+    ///             Ok(())
+    ///         }
+    ///     })
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`TypedChildren`]: ../typed/struct.TypedChildren.html
+    /// [`TypedChildrenRef`]: ../typed/struct.TypedChildrenRef.html
+    /// [`children`]: #method.children
+    pub fn typed_children<P, C>(&self, init: C) -> Result<TypedChildrenRef<P>, ()>
+    where
+        P: Message,
+        C: FnOnce(TypedChildren<P>) -> TypedChildren<P>,
+    {
+        let children_ref =
+            self.children(|children| init(TypedChildren::new(children)).into_untyped())?;
+        Ok(TypedChildrenRef::new(children_ref))
+    }
+
+    /// Deploys a children group on another node, using the
+    /// factory registered to it with the given name (see
+    /// [`Node::register_factory`]), and starts supervising it.
+    ///
+    /// The supervisor this `SupervisorRef` is referencing
+    /// supervises a local children group (named
+    /// `<factory>@<host>`) standing in for the remote one, which
+    /// stops when the remote group stops and faults when it
+    /// faults or when the connection to its node is lost (eg.
+    /// because the node went down), so that the supervisor's
+    /// [`SupervisionStrategy`] applies to the remote group too:
+    /// restarting the stand-in group deploys the remote group
+    /// again. Stopping, killing or restarting the stand-in group
+    /// stops the remote group.
+    ///
+    /// This method is only available with the `distributed`
+    /// feature.
+    ///
+    /// This methods returns a [`ChildrenRef`] referencing the
+    /// stand-in group if it succeeded, or `Err(())` otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The node this supervisor is running on.
+    /// * `host` - The identifier of the node to deploy the
+    ///   children group on.
+    /// * `factory` - The name of the factory registered to the
+    ///   other node to deploy the children group from.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::codec::Codecs;
+    /// use bastion::prelude::*;
+    /// use bastion::transport::{Node, NodeId};
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    ///     # Bastion::init();
+    ///     #
+    /// let node = Node::listen("127.0.0.1:0", Codecs::new())?;
+    /// let host: NodeId = "127.0.0.1:4000".parse().unwrap();
+    ///
+    /// let sp_ref = Bastion::supervisor(|sp| {
+    ///     sp.with_strategy(SupervisionStrategy::OneForAll)
+    /// }).expect("Couldn't create the supervisor.");
+    ///
+    /// // The "workers" factory registered to the other node is
+    /// // deployed again if the workers fault...
+    /// sp_ref
+    ///     .remote_children(&node, &host, "workers")
+    ///     .expect("Couldn't deploy the children group.");
+    /// // ...as well as this local children group.
+    /// sp_ref.children(|children| {
+    ///     // ...
+    ///     # children
+    /// }).expect("Couldn't create the children group.");
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    ///     # node.shutdown();
+    ///     # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Node::register_factory`]: ../transport/struct.Node.html#method.register_factory
+    /// [`SupervisionStrategy`]: enum.SupervisionStrategy.html
+    /// [`ChildrenRef`]: ../children_ref/struct.ChildrenRef.html
+    #[cfg(feature = "distributed")]
+    pub fn remote_children(
+        &self,
+        node: &Node,
+        host: &NodeId,
+        factory: &str,
+    ) -> Result<ChildrenRef, ()> {
+        debug!(
+            "SupervisorRef({}): Deploying {:?} on {}.",
+            self.id(),
+            factory,
+            host
+        );
+        self.children(|children| node.stand_in(host, factory, children))
+    }
+
     pub(crate) fn children_with_id<C>(&self, id: BastionId, init: C) -> Result<ChildrenRef, ()>
     where
         C: FnOnce(Children) -> Children,
@@ -1338,7 +1607,7 @@ impl SupervisorRef {
             strategy
         );
         let msg = BastionMessage::supervise_with(strategy);
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         self.send(env).map_err(|_| ())
     }
 
@@ -1390,16 +1659,16 @@ impl SupervisorRef {
     ///     # Bastion::block_until_stopped();
     /// # }
     /// ```
-    pub fn broadcast<M: Message>(&self, msg: M) -> Result<(), M> {
+    pub fn broadcast<M: Message + Sync>(&self, msg: M) -> Result<(), M> {
         debug!(
             "SupervisorRef({}): Broadcasting message: {:?}",
             self.id(),
             msg
         );
         let msg = BastionMessage::broadcast(msg);
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         // FIXME: panics?
-        self.send(env).map_err(|env| env.into_msg().unwrap())
+        self.send(env).map_err(|env| env.into_broadcast().unwrap())
     }
 
     /// Sends a message to the supervisor this `SupervisorRef`
@@ -1428,7 +1697,7 @@ impl SupervisorRef {
     pub fn stop(&self) -> Result<(), ()> {
         debug!("SupervisorRef({}): Stopping.", self.id());
         let msg = BastionMessage::stop();
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         self.send(env).map_err(|_| ())
     }
 
@@ -1458,10 +1727,70 @@ impl SupervisorRef {
     pub fn kill(&self) -> Result<(), ()> {
         debug!("SupervisorRef({}): Killing.", self.id());
         let msg = BastionMessage::kill();
-        let env = Envelope::from_dead_letters(msg);
+        let env = Envelope::from_dead_letters(&self.system, msg);
         self.send(env).map_err(|_| ())
     }
 
+    /// Returns whether the supervisor this `SupervisorRef` is referencing
+    /// is still running (ie. it hasn't stopped, been killed or
+    /// faulted yet).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    ///     # let sp_ref = Bastion::supervisor(|sp| sp).unwrap();
+    /// assert!(sp_ref.is_alive());
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    pub fn is_alive(&self) -> bool {
+        !self.sender.is_closed()
+    }
+
+    /// Returns a [`Terminated`] future which resolves to the
+    /// [`ExitReason`] of the supervisor this `SupervisorRef` is
+    /// referencing once it terminated.
+    ///
+    /// If the supervisor already terminated, the future
+    /// resolves to [`ExitReason::Unknown`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use bastion::prelude::*;
+    /// # use futures::executor;
+    /// #
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     # Bastion::start();
+    ///     #
+    ///     # let sp_ref = Bastion::supervisor(|sp| sp).unwrap();
+    /// let terminated = sp_ref.terminated();
+    /// sp_ref.stop().expect("Couldn't send the message.");
+    ///
+    /// assert_eq!(executor::block_on(terminated), ExitReason::Stopped);
+    /// assert!(!sp_ref.is_alive());
+    ///     #
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`Terminated`]: ../monitor/struct.Terminated.html
+    /// [`ExitReason`]: ../monitor/enum.ExitReason.html
+    /// [`ExitReason::Unknown`]: ../monitor/enum.ExitReason.html#variant.Unknown
+    pub fn terminated(&self) -> Terminated {
+        self.system.monitors().terminated(self.id(), &self.sender)
+    }
+
     pub(crate) fn send(&self, env: Envelope) -> Result<(), Envelope> {
         trace!("SupervisorRef({}): Sending message: {:?}", self.id(), env);
         self.sender
@@ -1472,6 +1801,10 @@ impl SupervisorRef {
     pub(crate) fn path(&self) -> &Arc<BastionPath> {
         &self.path
     }
+
+    pub(crate) fn system(&self) -> &BastionSystem {
+        &self.system
+    }
 }
 
 impl Supervised {
@@ -1545,6 +1878,13 @@ impl Supervised {
         }
     }
 
+    fn exited_for_good(&self) {
+        match self {
+            Supervised::Supervisor(supervisor) => supervisor.exited_for_good(),
+            Supervised::Children(children) => children.exited_for_good(),
+        }
+    }
+
     fn launch(self) -> RecoverableHandle<Self> {
         debug!("Supervised({}): Launching.", self.id());
         let stack = self.stack();
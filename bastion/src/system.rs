@@ -1,11 +1,19 @@
 use crate::broadcast::{Broadcast, Parent, Sender};
+use crate::child_ref::ChildRef;
+use crate::children::Children;
 use crate::children_ref::ChildrenRef;
+use crate::config::Config;
 use crate::context::{BastionContext, BastionId, NIL_ID};
-use crate::envelope::Envelope;
-use crate::message::{BastionMessage, Deployment};
-use crate::path::{BastionPath, BastionPathElement};
+use crate::envelope::{Envelope, RefAddr};
+use crate::message::{BastionMessage, Deployment, Message};
+use crate::monitor::Monitors;
+use crate::path::{self, BastionPath, BastionPathElement};
+use crate::pubsub::Topics;
+use crate::registry::Registry;
 use crate::supervisor::{Supervisor, SupervisorRef};
-use bastion_executor::pool;
+use crate::tree::Tree;
+use crate::typed::{TypedChildren, TypedChildrenRef};
+use bastion_executor::{deterministic, pool};
 use futures::prelude::*;
 use futures::stream::FuturesUnordered;
 use futures::{pending, poll};
@@ -13,23 +21,87 @@ use fxhash::{FxHashMap, FxHashSet};
 use lazy_static::lazy_static;
 use lightproc::prelude::*;
 use qutex::Qutex;
-use std::sync::Arc;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex, RwLock};
 use std::task::Poll;
+use std::thread;
 
-pub(crate) struct GlobalSystem {
+lazy_static! {
+    // The system used by `Bastion`'s methods, created when it
+    // is first initialized or used.
+    static ref SYSTEM: Mutex<Option<BastionSystem>> = Mutex::new(None);
+}
+
+#[derive(Clone)]
+/// A handle to an instance of the system, running its own
+/// supervision tree, with its own system supervisor, dead
+/// letters, registry of names, topics and configuration.
+///
+/// Several systems can run in the same process without
+/// interfering with each other: the supervisors and children
+/// groups created by a system are only supervised by it, the
+/// names and topics they use are only visible to the elements of
+/// the same system, and each system can be started, stopped or
+/// killed separately. They still share the same executor.
+///
+/// The methods of [`Bastion`] use a default system, which can be
+/// retrieved using [`Bastion::system`].
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::prelude::*;
+///
+/// fn main() {
+///     let first = BastionSystem::new();
+///     let second = BastionSystem::new();
+///
+///     // Each system supervises its own children groups...
+///     first.children(|children| {
+///         // ...
+///         # children
+///     }).expect("Couldn't create the children group.");
+///     second.children(|children| {
+///         // ...
+///         # children
+///     }).expect("Couldn't create the children group.");
+///
+///     first.start();
+///     second.start();
+///
+///     // ...and can be stopped without stopping the other one.
+///     first.stop();
+///     first.block_until_stopped();
+///     #
+///     # second.stop();
+///     # second.block_until_stopped();
+/// }
+/// ```
+///
+/// [`Bastion`]: struct.Bastion.html
+/// [`Bastion::system`]: struct.Bastion.html#method.system
+pub struct BastionSystem {
+    inner: Arc<SystemInner>,
+}
+
+struct SystemInner {
+    config: Config,
     sender: Sender,
-    supervisor: SupervisorRef,
-    dead_letters: ChildrenRef,
     path: Arc<BastionPath>,
+    registry: Registry,
+    topics: Topics,
+    monitors: Monitors,
+    tree: Tree,
+    // The system supervisor and the dead letters, which are
+    // dropped once the system stopped since they reference it.
+    supervisor: RwLock<Option<SupervisorRef>>,
+    dead_letters: RwLock<Option<ChildrenRef>>,
     handle: Qutex<Option<RecoverableHandle<()>>>,
 }
 
-lazy_static! {
-    pub(crate) static ref SYSTEM: GlobalSystem = System::init();
-}
-
 #[derive(Debug)]
 struct System {
+    system: BastionSystem,
     bcast: Broadcast,
     launched: FxHashMap<BastionId, RecoverableHandle<Supervisor>>,
     // TODO: set limit
@@ -39,61 +111,550 @@ struct System {
     started: bool,
 }
 
-impl GlobalSystem {
-    fn new(
-        sender: Sender,
-        supervisor: SupervisorRef,
-        dead_letters: ChildrenRef,
-        handle: RecoverableHandle<()>,
-    ) -> Self {
-        let handle = Some(handle);
-        let handle = Qutex::new(handle);
-        let path = Arc::new(BastionPath::root());
-
-        GlobalSystem {
-            sender,
-            supervisor,
-            dead_letters,
-            path,
-            handle,
+impl BastionSystem {
+    /// Creates and initializes a new system, using the default
+    /// [`Config`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::prelude::*;
+    ///
+    /// fn main() {
+    ///     let system = BastionSystem::new();
+    ///
+    ///     // You can now use the system...
+    ///     #
+    ///     # system.start();
+    ///     # system.stop();
+    ///     # system.block_until_stopped();
+    /// }
+    /// ```
+    ///
+    /// [`Config`]: struct.Config.html
+    pub fn new() -> Self {
+        BastionSystem::with_config(Config::default())
+    }
+
+    /// Creates and initializes a new system, using the
+    /// specified [`Config`].
+    ///
+    /// Note that hiding backtraces (see
+    /// [`Config::hide_backtraces`]) applies to the whole process.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration used to initialize the system.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::prelude::*;
+    ///
+    /// fn main() {
+    ///     let config = Config::new().show_backtraces();
+    ///     let system = BastionSystem::with_config(config);
+    ///
+    ///     // You can now use the system...
+    ///     #
+    ///     # system.start();
+    ///     # system.stop();
+    ///     # system.block_until_stopped();
+    /// }
+    /// ```
+    ///
+    /// [`Config`]: struct.Config.html
+    /// [`Config::hide_backtraces`]: struct.Config.html#method.hide_backtraces
+    pub fn with_config(config: Config) -> Self {
+        debug!("BastionSystem: Initializing with config: {:?}", config);
+        if config.backtraces().is_hide() {
+            debug!("BastionSystem: Hiding backtraces.");
+            std::panic::set_hook(Box::new(|_| ()));
+        }
+
+        BastionSystem::run_deterministically(&config);
+
+        System::init(config)
+    }
+
+    // Makes the executor run deterministically if the config asks
+    // for it, panicking if it can't (because it is already running
+    // processes on its thread pool or with another seed).
+    fn run_deterministically(config: &Config) {
+        if let Some(seed) = config.deterministic_seed() {
+            debug!(
+                "BastionSystem: Running deterministically with seed: {}",
+                seed
+            );
+            if deterministic::enable(seed).is_err() {
+                panic!(
+                    "BastionSystem: Couldn't run deterministically with seed {}: the executor \
+                     already ran processes on its thread pool or with another seed.",
+                    seed
+                );
+            }
+        }
+    }
+
+    // Returns the default system, creating it with the default
+    // config if it wasn't already.
+    pub(crate) fn default_system() -> Self {
+        // FIXME: panics?
+        let mut system = SYSTEM.lock().unwrap();
+        system
+            .get_or_insert_with(|| BastionSystem::with_config(Config::default()))
+            .clone()
+    }
+
+    // Initializes the default system with the given config if it
+    // wasn't already, or replaces it with a new one if it stopped.
+    pub(crate) fn init_default(config: Config) -> Self {
+        // FIXME: panics?
+        let mut system = SYSTEM.lock().unwrap();
+        if let Some(system) = &*system {
+            if !system.is_stopped() {
+                BastionSystem::run_deterministically(&config);
+                return system.clone();
+            }
+
+            debug!("BastionSystem: Replacing the stopped default system.");
         }
+
+        let new = BastionSystem::with_config(config);
+        *system = Some(new.clone());
+        new
+    }
+
+    /// Returns the configuration this system was created with.
+    pub fn config(&self) -> &Config {
+        &self.inner.config
+    }
+
+    /// Creates a new [`Supervisor`], passes it through the specified
+    /// `init` closure and then sends it to this system for it to
+    /// start supervising children.
+    ///
+    /// This method returns a [`SupervisorRef`] referencing the newly
+    /// created supervisor if it succeeded, or `Err(())`
+    /// otherwise.
+    ///
+    /// See [`Bastion::supervisor`].
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure taking the new [`Supervisor`] as an
+    ///   argument and returning it once configured.
+    ///
+    /// [`Supervisor`]: supervisor/struct.Supervisor.html
+    /// [`SupervisorRef`]: supervisor/struct.SupervisorRef.html
+    /// [`Bastion::supervisor`]: struct.Bastion.html#method.supervisor
+    pub fn supervisor<S>(&self, init: S) -> Result<SupervisorRef, ()>
+    where
+        S: FnOnce(Supervisor) -> Supervisor,
+    {
+        debug!("BastionSystem: Creating supervisor.");
+        let parent = Parent::system(self.clone());
+        let bcast = Broadcast::new(parent, BastionPathElement::Supervisor(BastionId::new()));
+
+        debug!("BastionSystem: Initializing Supervisor({}).", bcast.id());
+        let supervisor = Supervisor::new(bcast);
+        let supervisor = init(supervisor);
+        debug!("Supervisor({}): Initialized.", supervisor.id());
+        let supervisor_ref = supervisor.as_ref();
+
+        debug!("BastionSystem: Deploying Supervisor({}).", supervisor.id());
+        let msg = BastionMessage::deploy_supervisor(supervisor);
+        let envelope = Envelope::new(msg, self.path().clone(), self.sender().clone());
+        trace!("BastionSystem: Sending envelope: {:?}", envelope);
+        self.sender().unbounded_send(envelope).map_err(|_| ())?;
+
+        Ok(supervisor_ref)
+    }
+
+    /// Creates a new [`Children`], passes it through the specified
+    /// `init` closure and then sends it to this system's supervisor
+    /// for it to start supervising it.
+    ///
+    /// This methods returns a [`ChildrenRef`] referencing the newly
+    /// created children group it it succeeded, or `Err(())`
+    /// otherwise.
+    ///
+    /// See [`Bastion::children`].
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure taking the new [`Children`] as an
+    ///   argument and returning it once configured.
+    ///
+    /// [`Children`]: children/struct.Children.html
+    /// [`ChildrenRef`]: children_ref/struct.ChildrenRef.html
+    /// [`Bastion::children`]: struct.Bastion.html#method.children
+    pub fn children<C>(&self, init: C) -> Result<ChildrenRef, ()>
+    where
+        C: FnOnce(Children) -> Children,
+    {
+        debug!("BastionSystem: Creating children group.");
+        self.root_supervisor().ok_or(())?.children(init)
+    }
+
+    /// Creates a new [`TypedChildren`], whose elements only handle
+    /// messages of type `P`, passes it through the specified `init`
+    /// closure and then sends it to this system's supervisor for it
+    /// to start supervising it.
+    ///
+    /// This methods returns a [`TypedChildrenRef`] referencing the
+    /// newly created children group if it succeeded, or `Err(())`
+    /// otherwise.
+    ///
+    /// See [`Bastion::typed_children`].
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure taking the new [`TypedChildren`] as
+    ///   an argument and returning it once configured.
+    ///
+    /// [`TypedChildren`]: typed/struct.TypedChildren.html
+    /// [`TypedChildrenRef`]: typed/struct.TypedChildrenRef.html
+    /// [`Bastion::typed_children`]: struct.Bastion.html#method.typed_children
+    pub fn typed_children<P, C>(&self, init: C) -> Result<TypedChildrenRef<P>, ()>
+    where
+        P: Message,
+        C: FnOnce(TypedChildren<P>) -> TypedChildren<P>,
+    {
+        debug!("BastionSystem: Creating typed children group.");
+        self.root_supervisor().ok_or(())?.typed_children(init)
+    }
+
+    /// Creates a new children group with a single element, running
+    /// the future returned by the specified `action`.
+    ///
+    /// See [`Bastion::spawn`].
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - The closure returning the future the element
+    ///   will run.
+    ///
+    /// [`Bastion::spawn`]: struct.Bastion.html#method.spawn
+    pub fn spawn<I, F>(&self, action: I) -> Result<ChildrenRef, ()>
+    where
+        I: Fn(BastionContext) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        self.children(|ch| ch.with_redundancy(1).with_exec(action))
+    }
+
+    /// Sends a message to this system which will then send it to
+    /// all its root-level supervisors and their supervised children
+    /// and supervisors, etc.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)`
+    /// otherwise.
+    ///
+    /// See [`Bastion::broadcast`].
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to send.
+    ///
+    /// [`Bastion::broadcast`]: struct.Bastion.html#method.broadcast
+    pub fn broadcast<M: Message + Sync>(&self, msg: M) -> Result<(), M> {
+        debug!("BastionSystem: Broadcasting message: {:?}", msg);
+        let msg = BastionMessage::broadcast(msg);
+        let envelope = Envelope::from_dead_letters(self, msg);
+        trace!("BastionSystem: Sending envelope: {:?}", envelope);
+        // FIXME: panics?
+        self.sender()
+            .unbounded_send(envelope)
+            .map_err(|err| err.into_inner().into_broadcast().unwrap())
+    }
+
+    /// Registers the child referenced by the specified [`ChildRef`]
+    /// under the specified name in this system's registry.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(())` if
+    /// the name is already registered by another running child.
+    ///
+    /// See [`Bastion::register`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to register the child under.
+    /// * `child` - The reference to the child to register.
+    ///
+    /// [`ChildRef`]: child_ref/struct.ChildRef.html
+    /// [`Bastion::register`]: struct.Bastion.html#method.register
+    pub fn register(&self, name: &str, child: &ChildRef) -> Result<(), ()> {
+        debug!(
+            "BastionSystem: Registering Child({}) as {:?}.",
+            child.id(),
+            name
+        );
+        self.registry().register(name, child)
+    }
+
+    /// Unregisters the specified name from this system's registry,
+    /// returning the [`ChildRef`] it was registered with, if any.
+    ///
+    /// See [`Bastion::unregister`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to unregister.
+    ///
+    /// [`ChildRef`]: child_ref/struct.ChildRef.html
+    /// [`Bastion::unregister`]: struct.Bastion.html#method.unregister
+    pub fn unregister(&self, name: &str) -> Option<ChildRef> {
+        debug!("BastionSystem: Unregistering {:?}.", name);
+        self.registry().unregister(name)
+    }
+
+    /// Returns the [`ChildRef`] of the running child registered
+    /// under the specified name in this system's registry, if any.
+    ///
+    /// See [`Bastion::whereis`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name to look up.
+    ///
+    /// [`ChildRef`]: child_ref/struct.ChildRef.html
+    /// [`Bastion::whereis`]: struct.Bastion.html#method.whereis
+    pub fn whereis(&self, name: &str) -> Option<ChildRef> {
+        trace!("BastionSystem: Looking up {:?}.", name);
+        self.registry().whereis(name)
+    }
+
+    /// Publishes a message to the children of this system which
+    /// subscribed to a topic matching the specified one.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)`
+    /// otherwise.
+    ///
+    /// See [`Bastion::publish`].
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic to publish the message to.
+    /// * `msg` - The message to publish.
+    ///
+    /// [`Bastion::publish`]: struct.Bastion.html#method.publish
+    pub fn publish<M: Message + Sync>(&self, topic: &str, msg: M) -> Result<(), M> {
+        debug!(
+            "BastionSystem: Publishing message about {:?}: {:?}",
+            topic, msg
+        );
+        let msg = BastionMessage::broadcast(msg);
+        let env = Envelope::from_dead_letters(self, msg);
+        // FIXME: panics?
+        self.topics()
+            .publish(topic, env)
+            .map_err(|env| env.into_broadcast().unwrap())
+    }
+
+    /// Returns the [`RefAddr`] of the element of this system
+    /// identified by the given path, or `None` if it isn't running.
+    ///
+    /// See [`Bastion::resolve`].
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the element.
+    ///
+    /// [`RefAddr`]: envelope/struct.RefAddr.html
+    /// [`Bastion::resolve`]: struct.Bastion.html#method.resolve
+    pub fn resolve(&self, path: &BastionPath) -> Option<RefAddr> {
+        debug!("BastionSystem: Resolving {}.", path);
+        self.tree().resolve(path)
+    }
+
+    /// Returns the [`RefAddr`]s of all the running elements of this
+    /// system whose path matches the given pattern, or `Err(())` if
+    /// the pattern is invalid.
+    ///
+    /// See [`Bastion::select`].
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern to match paths against.
+    ///
+    /// [`RefAddr`]: envelope/struct.RefAddr.html
+    /// [`Bastion::select`]: struct.Bastion.html#method.select
+    pub fn select(&self, pattern: &str) -> Result<Vec<RefAddr>, ()> {
+        debug!("BastionSystem: Selecting {:?}.", pattern);
+        let pattern = path::parse_pattern(pattern)?;
+        Ok(self.tree().select(&pattern))
+    }
+
+    /// Sends a message to this system to tell it to start
+    /// handling messages and running children.
+    ///
+    /// See [`Bastion::start`].
+    ///
+    /// [`Bastion::start`]: struct.Bastion.html#method.start
+    pub fn start(&self) {
+        debug!("BastionSystem: Starting.");
+        let msg = BastionMessage::start();
+        let envelope = Envelope::from_dead_letters(self, msg);
+        trace!("BastionSystem: Sending envelope: {:?}", envelope);
+        // FIXME: Err(Error)
+        self.sender().unbounded_send(envelope).ok();
+    }
+
+    /// Sends a message to this system to tell it to stop every
+    /// running children groups and supervisors.
+    ///
+    /// See [`Bastion::stop`].
+    ///
+    /// [`Bastion::stop`]: struct.Bastion.html#method.stop
+    pub fn stop(&self) {
+        debug!("BastionSystem: Stopping.");
+        let msg = BastionMessage::stop();
+        let envelope = Envelope::from_dead_letters(self, msg);
+        trace!("BastionSystem: Sending envelope: {:?}", envelope);
+        // FIXME: Err(Error)
+        self.sender().unbounded_send(envelope).ok();
+    }
+
+    /// Sends a message to this system to tell it to kill every
+    /// running children groups and supervisors.
+    ///
+    /// See [`Bastion::kill`].
+    ///
+    /// [`Bastion::kill`]: struct.Bastion.html#method.kill
+    pub fn kill(&self) {
+        debug!("BastionSystem: Killing.");
+        let msg = BastionMessage::kill();
+        let envelope = Envelope::from_dead_letters(self, msg);
+        trace!("BastionSystem: Sending envelope: {:?}", envelope);
+        // FIXME: Err(Error)
+        self.sender().unbounded_send(envelope).ok();
+
+        // FIXME: panics
+        let mut handle = self.inner.handle.clone().lock().wait().unwrap();
+        if let Some(handle) = handle.take() {
+            debug!("BastionSystem: Cancelling system handle.");
+            handle.cancel();
+        }
+
+        drop(handle);
+        self.exited();
+    }
+
+    /// Blocks the current thread until this system is stopped
+    /// (either by calling [`stop`] or [`kill`]).
+    ///
+    /// See [`Bastion::block_until_stopped`].
+    ///
+    /// [`stop`]: #method.stop
+    /// [`kill`]: #method.kill
+    /// [`Bastion::block_until_stopped`]: struct.Bastion.html#method.block_until_stopped
+    pub fn block_until_stopped(&self) {
+        debug!("BastionSystem: Blocking until system is stopped.");
+        while !self.is_stopped() {
+            thread::yield_now();
+        }
+
+        debug!("BastionSystem: Unblocking because system is stopped.");
+    }
+
+    /// Returns whether this system is stopped (either because
+    /// [`stop`] or [`kill`] was called).
+    ///
+    /// [`stop`]: #method.stop
+    /// [`kill`]: #method.kill
+    pub fn is_stopped(&self) -> bool {
+        // FIXME: panics
+        let handle = self.inner.handle.clone().lock().wait().unwrap();
+        handle.is_none()
     }
 
     pub(crate) fn sender(&self) -> &Sender {
-        &self.sender
+        &self.inner.sender
     }
 
-    pub(crate) fn supervisor(&self) -> &SupervisorRef {
-        &self.supervisor
+    pub(crate) fn root_supervisor(&self) -> Option<SupervisorRef> {
+        // FIXME: panics?
+        self.inner.supervisor.read().unwrap().clone()
     }
 
-    pub(crate) fn dead_letters(&self) -> &ChildrenRef {
-        &self.dead_letters
+    pub(crate) fn dead_letters(&self) -> Option<ChildrenRef> {
+        // FIXME: panics?
+        self.inner.dead_letters.read().unwrap().clone()
     }
 
-    pub(crate) fn handle(&self) -> Qutex<Option<RecoverableHandle<()>>> {
-        self.handle.clone()
+    pub(crate) fn dead_letters_addr(&self) -> RefAddr {
+        match self.dead_letters() {
+            Some(dead_letters) => {
+                RefAddr::new(dead_letters.path().clone(), dead_letters.sender().clone())
+            }
+            // The dead letters are spawned once the system is
+            // initialized and dropped once it stopped, in which
+            // case the system itself stands in for them.
+            None => RefAddr::new(self.path().clone(), self.sender().clone()),
+        }
     }
 
     pub(crate) fn path(&self) -> &Arc<BastionPath> {
-        &self.path
+        &self.inner.path
+    }
+
+    pub(crate) fn registry(&self) -> &Registry {
+        &self.inner.registry
+    }
+
+    pub(crate) fn topics(&self) -> &Topics {
+        &self.inner.topics
+    }
+
+    pub(crate) fn monitors(&self) -> &Monitors {
+        &self.inner.monitors
+    }
+
+    pub(crate) fn tree(&self) -> &Tree {
+        &self.inner.tree
+    }
+
+    // Drops the references to the system supervisor (which is
+    // gone anyway), since they reference the system.
+    fn exited(&self) {
+        // FIXME: panics?
+        self.inner.supervisor.write().unwrap().take();
+        // FIXME: panics?
+        self.inner.dead_letters.write().unwrap().take();
     }
 }
 
 impl System {
-    fn init() -> GlobalSystem {
+    fn init(config: Config) -> BastionSystem {
         info!("System: Initializing.");
         let parent = Parent::none();
         let bcast = Broadcast::new_root(parent);
+
+        let inner = SystemInner {
+            config,
+            sender: bcast.sender().clone(),
+            path: Arc::new(BastionPath::root()),
+            registry: Registry::new(),
+            topics: Topics::new(),
+            monitors: Monitors::new(),
+            tree: Tree::new(),
+            supervisor: RwLock::new(None),
+            dead_letters: RwLock::new(None),
+            handle: Qutex::new(None),
+        };
+        let system = BastionSystem {
+            inner: Arc::new(inner),
+        };
+
         let launched = FxHashMap::default();
         let restart = FxHashSet::default();
         let waiting = FuturesUnordered::new();
         let pre_start_msgs = Vec::new();
         let started = false;
 
-        let sender = bcast.sender().clone();
-
-        let system = System {
+        let root = System {
+            system: system.clone(),
             bcast,
             launched,
             restart,
@@ -103,28 +664,27 @@ impl System {
         };
 
         debug!("System: Creating the system supervisor.");
-        let parent = Parent::system();
+        let parent = Parent::system(system.clone());
         let bcast = Broadcast::new(parent, BastionPathElement::Supervisor(NIL_ID));
-
         let supervisor = Supervisor::system(bcast);
         let supervisor_ref = supervisor.as_ref();
 
         let msg = BastionMessage::deploy_supervisor(supervisor);
-        let env = Envelope::new(
-            msg,
-            system.bcast.path().clone(),
-            system.bcast.sender().clone(),
-        );
-        system.bcast.send_self(env);
+        let env = Envelope::new(msg, root.bcast.path().clone(), root.bcast.sender().clone());
+        root.bcast.send_self(env);
 
         debug!("System: Launching.");
-        let stack = system.stack();
-        let handle = pool::spawn(system.run(), stack);
+        let stack = root.stack();
+        let handle = pool::spawn(root.run(), stack);
+        // FIXME: panics?
+        *system.inner.handle.clone().lock().wait().unwrap() = Some(handle);
+        *system.inner.supervisor.write().unwrap() = Some(supervisor_ref.clone());
 
-        let dead_letters_ref =
+        let dead_letters =
             Self::spawn_dead_letters(&supervisor_ref).expect("Can't spawn dead letters");
+        *system.inner.dead_letters.write().unwrap() = Some(dead_letters);
 
-        GlobalSystem::new(sender, supervisor_ref, dead_letters_ref, handle)
+        system
     }
 
     fn stack(&self) -> ProcStack {
@@ -150,7 +710,7 @@ impl System {
         warn!("System: Recovering Supervisor({}).", supervisor.id());
         supervisor.callbacks().before_restart();
 
-        let parent = Parent::system();
+        let parent = Parent::system(self.system.clone());
         let bcast = if supervisor.id() == &NIL_ID {
             None
         } else {
@@ -211,6 +771,7 @@ impl System {
             match poll!(&mut self.waiting.next()) {
                 Poll::Ready(Some(Some(supervisor))) => {
                     debug!("System: Supervisor({}) killed.", supervisor.id());
+                    supervisor.exited_for_good();
                 }
                 Poll::Ready(Some(None)) => {
                     debug!("System: Unknown Supervisor killed.");
@@ -234,6 +795,7 @@ impl System {
                 info!("System: Stopping.");
                 for supervisor in self.stop().await {
                     supervisor.callbacks().after_stop();
+                    supervisor.exited_for_good();
                 }
 
                 return Err(());
@@ -337,6 +899,7 @@ impl System {
                         self.recover(supervisor).await;
                     } else {
                         supervisor.callbacks().after_stop();
+                        supervisor.exited_for_good();
                     }
 
                     continue;
@@ -373,8 +936,11 @@ impl System {
                         // FIXME: Err(Error)?
                         if self.handle(msg).await.is_err() {
                             // FIXME: panics?
-                            let mut system = SYSTEM.handle().lock_async().await.unwrap();
-                            *system = None;
+                            let mut handle =
+                                self.system.inner.handle.clone().lock_async().await.unwrap();
+                            *handle = None;
+                            drop(handle);
+                            self.system.exited();
 
                             return;
                         }
@@ -388,8 +954,11 @@ impl System {
                     trace!("System: Received a new message (started=true): {:?}", msg);
                     if self.handle(msg).await.is_err() {
                         // FIXME: panics?
-                        let mut system = SYSTEM.handle().lock_async().await.unwrap();
-                        *system = None;
+                        let mut handle =
+                            self.system.inner.handle.clone().lock_async().await.unwrap();
+                        *handle = None;
+                        drop(handle);
+                        self.system.exited();
 
                         return;
                     }
@@ -403,3 +972,18 @@ impl System {
         }
     }
 }
+
+impl Default for BastionSystem {
+    fn default() -> Self {
+        BastionSystem::new()
+    }
+}
+
+impl Debug for BastionSystem {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("BastionSystem")
+            .field("path", &self.inner.path)
+            .field("config", &self.inner.config)
+            .finish()
+    }
+}
@@ -0,0 +1,501 @@
+//!
+//! Helpers to test elements running in the system, allowing to
+//! assert on the messages they send, to inject faults into them
+//! and to assert on the lifecycle of supervised elements.
+//!
+//! A [`TestProbe`] is a children group with one element which
+//! records every message it receives, so that a test can wait
+//! for a message of a given type (see [`TestProbe::expect_msg`])
+//! or check that no message was received in a given time (see
+//! [`TestProbe::expect_no_msg`]).
+//!
+//! A [`LifecycleProbe`] records when the supervisors or children
+//! groups it was given [`Callbacks`] to are started, restarted or
+//! stopped, and when the supervisors or children groups it
+//! watches fault (thus escalating the fault to their supervisor).
+//!
+//! All the methods waiting for something panic once the given
+//! timeout elapsed, to make the test calling them fail.
+//!
+//! [`TestProbe`]: struct.TestProbe.html
+//! [`TestProbe::expect_msg`]: struct.TestProbe.html#method.expect_msg
+//! [`TestProbe::expect_no_msg`]: struct.TestProbe.html#method.expect_no_msg
+//! [`LifecycleProbe`]: struct.LifecycleProbe.html
+//! [`Callbacks`]: ../struct.Callbacks.html
+use crate::bastion::Bastion;
+use crate::callbacks::Callbacks;
+use crate::child_ref::ChildRef;
+use crate::children_ref::ChildrenRef;
+use crate::context::BastionContext;
+use crate::envelope::{Envelope, SignedMessage};
+use crate::message::{BastionMessage, Message};
+use crate::monitor::{ExitReason, Terminated};
+use crate::system::BastionSystem;
+use bastion_executor::pool;
+use lightproc::prelude::*;
+use std::any::type_name;
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// A children group with one element recording every message it
+/// receives, for tests to assert on them.
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::prelude::*;
+/// use bastion::testkit::TestProbe;
+/// use std::time::Duration;
+///
+/// # fn main() {
+///     # Bastion::init();
+/// Bastion::start();
+///
+/// let probe = TestProbe::new().expect("Couldn't create the probe.");
+/// let probe_ref = probe.child().clone();
+/// Bastion::children(|children| {
+///     children.with_exec(move |ctx: BastionContext| {
+///         let probe_ref = probe_ref.clone();
+///         async move {
+///             ctx.tell(&probe_ref.addr(), "hello").expect("Couldn't send the message.");
+///             Ok(())
+///         }
+///     })
+/// }).expect("Couldn't create the children group.");
+///
+/// assert_eq!(probe.expect_msg::<&str>(Duration::from_secs(1)), "hello");
+/// probe.expect_no_msg(Duration::from_millis(10));
+///     #
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+pub struct TestProbe {
+    children: ChildrenRef,
+    // FIXME: `Receiver` isn't `Sync`.
+    recver: Mutex<Receiver<SignedMessage>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// An event recorded by a [`LifecycleProbe`].
+///
+/// [`LifecycleProbe`]: struct.LifecycleProbe.html
+pub enum LifecycleEvent {
+    /// The element was started (by its supervisor, or by the
+    /// system for supervisors).
+    Started,
+    /// The element was restarted, either because it faulted or
+    /// because of its supervisor's strategy.
+    Restarted,
+    /// The element was stopped (or killed) and won't be
+    /// restarted.
+    Stopped,
+    /// The element faulted, leaving its supervisor to handle the
+    /// fault.
+    Escalated,
+}
+
+#[derive(Default, Clone)]
+/// A recorder of the lifecycle events of supervisors and
+/// children groups, for tests to assert on them.
+///
+/// The started, restarted and stopped events are recorded using
+/// the [`Callbacks`] returned by [`callbacks`], while escalated
+/// events are recorded by [`watch`]ing an element.
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::prelude::*;
+/// use bastion::testkit::{LifecycleEvent, LifecycleProbe};
+/// use std::time::Duration;
+///
+/// # fn main() {
+///     # Bastion::init();
+/// Bastion::start();
+///
+/// let probe = LifecycleProbe::new();
+/// let children_ref = Bastion::children(|children| {
+///     children
+///         .with_callbacks(probe.callbacks())
+///         .with_exec(|ctx: BastionContext| {
+///             async move {
+///                 ctx.recv().await?;
+///                 Err(())
+///             }
+///         })
+/// }).expect("Couldn't create the children group.");
+///
+/// let timeout = Duration::from_secs(1);
+/// probe.expect_event(LifecycleEvent::Started, timeout);
+///
+/// children_ref.broadcast("fault").expect("Couldn't broadcast the message.");
+/// probe.expect_event(LifecycleEvent::Restarted, timeout);
+///     #
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`Callbacks`]: ../struct.Callbacks.html
+/// [`callbacks`]: #method.callbacks
+/// [`watch`]: #method.watch
+pub struct LifecycleProbe {
+    events: Arc<(Mutex<VecDeque<LifecycleEvent>>, Condvar)>,
+}
+
+impl TestProbe {
+    /// Creates a new probe in the default system (see
+    /// [`Bastion::system`]).
+    ///
+    /// This method returns the probe if it succeeded, or `Err(())`
+    /// otherwise.
+    ///
+    /// [`Bastion::system`]: ../struct.Bastion.html#method.system
+    pub fn new() -> Result<Self, ()> {
+        TestProbe::in_system(&Bastion::system())
+    }
+
+    /// Creates a new probe in the given system.
+    ///
+    /// This method returns the probe if it succeeded, or `Err(())`
+    /// otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - The system the probe's children group will be
+    ///   supervised by.
+    pub fn in_system(system: &BastionSystem) -> Result<Self, ()> {
+        let (sender, recver) = mpsc::channel();
+        let sender = Arc::new(Mutex::new(sender));
+        let children = system.children(|children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let sender = sender.clone();
+                async move {
+                    loop {
+                        let smsg = ctx.recv().await?;
+                        // FIXME: panics?
+                        sender.lock().unwrap().send(smsg).ok();
+                    }
+                }
+            })
+        })?;
+
+        let recver = Mutex::new(recver);
+        Ok(TestProbe { children, recver })
+    }
+
+    /// Returns a reference to the probe's element, which other
+    /// elements can send messages to.
+    pub fn child(&self) -> &ChildRef {
+        // FIXME: panics?
+        &self.children.elems()[0]
+    }
+
+    /// Returns a reference to the probe's children group.
+    pub fn children(&self) -> &ChildrenRef {
+        &self.children
+    }
+
+    /// Waits for the probe to receive a message, returning it or
+    /// `None` if none was received before the timeout elapsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum duration to wait for.
+    pub fn recv(&self, timeout: Duration) -> Option<SignedMessage> {
+        // FIXME: panics?
+        let recver = self.recver.lock().unwrap();
+        // NOTE: this also returns `None` if the probe's element
+        //      stopped, dropping the sender.
+        recver.recv_timeout(timeout).ok()
+    }
+
+    /// Waits for the probe to receive a message which was sent
+    /// (using `tell` or `ask`) with the type `M`, and returns it.
+    ///
+    /// If the message was asked, the element which asked it
+    /// won't receive an answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum duration to wait for.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if no message was received before the
+    /// timeout elapsed, or if the next message received isn't of
+    /// type `M`.
+    pub fn expect_msg<M: Message>(&self, timeout: Duration) -> M {
+        let smsg = self.expect_any(timeout, type_name::<M>());
+        match smsg.msg.downcast() {
+            Ok(msg) => msg,
+            Err(msg) => panic!(
+                "TestProbe: Expected a message of type {}, received {:?}.",
+                type_name::<M>(),
+                msg
+            ),
+        }
+    }
+
+    /// Waits for the probe to receive a message which was
+    /// broadcasted or published with the type `M`, and returns
+    /// it.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum duration to wait for.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if no message was received before the
+    /// timeout elapsed, or if the next message received isn't a
+    /// broadcasted message of type `M`.
+    pub fn expect_broadcast<M: Message + Sync>(&self, timeout: Duration) -> Arc<M> {
+        let smsg = self.expect_any(timeout, type_name::<M>());
+        match smsg.msg.downcast_ref() {
+            Some(msg) => msg,
+            None => panic!(
+                "TestProbe: Expected a broadcasted message of type {}, received {:?}.",
+                type_name::<M>(),
+                smsg.msg
+            ),
+        }
+    }
+
+    /// Checks that the probe doesn't receive any message during
+    /// the given duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The duration to wait for.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if a message was received before the
+    /// timeout elapsed.
+    pub fn expect_no_msg(&self, timeout: Duration) {
+        if let Some(smsg) = self.recv(timeout) {
+            panic!("TestProbe: Expected no message, received {:?}.", smsg);
+        }
+    }
+
+    fn expect_any(&self, timeout: Duration, expected: &str) -> SignedMessage {
+        match self.recv(timeout) {
+            Some(smsg) => smsg,
+            None => panic!(
+                "TestProbe: Timed out after {:?} waiting for a message of type {}.",
+                timeout, expected
+            ),
+        }
+    }
+}
+
+impl LifecycleProbe {
+    /// Creates a new probe which didn't record any event.
+    pub fn new() -> Self {
+        LifecycleProbe::default()
+    }
+
+    /// Returns the [`Callbacks`] recording the started, restarted
+    /// and stopped events of the supervisors or children groups
+    /// they are given to.
+    ///
+    /// [`Callbacks`]: ../struct.Callbacks.html
+    pub fn callbacks(&self) -> Callbacks {
+        let started = self.clone();
+        let restarted = self.clone();
+        let stopped = self.clone();
+
+        // NOTE: `before_restart` calls `after_stop` if it isn't
+        //      set, which would record a stopped event.
+        Callbacks::new()
+            .with_before_start(move || started.record(LifecycleEvent::Started))
+            .with_before_restart(|| ())
+            .with_after_restart(move || restarted.record(LifecycleEvent::Restarted))
+            .with_after_stop(move || stopped.record(LifecycleEvent::Stopped))
+    }
+
+    /// Records an escalated event once the element whose
+    /// termination is given faults.
+    ///
+    /// Because restarted elements are new elements, only the
+    /// first fault of the element is recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `terminated` - The future returned by the `terminated`
+    ///   method of a [`SupervisorRef`], [`ChildrenRef`] or
+    ///   [`ChildRef`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::prelude::*;
+    /// use bastion::testkit::{LifecycleEvent, LifecycleProbe};
+    ///
+    /// # fn main() {
+    ///     # Bastion::init();
+    ///     #
+    /// let probe = LifecycleProbe::new();
+    /// let sp_ref = Bastion::supervisor(|sp| {
+    ///     sp.with_callbacks(probe.callbacks())
+    /// }).expect("Couldn't create the supervisor.");
+    /// probe.watch(sp_ref.terminated());
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    /// # }
+    /// ```
+    ///
+    /// [`SupervisorRef`]: ../supervisor/struct.SupervisorRef.html
+    /// [`ChildrenRef`]: ../children_ref/struct.ChildrenRef.html
+    /// [`ChildRef`]: ../child_ref/struct.ChildRef.html
+    pub fn watch(&self, terminated: Terminated) {
+        let probe = self.clone();
+        pool::spawn(
+            async move {
+                if terminated.await == ExitReason::Faulted {
+                    probe.record(LifecycleEvent::Escalated);
+                }
+            },
+            ProcStack::default(),
+        );
+    }
+
+    /// Waits for the next recorded event and checks that it is
+    /// the expected one.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - The event expected to be recorded next.
+    /// * `timeout` - The maximum duration to wait for.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if no event was recorded before the
+    /// timeout elapsed, or if the next event isn't `expected`.
+    pub fn expect_event(&self, expected: LifecycleEvent, timeout: Duration) {
+        match self.next_event(timeout) {
+            Some(event) if event == expected => (),
+            Some(event) => panic!(
+                "LifecycleProbe: Expected {:?}, recorded {:?}.",
+                expected, event
+            ),
+            None => panic!(
+                "LifecycleProbe: Timed out after {:?} waiting for {:?}.",
+                timeout, expected
+            ),
+        }
+    }
+
+    /// Checks that no event is recorded during the given
+    /// duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The duration to wait for.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if an event was recorded before the
+    /// timeout elapsed.
+    pub fn expect_no_event(&self, timeout: Duration) {
+        if let Some(event) = self.next_event(timeout) {
+            panic!("LifecycleProbe: Expected no event, recorded {:?}.", event);
+        }
+    }
+
+    fn next_event(&self, timeout: Duration) -> Option<LifecycleEvent> {
+        let (events, cond) = &*self.events;
+        let deadline = Instant::now() + timeout;
+        // FIXME: panics?
+        let mut events = events.lock().unwrap();
+        loop {
+            if let Some(event) = events.pop_front() {
+                return Some(event);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return None;
+            }
+
+            // FIXME: panics?
+            events = cond.wait_timeout(events, deadline - now).unwrap().0;
+        }
+    }
+
+    fn record(&self, event: LifecycleEvent) {
+        debug!("LifecycleProbe: Recording {:?}.", event);
+        let (events, cond) = &*self.events;
+        // FIXME: panics?
+        events.lock().unwrap().push_back(event);
+        cond.notify_all();
+    }
+}
+
+/// Makes the child referenced by the given [`ChildRef`] fault as
+/// if its future returned an error, letting its supervisor
+/// recover from it.
+///
+/// This method returns `()` if the child was notified, or
+/// `Err(())` if it already stopped.
+///
+/// # Arguments
+///
+/// * `child` - The child to inject the fault into.
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::prelude::*;
+/// use bastion::testkit;
+///
+/// # fn main() {
+///     # Bastion::init();
+///     #
+/// let children_ref = Bastion::children(|children| {
+///     // ...
+///     # children
+/// }).expect("Couldn't create the children group.");
+///
+/// for child in children_ref.elems() {
+///     testkit::inject_fault(child).expect("Couldn't inject the fault.");
+/// }
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`ChildRef`]: ../child_ref/struct.ChildRef.html
+pub fn inject_fault(child: &ChildRef) -> Result<(), ()> {
+    debug!("TestKit: Injecting a fault into Child({}).", child.id());
+    let msg = BastionMessage::faulted(child.id().clone());
+    let env = Envelope::from_dead_letters(child.system(), msg);
+    child.send(env).map_err(|_| ())
+}
+
+impl Debug for TestProbe {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("TestProbe")
+            .field("children", &self.children)
+            .finish()
+    }
+}
+
+impl Debug for LifecycleProbe {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        // FIXME: panics?
+        let events = self.events.0.lock().unwrap();
+        fmt.debug_struct("LifecycleProbe")
+            .field("events", &*events)
+            .finish()
+    }
+}
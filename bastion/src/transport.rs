@@ -0,0 +1,1234 @@
+//!
+//! A transport allowing elements to send messages to elements
+//! running on other nodes (ie. other systems, usually running in
+//! other processes or on other machines) over TCP.
+//!
+//! A [`Node`] listens for connections from other nodes and
+//! creates [`RefAddr`]s referencing remote elements, identified by
+//! the [`NodeId`] of the node they are running on and their path.
+//! Messages sent to those using [`BastionContext::tell`] or
+//! [`BastionContext::ask`] are encoded using the node's
+//! [`Codecs`] and written to the link to the remote node, which
+//! keeps them in the order they were sent in and reconnects to it
+//! when its connection is lost. Messages received from a remote
+//! element are signed with a remote [`RefAddr`] too, so they can be
+//! replied to or answered as usual.
+//!
+//! Note that messages are delivered at most once: they can be lost
+//! along with a connection, in which case the [`Answer`]s of the
+//! messages asked over it return an error.
+//!
+//! Supervisors can also deploy children groups on other nodes
+//! (see [`SupervisorRef::remote_children`]), using the factories
+//! registered to them with [`Node::register_factory`]. The
+//! supervisor then supervises a local children group standing in
+//! for the remote one, which stops or faults when the remote group
+//! does, and faults when the connection to its node is lost. When
+//! the stand-in group is stopped, killed or restarted, the remote
+//! group is stopped too.
+//!
+//! This module is only available with the `distributed` feature.
+//!
+//! [`Node`]: struct.Node.html
+//! [`RefAddr`]: ../envelope/struct.RefAddr.html
+//! [`NodeId`]: struct.NodeId.html
+//! [`BastionContext::tell`]: ../context/struct.BastionContext.html#method.tell
+//! [`BastionContext::ask`]: ../context/struct.BastionContext.html#method.ask
+//! [`Codecs`]: ../codec/struct.Codecs.html
+//! [`Answer`]: ../message/struct.Answer.html
+//! [`SupervisorRef::remote_children`]: ../supervisor/struct.SupervisorRef.html#method.remote_children
+//! [`Node::register_factory`]: struct.Node.html#method.register_factory
+use crate::bastion::Bastion;
+use crate::children::Children;
+use crate::children_ref::ChildrenRef;
+use crate::cluster::{Gossip, GOSSIP_TAG};
+use crate::codec::{Bincode, Codecs, Format, MsgCodecs};
+use crate::context::BastionContext;
+use crate::envelope::{Envelope, RefAddr, SignedMessage};
+use crate::message::{AnswerSender, BastionMessage};
+use crate::monitor::ExitReason;
+use crate::path::BastionPath;
+use crate::supervisor::SupervisorRef;
+use crate::system::BastionSystem;
+use bastion_executor::pool;
+use futures::channel::oneshot;
+use fxhash::FxHashMap;
+use lightproc::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::io::{self, Read, Write};
+use std::net::{AddrParseError, Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, RwLock, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The delay before trying to connect to a node again, which is
+// doubled after each failed attempt.
+const MIN_BACKOFF: Duration = Duration::from_millis(50);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+// Frames announcing a larger size are considered invalid.
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+// Configures a children group deployed by another node.
+type Factory = Arc<dyn Fn(Children) -> Children + Send + Sync>;
+
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// The identifier of a [`Node`], which is the address it is
+/// listening on.
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::transport::NodeId;
+///
+/// let node: NodeId = "127.0.0.1:4000".parse().unwrap();
+/// assert_eq!(node.addr().port(), 4000);
+/// assert_eq!(node.to_string(), "127.0.0.1:4000");
+/// ```
+///
+/// [`Node`]: struct.Node.html
+pub struct NodeId(SocketAddr);
+
+#[derive(Clone)]
+/// A node of a distributed system, listening for connections
+/// from other nodes and allowing elements to send messages to
+/// the elements running on them.
+///
+/// Only the message types registered to the [`Codecs`] a node
+/// was created with can be sent to and received from other
+/// nodes.
+///
+/// A node is shut down (see [`shutdown`]) when it is dropped,
+/// once all its handles (including the ones held by the
+/// children groups standing in for remote ones and by a
+/// [`Cluster`]) are.
+///
+/// # Example
+///
+/// ```rust
+/// use bastion::codec::Codecs;
+/// use bastion::prelude::*;
+/// use bastion::transport::{Node, NodeId};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Serialize, Deserialize)]
+/// struct Ping(u64);
+///
+/// # fn main() -> std::io::Result<()> {
+///     # Bastion::init();
+///     #
+/// let codecs = Codecs::new().register::<Ping>("ping");
+/// let node = Node::listen("127.0.0.1:0", codecs)?;
+///
+/// // The identifier of another node and the path of an
+/// // element running on it...
+/// let other: NodeId = "127.0.0.1:4000".parse().unwrap();
+/// let path: BastionPath = "/pings/ponger/0".parse().unwrap();
+/// let remote: RefAddr = node.remote(&other, &path);
+///
+/// Bastion::children(|children| {
+///     children.with_exec(move |ctx: BastionContext| {
+///         let remote = remote.clone();
+///         async move {
+///             // ...which can be sent messages like local elements.
+///             ctx.tell(&remote, Ping(42)).expect("Couldn't send the message.");
+///             Ok(())
+///         }
+///     })
+/// }).expect("Couldn't create the children group.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+///     # node.shutdown();
+///     # Ok(())
+/// # }
+/// ```
+///
+/// [`Codecs`]: ../codec/struct.Codecs.html
+/// [`shutdown`]: #method.shutdown
+/// [`Cluster`]: ../cluster/struct.Cluster.html
+pub struct Node {
+    inner: Arc<NodeInner>,
+}
+
+struct NodeInner {
+    id: NodeId,
+    // The system the messages received by this node are
+    // delivered in.
+    system: BastionSystem,
+    codecs: Arc<dyn MsgCodecs>,
+    links: Mutex<FxHashMap<NodeId, Link>>,
+    // The factories other nodes can deploy children groups from,
+    // by name.
+    factories: RwLock<FxHashMap<String, Factory>>,
+    // The supervisor of the children groups deployed by other
+    // nodes, created when the first one is.
+    deployed: Mutex<Option<SupervisorRef>>,
+    closed: AtomicBool,
+}
+
+#[derive(Clone)]
+// The link to another node, which queues the messages sent to
+// it and writes them to a connection to it (which is created by
+// the link if it was used to send messages, or by the other node
+// otherwise), and reads the messages received from it.
+pub(crate) struct Link {
+    inner: Arc<LinkInner>,
+}
+
+struct LinkInner {
+    peer: NodeId,
+    system: BastionSystem,
+    node: Weak<NodeInner>,
+    codecs: Arc<dyn MsgCodecs>,
+    state: Mutex<LinkState>,
+    // Notified when frames are queued, when the connection frames
+    // are written to changes, or when the link is closed.
+    cond: Condvar,
+}
+
+#[derive(Default)]
+struct LinkState {
+    // The encoded frames waiting to be written.
+    frames: VecDeque<Vec<u8>>,
+    // The connections to the other node, by identifier.
+    conns: FxHashMap<u64, TcpStream>,
+    // The connection frames are written to.
+    writer: Option<u64>,
+    next_conn: u64,
+    // The senders of the messages asked over the link, by
+    // request identifier.
+    pending: FxHashMap<u64, AnswerSender>,
+    next_request: u64,
+    // The senders notified when the children groups deployed on
+    // the other node exit, by deployment identifier.
+    deploying: FxHashMap<u64, oneshot::Sender<ExitReason>>,
+    // The children groups deployed by the other node, by
+    // deployment identifier.
+    deployed: FxHashMap<u64, ChildrenRef>,
+    // Whether the link should connect to the other node when
+    // there is no connection to it.
+    dial: bool,
+    closed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Frame {
+    // The first frame sent by a node connecting to another one.
+    Hello(NodeId),
+    Msg {
+        to: BastionPath,
+        from: BastionPath,
+        ask: Option<u64>,
+        msg: Vec<u8>,
+    },
+    // The answer to an asked message (or `None` if it couldn't
+    // be answered), along with the path of its sender.
+    Answer {
+        id: u64,
+        answer: Option<(BastionPath, Vec<u8>)>,
+    },
+    // Asks the other node to deploy a children group using one
+    // of its factories...
+    Deploy {
+        id: u64,
+        factory: String,
+    },
+    // ...to stop it...
+    Undeploy {
+        id: u64,
+    },
+    // ...and tells the node which deployed it that it exited.
+    Exited {
+        id: u64,
+        reason: ExitReason,
+    },
+}
+
+// Stops the children group deployed on the other node when the
+// stand-in group it was deployed by stops (or is restarted)
+// while it is still running.
+struct Deployment {
+    link: Link,
+    id: u64,
+    exited: bool,
+}
+
+impl NodeId {
+    /// Creates the identifier of the node listening on the
+    /// given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address the node is listening on.
+    pub fn new(addr: SocketAddr) -> Self {
+        NodeId(addr)
+    }
+
+    /// Returns the address the node is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.0
+    }
+}
+
+impl Node {
+    /// Creates a new node listening on the given address (which
+    /// becomes its identifier, so it should be reachable by the
+    /// other nodes) and able to send and receive the message
+    /// types registered to `codecs`.
+    ///
+    /// This method returns the node if it succeeded, or an
+    /// error if it couldn't listen on the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to listen on, whose port can be
+    ///   `0` to let the OS pick one.
+    /// * `codecs` - The message types that can be sent to and
+    ///   received from other nodes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::codec::Codecs;
+    /// use bastion::transport::Node;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let codecs = Codecs::new().register::<String>("string");
+    /// let node = Node::listen("127.0.0.1:0", codecs)?;
+    /// println!("Listening on {}.", node.id());
+    ///     #
+    ///     # node.shutdown();
+    ///     # Ok(())
+    /// # }
+    /// ```
+    pub fn listen<A: ToSocketAddrs, F: Format>(addr: A, codecs: Codecs<F>) -> io::Result<Self> {
+        Self::listen_in(&Bastion::system(), addr, codecs)
+    }
+
+    /// Creates a node delivering the messages it receives to the
+    /// elements of the given [`BastionSystem`] instead of the
+    /// default one (see [`listen`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `system` - The system the node's elements belong to.
+    /// * `addr` - The address to listen on, whose port can be
+    ///   `0` to let the OS pick one.
+    /// * `codecs` - The message types that can be sent to and
+    ///   received from other nodes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::codec::Codecs;
+    /// use bastion::transport::Node;
+    /// use bastion::BastionSystem;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    /// let system = BastionSystem::new();
+    /// let node = Node::listen_in(&system, "127.0.0.1:0", Codecs::new())?;
+    ///     #
+    ///     # system.start();
+    ///     # node.shutdown();
+    ///     # system.stop();
+    ///     # system.block_until_stopped();
+    ///     # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`BastionSystem`]: ../struct.BastionSystem.html
+    /// [`listen`]: #method.listen
+    pub fn listen_in<A: ToSocketAddrs, F: Format>(
+        system: &BastionSystem,
+        addr: A,
+        codecs: Codecs<F>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let id = NodeId(listener.local_addr()?);
+        // The messages used by the membership service (see
+        // `Cluster`) can always be sent.
+        let codecs = codecs.register::<Gossip>(GOSSIP_TAG);
+        let inner = Arc::new(NodeInner {
+            id: id.clone(),
+            system: system.clone(),
+            codecs: Arc::new(codecs),
+            links: Mutex::new(FxHashMap::default()),
+            factories: RwLock::new(FxHashMap::default()),
+            deployed: Mutex::new(None),
+            closed: AtomicBool::new(false),
+        });
+
+        // The listener only holds a weak reference to the node so
+        // that it is shut down once dropped.
+        let accepting = Arc::downgrade(&inner);
+        let listening = id.clone();
+        thread::Builder::new()
+            .name(format!("bastion-node-{}", id))
+            .spawn(move || Node::accept(listening, accepting, listener))?;
+
+        info!("Node({}): Listening.", id);
+        Ok(Node { inner })
+    }
+
+    /// Returns the identifier of this node.
+    pub fn id(&self) -> &NodeId {
+        &self.inner.id
+    }
+
+    /// Returns the system this node delivers messages in.
+    pub fn system(&self) -> &BastionSystem {
+        &self.inner.system
+    }
+
+    /// Returns a [`RefAddr`] referencing the element running on
+    /// another node with the given path, connecting to it if it
+    /// wasn't already.
+    ///
+    /// Messages sent to the returned address are queued until a
+    /// connection to the other node is established. The path is
+    /// resolved by the other node (see [`Bastion::resolve`]) when
+    /// it receives them, and they are dropped if no element with
+    /// this path is running on it.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the node the element is
+    ///   running on.
+    /// * `path` - The path of the element, which can be parsed
+    ///   from a string using names instead of identifiers so
+    ///   that it stays valid when the element is restarted.
+    ///
+    /// [`RefAddr`]: ../envelope/struct.RefAddr.html
+    /// [`Bastion::resolve`]: ../struct.Bastion.html#method.resolve
+    pub fn remote(&self, node: &NodeId, path: &BastionPath) -> RefAddr {
+        debug!("Node({}): Referencing {} on {}.", self.id(), path, node);
+        RefAddr::remote(path.clone(), self.link(node, true))
+    }
+
+    /// Returns whether this node is currently connected to the
+    /// given node.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The identifier of the other node.
+    pub fn is_connected(&self, node: &NodeId) -> bool {
+        // FIXME: panics?
+        let links = self.inner.links.lock().unwrap();
+        links
+            .get(node)
+            .map(|link| link.is_connected())
+            .unwrap_or(false)
+    }
+
+    /// Registers a factory that other nodes can deploy children
+    /// groups from (see [`SupervisorRef::remote_children`]), by
+    /// passing a new [`Children`] through it once per deployment.
+    ///
+    /// If a factory was already registered with the same name,
+    /// it is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name other nodes deploy the factory with.
+    /// * `init` - The closure taking a new [`Children`] as an
+    ///   argument and returning it once configured.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bastion::codec::Codecs;
+    /// use bastion::prelude::*;
+    /// use bastion::transport::Node;
+    ///
+    /// # fn main() -> std::io::Result<()> {
+    ///     # Bastion::init();
+    ///     #
+    /// let node = Node::listen("127.0.0.1:0", Codecs::new())?;
+    /// node.register_factory("workers", |children| {
+    ///     children
+    ///         .with_redundancy(4)
+    ///         .with_exec(|ctx: BastionContext| {
+    ///             async move {
+    ///                 // Handle the messages sent to the workers...
+    ///                 # Ok(())
+    ///             }
+    ///         })
+    /// });
+    ///     #
+    ///     # Bastion::start();
+    ///     # Bastion::stop();
+    ///     # Bastion::block_until_stopped();
+    ///     # node.shutdown();
+    ///     # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`SupervisorRef::remote_children`]: ../supervisor/struct.SupervisorRef.html#method.remote_children
+    /// [`Children`]: ../children/struct.Children.html
+    pub fn register_factory<I>(&self, name: &str, init: I)
+    where
+        I: Fn(Children) -> Children + Send + Sync + 'static,
+    {
+        debug!("Node({}): Registering factory {:?}.", self.id(), name);
+        // FIXME: panics?
+        let mut factories = self.inner.factories.write().unwrap();
+        factories.insert(name.to_string(), Arc::new(init));
+    }
+
+    /// Stops listening for connections and closes the links to
+    /// the other nodes, dropping the messages waiting to be sent
+    /// to them and stopping the children groups they deployed.
+    ///
+    /// This is done when the node is dropped if this method
+    /// wasn't called.
+    pub fn shutdown(&self) {
+        self.inner.shutdown();
+    }
+
+    // Configures a children group standing in for the one
+    // deployed on the given node using the given factory.
+    pub(crate) fn stand_in(&self, host: &NodeId, factory: &str, children: Children) -> Children {
+        let node = self.clone();
+        let host = host.clone();
+        let factory = factory.to_string();
+        children
+            .with_name(&format!("{}@{}", factory, host))
+            .with_exec(move |_: BastionContext| {
+                let link = node.link(&host, true);
+                link.deploy(factory.clone())
+            })
+    }
+
+    // Deploys a children group using the given factory, under
+    // the supervisor of the groups deployed by other nodes.
+    fn host(&self, factory: &str) -> Result<ChildrenRef, ()> {
+        // FIXME: panics?
+        let init = match self.inner.factories.read().unwrap().get(factory) {
+            Some(init) => init.clone(),
+            None => {
+                warn!("Node({}): Unknown factory {:?}.", self.id(), factory);
+                return Err(());
+            }
+        };
+
+        // FIXME: panics?
+        let mut deployed = self.inner.deployed.lock().unwrap();
+        if self.inner.closed.load(Ordering::SeqCst) {
+            return Err(());
+        }
+
+        let supervisor = match &*deployed {
+            Some(supervisor) => supervisor.clone(),
+            None => {
+                let name = format!("deployed@{}", self.id());
+                let supervisor = self
+                    .system()
+                    .supervisor(|sp| sp.with_name(&name).without_restarts())?;
+                *deployed = Some(supervisor.clone());
+                supervisor
+            }
+        };
+
+        supervisor.children(|children| init(children))
+    }
+
+    fn link(&self, peer: &NodeId, dial: bool) -> Link {
+        // FIXME: panics?
+        let mut links = self.inner.links.lock().unwrap();
+        let link = if self.inner.closed.load(Ordering::SeqCst) {
+            let link = Link::new(peer.clone(), &self.inner);
+            link.close();
+            link
+        } else {
+            links
+                .entry(peer.clone())
+                .or_insert_with(|| Link::new(peer.clone(), &self.inner))
+                .clone()
+        };
+
+        if dial {
+            link.dial();
+        }
+
+        link
+    }
+
+    fn accept(id: NodeId, inner: Weak<NodeInner>, listener: TcpListener) {
+        for stream in listener.incoming() {
+            let node = match inner.upgrade() {
+                Some(inner) if !inner.closed.load(Ordering::SeqCst) => Node { inner },
+                _ => break,
+            };
+
+            match stream {
+                Ok(stream) => {
+                    let spawned = thread::Builder::new()
+                        .name(format!("bastion-node-{}", id))
+                        .spawn(move || node.handshake(stream));
+
+                    if let Err(err) = spawned {
+                        warn!("Node({}): Couldn't handle connection: {}", id, err);
+                    }
+                }
+                Err(err) => warn!("Node({}): Couldn't accept connection: {}", id, err),
+            }
+        }
+
+        info!("Node({}): Stopped listening.", id);
+    }
+
+    fn handshake(self, mut stream: TcpStream) {
+        // Nodes identify themselves when connecting to another one.
+        match read_frame(&mut stream) {
+            Ok(Frame::Hello(peer)) => {
+                debug!("Node({}): Accepted connection from {}.", self.id(), peer);
+                let link = self.link(&peer, false);
+                // The node isn't kept alive by its connections.
+                drop(self);
+                if let Some(id) = link.attach(&stream) {
+                    link.read(id, stream);
+                }
+            }
+            Ok(_) => warn!("Node({}): Connection wasn't identified.", self.id()),
+            Err(err) => warn!("Node({}): Couldn't identify connection: {}", self.id(), err),
+        }
+    }
+}
+
+impl Link {
+    fn new(peer: NodeId, node: &Arc<NodeInner>) -> Self {
+        let inner = Arc::new(LinkInner {
+            peer: peer.clone(),
+            system: node.system.clone(),
+            node: Arc::downgrade(node),
+            codecs: node.codecs.clone(),
+            state: Mutex::new(LinkState::default()),
+            cond: Condvar::new(),
+        });
+
+        let link = Link { inner };
+        let writing = link.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("bastion-link-{}", peer))
+            .spawn(move || writing.write());
+
+        // Nothing could be sent over the link.
+        if let Err(err) = spawned {
+            warn!("Link({}): Couldn't start writing: {}", peer, err);
+            link.close();
+        }
+
+        link
+    }
+
+    pub(crate) fn peer(&self) -> &NodeId {
+        &self.inner.peer
+    }
+
+    pub(crate) fn system(&self) -> &BastionSystem {
+        &self.inner.system
+    }
+
+    /// Encodes the message contained by an envelope and queues
+    /// it to be sent to the element with the given path.
+    ///
+    /// This method returns the envelope if the message couldn't
+    /// be encoded or if the link is closed.
+    pub(crate) fn send(&self, to: &BastionPath, env: Envelope) -> Result<(), Envelope> {
+        let Envelope { msg, sign } = env;
+        let mut msg = match msg {
+            BastionMessage::Message(msg) => msg,
+            msg => return Err(Envelope::new_with_sign(msg, sign)),
+        };
+
+        let encoded = match self.inner.codecs.encode(&msg) {
+            Ok(encoded) => encoded,
+            Err(err) => {
+                warn!("Link({}): Couldn't encode {:?}: {}", self.peer(), msg, err);
+                let msg = BastionMessage::Message(msg);
+                return Err(Envelope::new_with_sign(msg, sign));
+            }
+        };
+
+        let mut state = self.lock();
+        if state.closed {
+            let msg = BastionMessage::Message(msg);
+            return Err(Envelope::new_with_sign(msg, sign));
+        }
+
+        let ask = msg.take_sender().map(|sender| {
+            let id = state.next_request;
+            state.next_request += 1;
+            state.pending.insert(id, sender);
+            id
+        });
+
+        let frame = Frame::Msg {
+            to: BastionPath::clone(to),
+            from: BastionPath::clone(sign.path()),
+            ask,
+            msg: encoded,
+        };
+
+        trace!("Link({}): Queuing message to {}.", self.peer(), to);
+        self.push(&mut state, &frame);
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LinkState> {
+        // FIXME: panics?
+        self.inner.state.lock().unwrap()
+    }
+
+    fn push(&self, state: &mut LinkState, frame: &Frame) {
+        // FIXME: panics? (frames only contain types which can
+        //      always be encoded)
+        state.frames.push_back(Bincode.encode(frame).unwrap());
+        self.inner.cond.notify_all();
+    }
+
+    fn is_connected(&self) -> bool {
+        self.lock().writer.is_some()
+    }
+
+    fn dial(&self) {
+        let mut state = self.lock();
+        if !state.dial {
+            state.dial = true;
+            self.inner.cond.notify_all();
+        }
+    }
+
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        for conn in state.conns.values() {
+            conn.shutdown(Shutdown::Both).ok();
+        }
+
+        state.frames.clear();
+        state.pending.clear();
+        state.deploying.clear();
+        for (_, deployed) in state.deployed.drain() {
+            deployed.stop().ok();
+        }
+
+        self.inner.cond.notify_all();
+    }
+
+    // Registers a connection to the other node, which is used to
+    // write frames if there is no other connection.
+    fn attach(&self, stream: &TcpStream) -> Option<u64> {
+        let conn = stream.try_clone().ok()?;
+        let mut state = self.lock();
+        if state.closed {
+            stream.shutdown(Shutdown::Both).ok();
+            return None;
+        }
+
+        let id = state.next_conn;
+        state.next_conn += 1;
+        state.conns.insert(id, conn);
+        if state.writer.is_none() {
+            info!("Link({}): Connected.", self.peer());
+            state.writer = Some(id);
+            self.inner.cond.notify_all();
+        }
+
+        Some(id)
+    }
+
+    fn disconnect(&self, id: u64) {
+        let mut state = self.lock();
+        if let Some(conn) = state.conns.remove(&id) {
+            conn.shutdown(Shutdown::Both).ok();
+        }
+
+        if state.writer == Some(id) {
+            info!("Link({}): Disconnected.", self.peer());
+            // The messages asked over the connection (or their
+            // answers) might have been lost, and so might the
+            // frames about deployments, which are thus considered
+            // as faulted by both nodes.
+            state.pending.clear();
+            state.deploying.clear();
+            for (_, deployed) in state.deployed.drain() {
+                deployed.stop().ok();
+            }
+
+            state.writer = state.conns.keys().next().cloned();
+            self.inner.cond.notify_all();
+        }
+    }
+
+    fn connect(&self) -> io::Result<()> {
+        let local = match self.inner.node.upgrade() {
+            Some(node) => node.id.clone(),
+            None => return Err(io::ErrorKind::NotConnected.into()),
+        };
+
+        debug!("Link({}): Connecting.", self.peer());
+        let mut stream = TcpStream::connect(self.peer().addr())?;
+        // Connecting to a local port nothing is listening on can
+        // connect the socket to itself.
+        if stream.local_addr()? == stream.peer_addr()? {
+            return Err(io::ErrorKind::ConnectionRefused.into());
+        }
+
+        stream.set_nodelay(true)?;
+        write_frame(&mut stream, &Bincode.encode(&Frame::Hello(local))?)?;
+
+        if let Some(id) = self.attach(&stream) {
+            let link = self.clone();
+            thread::Builder::new()
+                .name(format!("bastion-link-{}", self.peer()))
+                .spawn(move || link.read(id, stream))?;
+        }
+
+        Ok(())
+    }
+
+    // Waits for the given duration, or until the link is closed.
+    fn sleep(&self, duration: Duration) {
+        let deadline = Instant::now() + duration;
+        let mut state = self.lock();
+        while !state.closed {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+
+            // FIXME: panics?
+            state = self
+                .inner
+                .cond
+                .wait_timeout(state, deadline - now)
+                .unwrap()
+                .0;
+        }
+    }
+
+    fn write(self) {
+        let mut backoff = MIN_BACKOFF;
+        // The connection frames were last written to.
+        let mut current: Option<(u64, TcpStream)> = None;
+
+        loop {
+            let mut state = self.lock();
+            loop {
+                if state.closed {
+                    return;
+                }
+
+                match state.writer {
+                    Some(_) if !state.frames.is_empty() => break,
+                    None if state.dial => break,
+                    // FIXME: panics?
+                    _ => state = self.inner.cond.wait(state).unwrap(),
+                }
+            }
+
+            let id = match state.writer {
+                Some(id) => id,
+                None => {
+                    drop(state);
+                    match self.connect() {
+                        Ok(()) => backoff = MIN_BACKOFF,
+                        Err(err) => {
+                            debug!("Link({}): Couldn't connect: {}", self.peer(), err);
+                            self.sleep(backoff);
+                            backoff = (backoff * 2).min(MAX_BACKOFF);
+                        }
+                    }
+
+                    continue;
+                }
+            };
+
+            if current.as_ref().map(|(current, _)| *current) != Some(id) {
+                match state.conns[&id].try_clone() {
+                    Ok(stream) => current = Some((id, stream)),
+                    Err(_) => {
+                        drop(state);
+                        self.disconnect(id);
+                        continue;
+                    }
+                }
+            }
+
+            let mut frames = state.frames.drain(..).collect::<VecDeque<_>>();
+            drop(state);
+
+            // This can't be `None` since it was just set.
+            let (_, stream) = current.as_mut().unwrap();
+            while let Some(frame) = frames.pop_front() {
+                if let Err(err) = write_frame(stream, &frame) {
+                    warn!("Link({}): Couldn't write frame: {}", self.peer(), err);
+                    // The frames that weren't written are written to
+                    // the next connection, in the same order.
+                    frames.push_front(frame);
+                    let mut state = self.lock();
+                    while let Some(frame) = frames.pop_back() {
+                        state.frames.push_front(frame);
+                    }
+
+                    drop(state);
+                    self.disconnect(id);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn read(self, id: u64, mut stream: TcpStream) {
+        loop {
+            match read_frame(&mut stream) {
+                Ok(frame) => self.receive(frame),
+                Err(err) => {
+                    debug!("Link({}): Connection lost: {}", self.peer(), err);
+                    break;
+                }
+            }
+        }
+
+        self.disconnect(id);
+    }
+
+    fn receive(&self, frame: Frame) {
+        match frame {
+            Frame::Hello(_) => (),
+            Frame::Msg { to, from, ask, msg } => self.deliver(to, from, ask, &msg),
+            Frame::Answer { id, answer } => self.answered(id, answer),
+            Frame::Deploy { id, factory } => self.deployed(id, &factory),
+            Frame::Undeploy { id } => self.undeployed(id),
+            Frame::Exited { id, reason } => self.exited(id, reason),
+        }
+    }
+
+    // Deploys a children group on the other node and waits for it
+    // to exit, returning `Ok(())` if it stopped or `Err(())` if it
+    // faulted (or if the connection to the other node was lost).
+    async fn deploy(self, factory: String) -> Result<(), ()> {
+        let (sender, recver) = oneshot::channel();
+        let id = {
+            let mut state = self.lock();
+            if state.closed {
+                return Err(());
+            }
+
+            let id = state.next_request;
+            state.next_request += 1;
+            state.deploying.insert(id, sender);
+
+            debug!("Link({}): Deploying {:?} ({}).", self.peer(), factory, id);
+            self.push(&mut state, &Frame::Deploy { id, factory });
+            id
+        };
+
+        let mut deployment = Deployment {
+            link: self,
+            id,
+            exited: false,
+        };
+
+        match recver.await {
+            Ok(reason) => {
+                debug!(
+                    "Link({}): Deployment {} exited: {:?}",
+                    deployment.link.peer(),
+                    id,
+                    reason
+                );
+                deployment.exited = true;
+                match reason {
+                    ExitReason::Stopped => Ok(()),
+                    _ => Err(()),
+                }
+            }
+            Err(_) => {
+                warn!("Link({}): Lost deployment {}.", deployment.link.peer(), id);
+                Err(())
+            }
+        }
+    }
+
+    // Handles a children group deployment asked by the other node.
+    fn deployed(&self, id: u64, factory: &str) {
+        let node = match self.inner.node.upgrade() {
+            Some(inner) => Node { inner },
+            None => return,
+        };
+
+        debug!("Link({}): Hosting {:?} ({}).", self.peer(), factory, id);
+        let deployed = match node.host(factory) {
+            Ok(deployed) => deployed,
+            Err(()) => {
+                let reason = ExitReason::Faulted;
+                let mut state = self.lock();
+                if !state.closed {
+                    self.push(&mut state, &Frame::Exited { id, reason });
+                }
+
+                return;
+            }
+        };
+
+        let terminated = deployed.terminated();
+        let mut state = self.lock();
+        if state.closed {
+            deployed.stop().ok();
+            return;
+        }
+
+        state.deployed.insert(id, deployed);
+        drop(state);
+
+        let link = self.clone();
+        pool::spawn(
+            async move {
+                let reason = terminated.await;
+                let mut state = link.lock();
+                // The group isn't there anymore if it was stopped
+                // because the other node asked it to.
+                if state.deployed.remove(&id).is_some() && !state.closed {
+                    link.push(&mut state, &Frame::Exited { id, reason });
+                }
+            },
+            ProcStack::default(),
+        );
+    }
+
+    // Stops a children group deployed by the other node.
+    fn undeployed(&self, id: u64) {
+        if let Some(deployed) = self.lock().deployed.remove(&id) {
+            debug!("Link({}): Stopping deployment {}.", self.peer(), id);
+            deployed.stop().ok();
+        }
+    }
+
+    // Handles the exit of a children group deployed on the other
+    // node.
+    fn exited(&self, id: u64, reason: ExitReason) {
+        if let Some(sender) = self.lock().deploying.remove(&id) {
+            sender.send(reason).ok();
+        }
+    }
+
+    fn deliver(&self, to: BastionPath, from: BastionPath, ask: Option<u64>, msg: &[u8]) {
+        let mut msg = match self.inner.codecs.decode(msg) {
+            Ok(msg) => msg,
+            Err(err) => {
+                warn!("Link({}): Couldn't decode message: {}", self.peer(), err);
+                if let Some(id) = ask {
+                    self.answer(id, None);
+                }
+
+                return;
+            }
+        };
+
+        trace!("Link({}): Received message to {}.", self.peer(), to);
+        if let Some(id) = ask {
+            match msg.attach_sender() {
+                Some(answer) => {
+                    let link = self.clone();
+                    pool::spawn(
+                        async move { link.answer(id, answer.await.ok()) },
+                        ProcStack::default(),
+                    );
+                }
+                None => self.answer(id, None),
+            }
+        }
+
+        let sign = self.sign(from);
+        let env = Envelope::new_with_sign(BastionMessage::Message(msg), sign);
+        // NOTE: dropping the message drops its sender, so the
+        //      element which asked it gets an error.
+        match self.system().tree().resolve(&to) {
+            Some(addr) => {
+                if addr.send(env).is_err() {
+                    debug!("Link({}): {} stopped, dropping message.", self.peer(), to);
+                }
+            }
+            None => debug!(
+                "Link({}): Unknown path {}, dropping message.",
+                self.peer(),
+                to
+            ),
+        }
+    }
+
+    // Sends the answer to a message asked by the other node.
+    fn answer(&self, id: u64, answer: Option<SignedMessage>) {
+        let answer = answer.and_then(|answer| {
+            let (msg, sign) = answer.extract();
+            match self.inner.codecs.encode(&msg) {
+                Ok(encoded) => Some((BastionPath::clone(sign.path()), encoded)),
+                Err(err) => {
+                    warn!("Link({}): Couldn't encode answer: {}", self.peer(), err);
+                    None
+                }
+            }
+        });
+
+        let mut state = self.lock();
+        if !state.closed {
+            self.push(&mut state, &Frame::Answer { id, answer });
+        }
+    }
+
+    // Handles the answer to a message asked to the other node.
+    fn answered(&self, id: u64, answer: Option<(BastionPath, Vec<u8>)>) {
+        let sender = match self.lock().pending.remove(&id) {
+            Some(sender) => sender,
+            None => return,
+        };
+
+        // NOTE: dropping the sender makes the element waiting
+        //      for the answer get an error.
+        if let Some((from, msg)) = answer {
+            match self.inner.codecs.decode(&msg) {
+                Ok(msg) => {
+                    let sign = self.sign(from);
+                    sender.send_signed(SignedMessage::new(msg, sign)).ok();
+                }
+                Err(err) => warn!("Link({}): Couldn't decode answer: {}", self.peer(), err),
+            }
+        }
+    }
+
+    // Returns the signature of an element which sent a message
+    // over the link.
+    fn sign(&self, from: BastionPath) -> RefAddr {
+        let node = match self.inner.node.upgrade() {
+            Some(inner) => Node { inner },
+            None => return self.system().dead_letters_addr(),
+        };
+
+        match from.node() {
+            // The element is running on the other node...
+            None => RefAddr::remote(from, self.clone()),
+            // ...on this node (when the other node forwarded a
+            // message received from it)...
+            Some(id) if id == node.id() => self
+                .system()
+                .tree()
+                .resolve(&from)
+                .unwrap_or_else(|| self.system().dead_letters_addr()),
+            // ...or on a third node.
+            Some(id) => {
+                let id = id.clone();
+                node.remote(&id, &from)
+            }
+        }
+    }
+}
+
+fn write_frame(stream: &mut TcpStream, frame: &[u8]) -> io::Result<()> {
+    let mut buf = Vec::with_capacity(4 + frame.len());
+    buf.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+    buf.extend_from_slice(frame);
+    stream.write_all(&buf)
+}
+
+fn read_frame(stream: &mut TcpStream) -> io::Result<Frame> {
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        let err = format!("Frame too large: {} bytes", len);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+    }
+
+    let mut frame = vec![0; len];
+    stream.read_exact(&mut frame)?;
+    Bincode.decode(&frame)
+}
+
+impl FromStr for NodeId {
+    type Err = AddrParseError;
+
+    fn from_str(addr: &str) -> Result<Self, Self::Err> {
+        Ok(NodeId(addr.parse()?))
+    }
+}
+
+impl Display for NodeId {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.0)
+    }
+}
+
+impl Debug for NodeId {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(fmt, "NodeId({})", self.0)
+    }
+}
+
+impl NodeInner {
+    fn shutdown(&self) {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        info!("Node({}): Shutting down.", self.id);
+        // FIXME: panics?
+        let links = std::mem::take(&mut *self.links.lock().unwrap());
+        for link in links.values() {
+            link.close();
+        }
+
+        // FIXME: panics?
+        if let Some(deployed) = self.deployed.lock().unwrap().take() {
+            deployed.stop().ok();
+        }
+
+        // Wakes the listener up so that it notices it should stop.
+        TcpStream::connect(self.id.addr()).ok();
+    }
+}
+
+impl Drop for NodeInner {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl Debug for Node {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Node")
+            .field("id", &self.inner.id)
+            .field("closed", &self.inner.closed.load(Ordering::SeqCst))
+            .finish()
+    }
+}
+
+impl Drop for Deployment {
+    fn drop(&mut self) {
+        if self.exited {
+            return;
+        }
+
+        let mut state = self.link.lock();
+        state.deploying.remove(&self.id);
+        if !state.closed {
+            let id = self.id;
+            self.link.push(&mut state, &Frame::Undeploy { id });
+        }
+    }
+}
+
+impl Debug for Link {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("Link")
+            .field("peer", &self.inner.peer)
+            .finish()
+    }
+}
@@ -0,0 +1,129 @@
+//!
+//! A tree keeps track of the running elements of the
+//! supervision tree, allowing paths to be resolved to
+//! addresses.
+use crate::envelope::RefAddr;
+use crate::path::{BastionPath, BastionPathElement, Segment};
+use fxhash::{FxHashMap, FxHashSet};
+use std::sync::RwLock;
+
+#[derive(Debug, Default)]
+pub(crate) struct Tree {
+    inner: RwLock<TreeInner>,
+}
+
+#[derive(Debug, Default)]
+struct TreeInner {
+    // The address of each running element.
+    // NOTE: elements are used instead of identifiers because
+    //      the system's supervisor and dead letters children
+    //      group share the same one.
+    addrs: FxHashMap<BastionPathElement, RefAddr>,
+    // The elements contained by each element (or by the
+    // system, using `None`).
+    children: FxHashMap<Option<BastionPathElement>, FxHashSet<BastionPathElement>>,
+}
+
+impl Tree {
+    pub(crate) fn new() -> Self {
+        Tree::default()
+    }
+
+    pub(crate) fn register(&self, addr: RefAddr) {
+        let path = addr.path();
+        // FIXME: panics?
+        let elem = path.elem().clone().unwrap();
+        let parent = path.parent();
+        trace!("Tree: Registering {:?}.", path);
+
+        // FIXME: panics?
+        let mut inner = self.inner.write().unwrap();
+        inner
+            .children
+            .entry(parent)
+            .or_default()
+            .insert(elem.clone());
+        inner.addrs.insert(elem, addr);
+    }
+
+    pub(crate) fn unregister(&self, path: &BastionPath) {
+        let elem = match path.elem() {
+            Some(elem) => elem,
+            None => return,
+        };
+        trace!("Tree: Unregistering {:?}.", path);
+
+        // FIXME: panics?
+        let mut inner = self.inner.write().unwrap();
+        inner.addrs.remove(elem);
+        // NOTE: the elements it contained are unregistered
+        //      when they stop, are killed or fault.
+        inner.children.remove(&Some(elem.clone()));
+
+        let parent = path.parent();
+        if let Some(siblings) = inner.children.get_mut(&parent) {
+            siblings.remove(elem);
+            if siblings.is_empty() {
+                inner.children.remove(&parent);
+            }
+        }
+    }
+
+    pub(crate) fn resolve(&self, path: &BastionPath) -> Option<RefAddr> {
+        let pattern = path
+            .iter()
+            .zip(path.names())
+            .map(|(id, name)| match name {
+                // Names stay the same across restarts.
+                Some(name) => Segment::Name(name.to_string()),
+                None => Segment::Id(id.clone()),
+            })
+            .collect::<Vec<_>>();
+        let selected = self.select(&pattern);
+
+        // Parsed paths might not contain the right kind of
+        // element (see `BastionPath::from_str`).
+        selected
+            .iter()
+            .find(|addr| addr.path().elem() == path.elem())
+            .or_else(|| selected.first())
+            .cloned()
+    }
+
+    pub(crate) fn select(&self, pattern: &[Segment]) -> Vec<RefAddr> {
+        if pattern.is_empty() {
+            return vec![];
+        }
+
+        // FIXME: panics?
+        let inner = self.inner.read().unwrap();
+
+        let mut selected = vec![None];
+        for segment in pattern {
+            selected = selected
+                .iter()
+                .filter_map(|parent| inner.children.get(parent))
+                .flat_map(|children| children.iter())
+                .filter(|elem| match segment {
+                    Segment::Any => true,
+                    Segment::Id(id) => elem.id() == id,
+                    Segment::Name(name) => inner
+                        .addrs
+                        .get(elem)
+                        .and_then(|addr| addr.path().name())
+                        .map(|elem| elem == name)
+                        .unwrap_or(false),
+                })
+                .cloned()
+                .map(Some)
+                .collect();
+        }
+
+        selected
+            .into_iter()
+            .filter_map(|elem| inner.addrs.get(&elem?))
+            .filter(|addr| !addr.sender().is_closed())
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,592 @@
+//!
+//! A typed layer over children groups and the references to them,
+//! allowing to check at compile-time that the messages sent to a
+//! children group are of the only type (its "protocol") it handles.
+//!
+//! A typed children group is created using
+//! [`Bastion::typed_children`] or [`SupervisorRef::typed_children`]
+//! and messages are still sent using the same transport as for
+//! untyped children groups.
+//!
+//! [`Bastion::typed_children`]: ../struct.Bastion.html#method.typed_children
+//! [`SupervisorRef::typed_children`]: ../supervisor/struct.SupervisorRef.html#method.typed_children
+use crate::actor::Request;
+use crate::callbacks::Callbacks;
+use crate::child_ref::ChildRef;
+use crate::children::Children;
+use crate::children_ref::ChildrenRef;
+use crate::context::{BastionContext, BastionId, State};
+use crate::message::{Answer, Message};
+use crate::monitor::Terminated;
+use std::fmt::{self, Debug, Formatter};
+use std::future::Future;
+use std::marker::PhantomData;
+
+#[derive(Debug)]
+/// A children group whose elements only handle messages of
+/// type `P` (eg. an enum of all the messages they accept).
+///
+/// This wraps a [`Children`], of which it exposes the methods
+/// that don't depend on the type of the messages its elements
+/// receive.
+///
+/// # Example
+///
+/// ```rust
+/// # use bastion::prelude::*;
+/// #
+/// #[derive(Debug)]
+/// enum Counter {
+///     Add(u64),
+///     Get,
+/// }
+///
+/// # fn main() {
+///     # Bastion::init();
+///     #
+/// let children_ref: TypedChildrenRef<Counter> = Bastion::typed_children(|children| {
+///     children.with_exec(|ctx: TypedContext<Counter>| {
+///         async move {
+///             let mut count = 0;
+///             loop {
+///                 match ctx.recv().await? {
+///                     (Counter::Add(added), _) => count += added,
+///                     (Counter::Get, req) => {
+///                         req.answer(count).ok();
+///                     }
+///                 }
+///             }
+///         }
+///     })
+/// }).expect("Couldn't create the children group.");
+///
+/// let child_ref = &children_ref.elems()[0];
+/// child_ref.tell_anonymously(Counter::Add(1)).expect("Couldn't send the message.");
+/// // This wouldn't compile...
+/// // child_ref.tell_anonymously("A message.");
+///     #
+///     # Bastion::start();
+///     # Bastion::stop();
+///     # Bastion::block_until_stopped();
+/// # }
+/// ```
+///
+/// [`Children`]: ../children/struct.Children.html
+pub struct TypedChildren<P: Message> {
+    children: Children,
+    _protocol: PhantomData<fn(P)>,
+}
+
+/// The context of an element of a [`TypedChildren`], which only
+/// receives messages of type `P`.
+///
+/// [`TypedChildren`]: struct.TypedChildren.html
+pub struct TypedContext<P: Message> {
+    ctx: BastionContext,
+    _protocol: PhantomData<fn(P)>,
+}
+
+/// A "reference" to an element of a [`TypedChildren`], only
+/// allowing to send it messages of type `P`.
+///
+/// [`TypedChildren`]: struct.TypedChildren.html
+pub struct TypedChildRef<P: Message> {
+    child: ChildRef,
+    _protocol: PhantomData<fn(P)>,
+}
+
+/// A "reference" to a [`TypedChildren`], only allowing to send
+/// messages of type `P` to its elements.
+///
+/// [`TypedChildren`]: struct.TypedChildren.html
+pub struct TypedChildrenRef<P: Message> {
+    children: ChildrenRef,
+    elems: Vec<TypedChildRef<P>>,
+}
+
+impl<P: Message> TypedChildren<P> {
+    pub(crate) fn new(children: Children) -> Self {
+        TypedChildren {
+            children,
+            _protocol: PhantomData,
+        }
+    }
+
+    pub(crate) fn into_untyped(self) -> Children {
+        self.children
+    }
+
+    /// Returns the identifier of the children group.
+    ///
+    /// See [`Children::id`].
+    ///
+    /// [`Children::id`]: ../children/struct.Children.html#method.id
+    pub fn id(&self) -> &BastionId {
+        self.children.id()
+    }
+
+    /// Sets the closure taking a [`TypedContext`] and returning a
+    /// [`Future`] that will be used by every element of this
+    /// children group.
+    ///
+    /// See [`Children::with_exec`].
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The closure taking a [`TypedContext`] and
+    ///   returning a [`Future`] that will be used by every element
+    ///   of this children group.
+    ///
+    /// [`TypedContext`]: struct.TypedContext.html
+    /// [`Future`]: https://doc.rust-lang.org/std/future/trait.Future.html
+    /// [`Children::with_exec`]: ../children/struct.Children.html#method.with_exec
+    pub fn with_exec<I, F>(mut self, init: I) -> Self
+    where
+        I: Fn(TypedContext<P>) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        self.children = self
+            .children
+            .with_exec(move |ctx: BastionContext| init(TypedContext::new(ctx)));
+        self
+    }
+
+    /// Sets the closure returning the initial state of this
+    /// children group's elements, and the closure taking a
+    /// [`TypedContext`] and the [`State`] of an element of this
+    /// children group, and returning the future that the element
+    /// will run.
+    ///
+    /// See [`Children::with_state`].
+    ///
+    /// [`TypedContext`]: struct.TypedContext.html
+    /// [`State`]: ../context/struct.State.html
+    /// [`Children::with_state`]: ../children/struct.Children.html#method.with_state
+    pub fn with_state<S, I, E, F>(mut self, init: I, exec: E) -> Self
+    where
+        S: Clone + Send + 'static,
+        I: Fn() -> S + Send + Sync + 'static,
+        E: Fn(TypedContext<P>, State<S>) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), ()>> + Send + 'static,
+    {
+        self.children = self
+            .children
+            .with_state(init, move |ctx: BastionContext, state: State<S>| {
+                exec(TypedContext::new(ctx), state)
+            });
+        self
+    }
+
+    /// Sets the name of this children group.
+    ///
+    /// See [`Children::with_name`].
+    ///
+    /// [`Children::with_name`]: ../children/struct.Children.html#method.with_name
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.children = self.children.with_name(name);
+        self
+    }
+
+    /// Sets the number of elements this children group will
+    /// contain.
+    ///
+    /// See [`Children::with_redundancy`].
+    ///
+    /// [`Children::with_redundancy`]: ../children/struct.Children.html#method.with_redundancy
+    pub fn with_redundancy(mut self, redundancy: usize) -> Self {
+        self.children = self.children.with_redundancy(redundancy);
+        self
+    }
+
+    /// Sets the maximum number of messages each element of this
+    /// children group can stash at the same time.
+    ///
+    /// See [`Children::with_stash_capacity`].
+    ///
+    /// [`Children::with_stash_capacity`]: ../children/struct.Children.html#method.with_stash_capacity
+    pub fn with_stash_capacity(mut self, capacity: usize) -> Self {
+        self.children = self.children.with_stash_capacity(capacity);
+        self
+    }
+
+    /// Sets the callbacks that will get called at this children
+    /// group's different lifecycle events.
+    ///
+    /// See [`Children::with_callbacks`].
+    ///
+    /// [`Children::with_callbacks`]: ../children/struct.Children.html#method.with_callbacks
+    pub fn with_callbacks(mut self, callbacks: Callbacks) -> Self {
+        self.children = self.children.with_callbacks(callbacks);
+        self
+    }
+}
+
+impl<P: Message> TypedContext<P> {
+    fn new(ctx: BastionContext) -> Self {
+        TypedContext {
+            ctx,
+            _protocol: PhantomData,
+        }
+    }
+
+    /// Returns the untyped [`BastionContext`] this context wraps,
+    /// allowing to use the rest of its methods (eg. to send
+    /// messages to other elements).
+    ///
+    /// [`BastionContext`]: ../context/struct.BastionContext.html
+    pub fn context(&self) -> &BastionContext {
+        &self.ctx
+    }
+
+    /// Returns a [`TypedChildRef`] referencing the element this
+    /// context is linked to.
+    ///
+    /// [`TypedChildRef`]: struct.TypedChildRef.html
+    pub fn current(&self) -> TypedChildRef<P> {
+        TypedChildRef::new(self.ctx.current().clone())
+    }
+
+    /// Waits for a message of type `P` to be received by the
+    /// element this context is linked to, returning it along with
+    /// its sender and the way to answer it if it was "asked".
+    ///
+    /// Messages of other types (or that were broadcasted using an
+    /// untyped [`ChildrenRef`]) are dropped.
+    ///
+    /// This method returns the message if it succeeded, or
+    /// `Err(())` otherwise.
+    ///
+    /// See [`BastionContext::recv`].
+    ///
+    /// [`ChildrenRef`]: ../children_ref/struct.ChildrenRef.html
+    /// [`BastionContext::recv`]: ../context/struct.BastionContext.html#method.recv
+    pub async fn recv(&self) -> Result<(P, Request), ()> {
+        loop {
+            let (mut msg, sign) = self.ctx.recv().await?.extract();
+            let sender = msg.take_sender();
+            match msg.downcast::<P>() {
+                Ok(msg) => {
+                    let req = Request::new(sign, sender, self.ctx.signature());
+                    return Ok((msg, req));
+                }
+                Err(msg) => warn!(
+                    "TypedContext({}): Dropping a message of another type: {:?}",
+                    self.ctx.current().id(),
+                    msg
+                ),
+            }
+        }
+    }
+}
+
+impl<P: Message> TypedChildRef<P> {
+    fn new(child: ChildRef) -> Self {
+        TypedChildRef {
+            child,
+            _protocol: PhantomData,
+        }
+    }
+
+    /// Returns the untyped [`ChildRef`] this reference wraps.
+    ///
+    /// [`ChildRef`]: ../child_ref/struct.ChildRef.html
+    pub fn untyped(&self) -> &ChildRef {
+        &self.child
+    }
+
+    /// Returns the identifier of the element this reference is
+    /// referencing.
+    ///
+    /// See [`ChildRef::id`].
+    ///
+    /// [`ChildRef::id`]: ../child_ref/struct.ChildRef.html#method.id
+    pub fn id(&self) -> &BastionId {
+        self.child.id()
+    }
+
+    /// Sends a message to the element this reference is
+    /// referencing, signed with the signature of the element
+    /// `ctx` is linked to.
+    ///
+    /// This method returns `()` if it succeeded, or `Err(msg)`
+    /// otherwise.
+    ///
+    /// See [`BastionContext::tell`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of the sender.
+    /// * `msg` - The message to send.
+    ///
+    /// [`BastionContext::tell`]: ../context/struct.BastionContext.html#method.tell
+    pub fn tell(&self, ctx: &BastionContext, msg: P) -> Result<(), P> {
+        ctx.tell(&self.child.addr(), msg)
+    }
+
+    /// Sends a message to the element this reference is
+    /// referencing, signed with the signature of the element
+    /// `ctx` is linked to, allowing it to answer.
+    ///
+    /// This method returns [`Answer`] if it succeeded, or
+    /// `Err(msg)` otherwise.
+    ///
+    /// See [`BastionContext::ask`].
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of the sender.
+    /// * `msg` - The message to send.
+    ///
+    /// [`Answer`]: ../message/struct.Answer.html
+    /// [`BastionContext::ask`]: ../context/struct.BastionContext.html#method.ask
+    pub fn ask(&self, ctx: &BastionContext, msg: P) -> Result<Answer, P> {
+        ctx.ask(&self.child.addr(), msg)
+    }
+
+    /// Sends a message to the element this reference is
+    /// referencing.
+    ///
+    /// See [`ChildRef::tell_anonymously`].
+    ///
+    /// [`ChildRef::tell_anonymously`]: ../child_ref/struct.ChildRef.html#method.tell_anonymously
+    pub fn tell_anonymously(&self, msg: P) -> Result<(), P> {
+        self.child.tell_anonymously(msg)
+    }
+
+    /// Sends a message to the element this reference is
+    /// referencing, allowing it to answer.
+    ///
+    /// See [`ChildRef::ask_anonymously`].
+    ///
+    /// [`ChildRef::ask_anonymously`]: ../child_ref/struct.ChildRef.html#method.ask_anonymously
+    pub fn ask_anonymously(&self, msg: P) -> Result<Answer, P> {
+        self.child.ask_anonymously(msg)
+    }
+
+    /// Tells the element this reference is referencing to stop
+    /// its execution.
+    ///
+    /// See [`ChildRef::stop`].
+    ///
+    /// [`ChildRef::stop`]: ../child_ref/struct.ChildRef.html#method.stop
+    pub fn stop(&self) -> Result<(), ()> {
+        self.child.stop()
+    }
+
+    /// Tells the element this reference is referencing to
+    /// suicide.
+    ///
+    /// See [`ChildRef::kill`].
+    ///
+    /// [`ChildRef::kill`]: ../child_ref/struct.ChildRef.html#method.kill
+    pub fn kill(&self) -> Result<(), ()> {
+        self.child.kill()
+    }
+
+    /// Returns whether the element this reference is referencing
+    /// is still running.
+    ///
+    /// See [`ChildRef::is_alive`].
+    ///
+    /// [`ChildRef::is_alive`]: ../child_ref/struct.ChildRef.html#method.is_alive
+    pub fn is_alive(&self) -> bool {
+        self.child.is_alive()
+    }
+
+    /// Returns a future which resolves once the element this
+    /// reference is referencing terminated.
+    ///
+    /// See [`ChildRef::terminated`].
+    ///
+    /// [`ChildRef::terminated`]: ../child_ref/struct.ChildRef.html#method.terminated
+    pub fn terminated(&self) -> Terminated {
+        self.child.terminated()
+    }
+}
+
+impl<P: Message> TypedChildrenRef<P> {
+    pub(crate) fn new(children: ChildrenRef) -> Self {
+        let elems = children
+            .elems()
+            .iter()
+            .cloned()
+            .map(TypedChildRef::new)
+            .collect();
+
+        TypedChildrenRef { children, elems }
+    }
+
+    /// Returns the untyped [`ChildrenRef`] this reference wraps.
+    ///
+    /// [`ChildrenRef`]: ../children_ref/struct.ChildrenRef.html
+    pub fn untyped(&self) -> &ChildrenRef {
+        &self.children
+    }
+
+    /// Returns the identifier of the children group this
+    /// reference is referencing.
+    ///
+    /// See [`ChildrenRef::id`].
+    ///
+    /// [`ChildrenRef::id`]: ../children_ref/struct.ChildrenRef.html#method.id
+    pub fn id(&self) -> &BastionId {
+        self.children.id()
+    }
+
+    /// Returns a list of [`TypedChildRef`] referencing the
+    /// elements of the children group this reference is
+    /// referencing.
+    ///
+    /// See [`ChildrenRef::elems`].
+    ///
+    /// [`TypedChildRef`]: struct.TypedChildRef.html
+    /// [`ChildrenRef::elems`]: ../children_ref/struct.ChildrenRef.html#method.elems
+    pub fn elems(&self) -> &[TypedChildRef<P>] {
+        &self.elems
+    }
+
+    /// Sends a copy of a message to every element of the children
+    /// group this reference is referencing.
+    ///
+    /// Unlike [`ChildrenRef::broadcast`], the message is "told"
+    /// to each element so that it can be received as a `P` by
+    /// [`TypedContext::recv`].
+    ///
+    /// Because the message is sent to each element in turn, the
+    /// elements which already stopped (and thus can't receive it)
+    /// are skipped and the others still receive it.
+    ///
+    /// This method returns `()` if at least one element received
+    /// the message (or if the group has no elements), or
+    /// `Err(msg)` otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The message to send.
+    ///
+    /// [`ChildrenRef::broadcast`]: ../children_ref/struct.ChildrenRef.html#method.broadcast
+    /// [`TypedContext::recv`]: struct.TypedContext.html#method.recv
+    pub fn broadcast(&self, msg: P) -> Result<(), P>
+    where
+        P: Clone,
+    {
+        debug!(
+            "TypedChildrenRef({}): Broadcasting message: {:?}",
+            self.id(),
+            msg
+        );
+        let mut delivered = self.elems().is_empty();
+        for elem in self.elems() {
+            if elem.tell_anonymously(msg.clone()).is_ok() {
+                delivered = true;
+            } else {
+                debug!(
+                    "TypedChildrenRef({}): Skipping stopped element: {}",
+                    self.id(),
+                    elem.id()
+                );
+            }
+        }
+
+        if delivered {
+            Ok(())
+        } else {
+            Err(msg)
+        }
+    }
+
+    /// Tells the children group this reference is referencing to
+    /// stop all of its running elements.
+    ///
+    /// See [`ChildrenRef::stop`].
+    ///
+    /// [`ChildrenRef::stop`]: ../children_ref/struct.ChildrenRef.html#method.stop
+    pub fn stop(&self) -> Result<(), ()> {
+        self.children.stop()
+    }
+
+    /// Tells the children group this reference is referencing to
+    /// kill all of its running elements.
+    ///
+    /// See [`ChildrenRef::kill`].
+    ///
+    /// [`ChildrenRef::kill`]: ../children_ref/struct.ChildrenRef.html#method.kill
+    pub fn kill(&self) -> Result<(), ()> {
+        self.children.kill()
+    }
+
+    /// Returns whether the children group this reference is
+    /// referencing is still running.
+    ///
+    /// See [`ChildrenRef::is_alive`].
+    ///
+    /// [`ChildrenRef::is_alive`]: ../children_ref/struct.ChildrenRef.html#method.is_alive
+    pub fn is_alive(&self) -> bool {
+        self.children.is_alive()
+    }
+
+    /// Returns a future which resolves once the children group
+    /// this reference is referencing terminated.
+    ///
+    /// See [`ChildrenRef::terminated`].
+    ///
+    /// [`ChildrenRef::terminated`]: ../children_ref/struct.ChildrenRef.html#method.terminated
+    pub fn terminated(&self) -> Terminated {
+        self.children.terminated()
+    }
+}
+
+impl<P: Message> Clone for TypedChildRef<P> {
+    fn clone(&self) -> Self {
+        TypedChildRef::new(self.child.clone())
+    }
+}
+
+impl<P: Message> Clone for TypedChildrenRef<P> {
+    fn clone(&self) -> Self {
+        TypedChildrenRef {
+            children: self.children.clone(),
+            elems: self.elems.clone(),
+        }
+    }
+}
+
+impl<P: Message> PartialEq for TypedChildRef<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.child == other.child
+    }
+}
+
+impl<P: Message> Eq for TypedChildRef<P> {}
+
+impl<P: Message> PartialEq for TypedChildrenRef<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.children == other.children
+    }
+}
+
+impl<P: Message> Eq for TypedChildrenRef<P> {}
+
+impl<P: Message> Debug for TypedContext<P> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("TypedContext")
+            .field("ctx", &self.ctx)
+            .finish()
+    }
+}
+
+impl<P: Message> Debug for TypedChildRef<P> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("TypedChildRef")
+            .field("child", &self.child)
+            .finish()
+    }
+}
+
+impl<P: Message> Debug for TypedChildrenRef<P> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        fmt.debug_struct("TypedChildrenRef")
+            .field("children", &self.children)
+            .finish()
+    }
+}
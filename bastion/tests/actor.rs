@@ -0,0 +1,129 @@
+use bastion::prelude::*;
+use futures::executor::block_on;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Started(u64),
+    Unhandled,
+    Broadcasted(String),
+    Stopped(u64),
+}
+
+struct Counter {
+    count: u64,
+    events: Arc<Mutex<Sender<Event>>>,
+}
+
+impl Counter {
+    fn send(&self, event: Event) {
+        self.events.lock().unwrap().send(event).unwrap();
+    }
+}
+
+impl Actor for Counter {
+    fn handlers(handlers: Handlers<Self>) -> Handlers<Self> {
+        handlers
+            .handle::<u64>()
+            .handle::<&'static str>()
+            .handle_broadcast::<String>()
+    }
+
+    fn started(&mut self, _: &BastionContext) -> Result<(), ()> {
+        self.send(Event::Started(self.count));
+        Ok(())
+    }
+
+    fn stopped(&mut self, _: &BastionContext) {
+        self.send(Event::Stopped(self.count));
+    }
+
+    fn unhandled(&mut self, _: &BastionContext, _: SignedMessage) -> Result<(), ()> {
+        self.send(Event::Unhandled);
+        Ok(())
+    }
+}
+
+impl Handler<u64> for Counter {
+    fn handle(&mut self, _: &BastionContext, added: u64, _: Request) -> Result<(), ()> {
+        self.count += added;
+        Ok(())
+    }
+}
+
+impl Handler<&'static str> for Counter {
+    fn handle(&mut self, _: &BastionContext, msg: &'static str, req: Request) -> Result<(), ()> {
+        match msg {
+            "count" => req.answer(self.count).map_err(|_| ()),
+            "fault" => Err(()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Handler<Arc<String>> for Counter {
+    fn handle(&mut self, _: &BastionContext, msg: Arc<String>, req: Request) -> Result<(), ()> {
+        assert!(!req.is_ask());
+        self.send(Event::Broadcasted(msg.to_string()));
+        Ok(())
+    }
+}
+
+#[test]
+fn actors_dispatch_messages() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let events = Arc::new(Mutex::new(sender));
+
+    let children_ref = Bastion::children(|children| {
+        children.with_actor(move || Counter {
+            count: 0,
+            events: events.clone(),
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let recv = || recver.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(recv(), Event::Started(0));
+
+    let child_ref = children_ref.stable().elems()[0].clone();
+    child_ref.tell_anonymously(2u64).unwrap();
+    child_ref.tell_anonymously(3u64).unwrap();
+
+    // Asked messages can be answered...
+    let answer = child_ref.ask_anonymously("count").unwrap();
+    msg! { block_on(answer).unwrap(),
+        count: u64 => assert_eq!(count, 5);
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    // ...broadcasted ones are handled separately...
+    children_ref.broadcast("hello".to_string()).unwrap();
+    assert_eq!(recv(), Event::Broadcasted("hello".to_string()));
+
+    // ...and the others go to the `unhandled` hook.
+    child_ref.tell_anonymously("hello".to_string()).unwrap();
+    child_ref.tell_anonymously(1u32).unwrap();
+    assert_eq!(recv(), Event::Unhandled);
+    assert_eq!(recv(), Event::Unhandled);
+
+    // A handler returning an error makes the element fault and
+    // restart with a new actor.
+    child_ref.tell_anonymously("fault").unwrap();
+    assert_eq!(recv(), Event::Stopped(5));
+    assert_eq!(recv(), Event::Started(0));
+
+    child_ref.tell_anonymously(1u64).unwrap();
+    // Waits for the message to be handled before stopping.
+    let answer = child_ref.ask_anonymously("count").unwrap();
+    block_on(answer).unwrap();
+    child_ref.stop().unwrap();
+    assert_eq!(recv(), Event::Stopped(1));
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
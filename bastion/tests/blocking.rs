@@ -0,0 +1,120 @@
+use bastion::prelude::*;
+use bastion_executor::blocking;
+use lightproc::proc_stack::ProcStack;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Ping;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    // The element started (for the given run) on the given thread.
+    Started(usize, Option<String>),
+    Pinged(usize),
+    Told,
+}
+
+#[test]
+fn blocking_children() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+    let runs = Arc::new(AtomicUsize::new(0));
+    let events = sender.clone();
+    let blocking = Bastion::children(|children| {
+        children
+            .with_blocking()
+            .with_exec(move |ctx: BastionContext| {
+                let sender = events.clone();
+                let runs = runs.clone();
+                async move {
+                    let run = runs.fetch_add(1, Ordering::SeqCst);
+                    let name = thread::current().name().map(str::to_string);
+                    sender
+                        .lock()
+                        .unwrap()
+                        .send(Event::Started(run, name))
+                        .unwrap();
+
+                    msg! { ctx.recv().await?,
+                        _: Ping => {
+                            sender.lock().unwrap().send(Event::Pinged(run)).unwrap();
+                            // Blocks the current thread.
+                            thread::sleep(Duration::from_millis(100));
+                        };
+                        _: _ => ();
+                    }
+
+                    // The element is restarted once by its supervisor.
+                    if run == 0 {
+                        Err(())
+                    } else {
+                        Ok(())
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    let timeout = Duration::from_secs(1);
+    match recver.recv_timeout(timeout).expect("Element didn't start.") {
+        Event::Started(0, Some(name)) => assert!(name.starts_with("bastion-blocking-driver")),
+        event => panic!("Unexpected event: {:?}", event),
+    }
+
+    // Keeps every thread of the blocking pool busy (spawning a
+    // process waits for a thread to be available), with enough
+    // processes waiting to outpace the threads the pool adds.
+    let busy = Arc::new(AtomicBool::new(true));
+    for _ in 0..32 {
+        let filling = busy.clone();
+        thread::spawn(move || {
+            while filling.load(Ordering::SeqCst) {
+                let sleep = async { thread::sleep(Duration::from_secs(5)) };
+                blocking::spawn_blocking(sleep, ProcStack::default());
+            }
+        });
+    }
+    thread::sleep(Duration::from_millis(500));
+
+    // The element still receives the messages sent by another
+    // element, without stalling it.
+    let elem = blocking.elems()[0].clone();
+    Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let sender = sender.clone();
+            let elem = elem.clone();
+            async move {
+                ctx.tell(&elem.addr(), Ping).unwrap();
+                sender.lock().unwrap().send(Event::Told).unwrap();
+                Ok(())
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let mut received = vec![
+        recver.recv_timeout(timeout).expect("Element was stalled."),
+        recver.recv_timeout(timeout).expect("Element was stalled."),
+    ];
+    received.sort_by_key(|event| *event == Event::Told);
+    assert_eq!(received, vec![Event::Pinged(0), Event::Told]);
+
+    match recver
+        .recv_timeout(timeout)
+        .expect("Element wasn't restarted.")
+    {
+        Event::Started(1, Some(name)) => assert!(name.starts_with("bastion-blocking-driver")),
+        event => panic!("Unexpected event: {:?}", event),
+    }
+
+    busy.store(false, Ordering::SeqCst);
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
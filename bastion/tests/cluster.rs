@@ -0,0 +1,113 @@
+use bastion::cluster::{Cluster, ClusterRef, MemberDown, MemberUp};
+use bastion::codec::Codecs;
+use bastion::prelude::*;
+use bastion::transport::{Node, NodeId};
+use futures::executor::block_on;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+mod common;
+
+use common::wait_for;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Up(NodeId),
+    Down(NodeId),
+}
+
+fn join(seeds: &[NodeId]) -> (Node, ClusterRef) {
+    let node = Node::listen("127.0.0.1:0", Codecs::new()).expect("Couldn't listen.");
+    let cluster = Cluster::new(&node)
+        .with_seeds(seeds)
+        .with_gossip_interval(Duration::from_millis(50))
+        .with_failure_timeout(Duration::from_millis(1000))
+        .join()
+        .expect("Couldn't join the cluster.");
+
+    (node, cluster)
+}
+
+#[test]
+fn members_join_and_leave() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let subscriber = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let sender = sender.clone();
+            async move {
+                ctx.subscribe("cluster.member.*").unwrap();
+                loop {
+                    msg! { ctx.recv().await?,
+                        ref up: MemberUp => {
+                            let event = Event::Up(up.node().clone());
+                            sender.lock().unwrap().send(event).unwrap();
+                        };
+                        ref down: MemberDown => {
+                            let event = Event::Down(down.node().clone());
+                            sender.lock().unwrap().send(event).unwrap();
+                        };
+                        _: &'static str =!> answer!(ctx, "subscribed").unwrap();
+                        _: _ => ();
+                    }
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    // Wait for the subscription before joining.
+    let answer = subscriber.elems()[0].ask_anonymously("ready?").unwrap();
+    block_on(answer).unwrap();
+
+    // Nodes join through a seed...
+    let (first_node, first) = join(&[]);
+    let seeds = [first.node().clone()];
+    let (second_node, second) = join(&seeds);
+    let (third_node, third) = join(&seeds);
+
+    // ...and learn about each other through gossip.
+    let mut all = vec![
+        first.node().clone(),
+        second.node().clone(),
+        third.node().clone(),
+    ];
+    all.sort_by_key(|node| node.addr());
+    for cluster in &[&first, &second, &third] {
+        wait_for(|| cluster.members() == all);
+    }
+
+    let mut ups = (0..6)
+        .map(|_| recver.recv_timeout(Duration::from_secs(5)).unwrap())
+        .collect::<Vec<_>>();
+    for node in &all {
+        ups.retain(|event| *event != Event::Up(node.clone()));
+    }
+    assert!(ups.is_empty());
+
+    // A node which leaves (or becomes unreachable)...
+    third.leave().unwrap();
+    third_node.shutdown();
+
+    // ...is detected as down by the others.
+    let left = third.node().clone();
+    all.retain(|node| *node != left);
+    wait_for(|| first.members() == all && second.members() == all);
+    assert!(!first.is_up(&left));
+
+    let down = recver.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(down, Event::Down(left));
+
+    first.leave().unwrap();
+    second.leave().unwrap();
+    first_node.shutdown();
+    second_node.shutdown();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
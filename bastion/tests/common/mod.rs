@@ -0,0 +1,16 @@
+use std::thread;
+use std::time::Duration;
+
+// Waits until the given condition is met, panicking if it isn't
+// after ten seconds.
+pub fn wait_for<F: Fn() -> bool>(cond: F) {
+    for _ in 0..1000 {
+        if cond() {
+            return;
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    panic!("Condition wasn't met in time.");
+}
@@ -0,0 +1,57 @@
+use bastion::prelude::*;
+use bastion_executor::{deterministic, timer};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn deterministic_executor() {
+    Bastion::init_with(Config::new().with_deterministic_seed(42));
+    assert_eq!(deterministic::seed(), Some(42));
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+    let runs = Arc::new(AtomicUsize::new(0));
+    Bastion::children(|children| {
+        children.with_exec(move |_: BastionContext| {
+            let sender = sender.clone();
+            let runs = runs.clone();
+            async move {
+                let run = runs.fetch_add(1, Ordering::SeqCst);
+                let name = thread::current().name().map(str::to_string);
+
+                // Waiting for an hour only advances the virtual time.
+                timer::sleep(Duration::from_secs(3600)).await;
+                sender
+                    .lock()
+                    .unwrap()
+                    .send((run, name, timer::now()))
+                    .unwrap();
+
+                // The element is restarted once by its supervisor.
+                if run == 0 {
+                    Err(())
+                } else {
+                    Ok(())
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let started = Instant::now();
+    let timeout = Duration::from_secs(5);
+    for expected in 0..2 {
+        let (run, name, now) = recver.recv_timeout(timeout).expect("Element didn't run.");
+        assert_eq!(run, expected);
+        assert_eq!(name.as_deref(), Some("bastion-deterministic-thread"));
+        assert!(now >= Duration::from_secs(3600 * (run as u64 + 1)));
+    }
+    assert!(started.elapsed() < timeout);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
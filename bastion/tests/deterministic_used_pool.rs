@@ -0,0 +1,13 @@
+use bastion::prelude::*;
+use bastion_executor::pool;
+use lightproc::proc_stack::ProcStack;
+
+#[test]
+#[should_panic(expected = "Couldn't run deterministically")]
+fn deterministic_seed_after_pool() {
+    // A process was already spawned onto the thread pool...
+    pool::spawn(async {}, ProcStack::default());
+
+    // ...so the system can't run deterministically.
+    Bastion::init_with(Config::new().with_deterministic_seed(42));
+}
@@ -0,0 +1,65 @@
+use bastion::prelude::*;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn elements_keep_their_index_across_restarts() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let children_ref = Bastion::children(|children| {
+        children
+            .with_redundancy(3)
+            .with_exec_indexed(move |index, ctx: BastionContext| {
+                let sender = sender.clone();
+                async move {
+                    assert_eq!(index, ctx.index());
+                    let started = (index, ctx.group_size(), ctx.current().id().clone());
+                    sender.lock().unwrap().send(started).unwrap();
+
+                    msg! { ctx.recv().await?,
+                        _msg: &'static str => {
+                            return Err(());
+                        };
+                        _: _ => ();
+                    }
+
+                    Ok(())
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    let recv = |count| {
+        let mut started = (0..count)
+            .map(|_| recver.recv_timeout(Duration::from_secs(1)).unwrap())
+            .collect::<Vec<_>>();
+        started.sort_by_key(|started| started.0);
+        started
+    };
+
+    let started = recv(3);
+    for (index, elem) in children_ref.elems().iter().enumerate() {
+        assert_eq!(started[index], (index, 3, elem.id().clone()));
+    }
+
+    // Make the group fault and restart...
+    children_ref.elems()[1]
+        .tell_anonymously("fault")
+        .expect("Couldn't send the message.");
+
+    // ...and the new elements get the same indices.
+    let restarted = recv(3);
+    for index in 0..3 {
+        assert_eq!(restarted[index].0, index);
+        assert_eq!(restarted[index].1, 3);
+        assert_ne!(restarted[index].2, started[index].2);
+    }
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
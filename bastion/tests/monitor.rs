@@ -0,0 +1,89 @@
+use bastion::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Fault;
+
+fn worker() -> ChildRef {
+    let children = Bastion::supervisor(|sp| sp)
+        .and_then(|sp| {
+            sp.children(|children| {
+                children.with_exec(|ctx: BastionContext| async move {
+                    loop {
+                        msg! { ctx.recv().await?,
+                            _msg: Fault => {
+                                return Err(());
+                            };
+                            _: _ => ();
+                        }
+                    }
+                })
+            })
+        })
+        .expect("Couldn't create the children group.");
+
+    children.elems()[0].clone()
+}
+
+#[test]
+fn monitors_and_links() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let monitored = worker();
+    let linked = worker();
+
+    let watched = vec![monitored.clone(), linked.clone()];
+    let watching = Arc::new(AtomicBool::new(false));
+    Bastion::supervisor(|sp| {
+        sp.children(|children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let sender = sender.clone();
+                let watched = watched.clone();
+                let watching = watching.clone();
+                async move {
+                    // Only watch the workers before being restarted.
+                    if !watching.swap(true, Ordering::SeqCst) {
+                        ctx.monitor(&watched[0]);
+                        ctx.link(&watched[1]);
+                    }
+                    sender.lock().unwrap().send(None).unwrap();
+
+                    loop {
+                        msg! { ctx.recv().await?,
+                            down: Down => {
+                                let id = down.id().clone();
+                                sender.lock().unwrap().send(Some((id, down.reason()))).unwrap();
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    // The watcher monitored and linked itself...
+    assert_eq!(recver.recv_timeout(Duration::from_secs(1)).unwrap(), None);
+
+    // ...is notified when the monitored child faults...
+    monitored.tell_anonymously(Fault).unwrap();
+    assert_eq!(
+        recver.recv_timeout(Duration::from_secs(1)).unwrap(),
+        Some((monitored.id().clone(), ExitReason::Faulted))
+    );
+
+    // ...and faults (then gets restarted) when the linked child faults.
+    linked.tell_anonymously(Fault).unwrap();
+    assert_eq!(recver.recv_timeout(Duration::from_secs(1)).unwrap(), None);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
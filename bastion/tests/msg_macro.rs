@@ -0,0 +1,103 @@
+use bastion::prelude::*;
+use futures::executor::block_on;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[test]
+fn msg_macro_arms() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let children_ref = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let sender = sender.clone();
+            async move {
+                let send = |event: String| sender.lock().unwrap().send(event).unwrap();
+
+                loop {
+                    msg! { ctx.recv().await?,
+                        // Guards...
+                        n: u32 if n > 10 => send(format!("big {}", n));
+                        // ...type alternation...
+                        n: u32 | u64 => send(format!("small {}", n));
+                        // ...tuple and struct patterns...
+                        (a, b): (i32, i32) if a == b => send(format!("pair {}", a));
+                        Point { x, y: 0 }: Point => send(format!("x axis {}", x));
+                        Point { x, y }: Point => send(format!("point {} {}", x, y));
+                        // ...broadcasted or "told" messages...
+                        any text: String => send(format!("text {}", text));
+                        // ...and answers from any case.
+                        n: u8 if n > 1 =!> {
+                            answer!(ctx, n * 2).unwrap();
+                        };
+                        n: u8 => {
+                            let answered = answer!(ctx, n).is_ok();
+                            send(format!("answered {}", answered));
+                        };
+                        msg: _ => {
+                            let answered = answer!(ctx, "default").is_ok();
+                            send(format!("default {} {}", msg.is_ask(), answered));
+                        };
+                    }
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let recv = || recver.recv_timeout(Duration::from_secs(1)).unwrap();
+    let child_ref = &children_ref.elems()[0];
+
+    child_ref.tell_anonymously(42u32).unwrap();
+    assert_eq!(recv(), "big 42");
+    child_ref.tell_anonymously(7u32).unwrap();
+    assert_eq!(recv(), "small 7");
+    child_ref.tell_anonymously(8u64).unwrap();
+    assert_eq!(recv(), "small 8");
+
+    child_ref.tell_anonymously((3i32, 3i32)).unwrap();
+    assert_eq!(recv(), "pair 3");
+    // The guard doesn't match so the default case does.
+    child_ref.tell_anonymously((1i32, 2i32)).unwrap();
+    assert_eq!(recv(), "default false false");
+
+    child_ref.tell_anonymously(Point { x: 1, y: 0 }).unwrap();
+    assert_eq!(recv(), "x axis 1");
+    child_ref.tell_anonymously(Point { x: 1, y: 2 }).unwrap();
+    assert_eq!(recv(), "point 1 2");
+
+    children_ref.broadcast("broadcasted".to_string()).unwrap();
+    assert_eq!(recv(), "text broadcasted");
+    child_ref.tell_anonymously("told".to_string()).unwrap();
+    assert_eq!(recv(), "text told");
+    // Asked messages don't match `any` cases.
+    let answer = child_ref.ask_anonymously("asked".to_string()).unwrap();
+    assert_eq!(recv(), "default true true");
+    msg! { block_on(answer).unwrap(),
+        msg: &'static str => assert_eq!(msg, "default");
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    let answer = child_ref.ask_anonymously(4u8).unwrap();
+    msg! { block_on(answer).unwrap(),
+        n: u8 => assert_eq!(n, 8);
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    // Messages that weren't asked can't be answered.
+    child_ref.tell_anonymously(4u8).unwrap();
+    assert_eq!(recv(), "answered false");
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,77 @@
+use bastion::prelude::*;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn names_are_kept_across_restarts() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    Bastion::supervisor(|sp| {
+        sp.with_name("ingest").children(|children| {
+            children.with_name("workers").with_redundancy(2).with_exec(
+                move |ctx: BastionContext| {
+                    let sender = sender.clone();
+                    async move {
+                        let path = format!("{:?}", ctx.signature().path());
+                        sender
+                            .lock()
+                            .unwrap()
+                            .send((path, ctx.current().id().clone()))
+                            .unwrap();
+
+                        msg! { ctx.recv().await?,
+                            _msg: &'static str => {
+                                return Err(());
+                            };
+                            _: _ => ();
+                        }
+
+                        Ok(())
+                    }
+                },
+            )
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    let recv = || recver.recv_timeout(Duration::from_secs(1)).unwrap();
+    let mut started = [recv(), recv()];
+    started.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(started[0].0, "/ingest/workers/0");
+    assert_eq!(started[1].0, "/ingest/workers/1");
+
+    let path: BastionPath = "/ingest/workers/1".parse().unwrap();
+    let addr = Bastion::resolve(&path).expect("Couldn't resolve the path.");
+    assert_eq!(addr.path().id(), &started[1].1);
+    assert_eq!(Bastion::select("/ingest/workers/*").unwrap().len(), 2);
+
+    // Make the group fault and restart...
+    Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let addr = addr.clone();
+            async move {
+                ctx.tell(&addr, "fault").unwrap();
+                Ok(())
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    // ...its elements keep the same paths.
+    let mut restarted = [recv(), recv()];
+    restarted.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(restarted[0].0, "/ingest/workers/0");
+    assert_eq!(restarted[1].0, "/ingest/workers/1");
+    assert_ne!(restarted[1].1, started[1].1);
+
+    let addr = Bastion::resolve(&path).expect("Couldn't resolve the path.");
+    assert_eq!(addr.path().id(), &restarted[1].1);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,34 @@
+use bastion::codec::Codecs;
+use bastion::prelude::*;
+use bastion::transport::Node;
+use std::net::TcpListener;
+
+mod common;
+
+use common::wait_for;
+
+#[test]
+fn dropped_node_shuts_down() {
+    Bastion::init();
+    Bastion::start();
+
+    let node = Node::listen("127.0.0.1:0", Codecs::new()).expect("Couldn't listen.");
+    let other = Node::listen("127.0.0.1:0", Codecs::new()).expect("Couldn't listen.");
+    let id = node.id().clone();
+    let other_id = other.id().clone();
+
+    // Referencing an element on the other node connects to it.
+    let path = "/remote/echo/0".parse().unwrap();
+    let _remote = node.remote(&other_id, &path);
+    wait_for(|| node.is_connected(&other_id) && other.is_connected(&id));
+
+    // Dropping the node closes its connections and stops its
+    // listener, without calling `shutdown`.
+    drop(node);
+    wait_for(|| !other.is_connected(&id));
+    wait_for(|| TcpListener::bind(id.addr()).is_ok());
+
+    other.shutdown();
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,99 @@
+use bastion::persistence::{Journal, MemoryStorage, Persistent, Storage};
+use bastion::prelude::*;
+use std::convert::TryInto;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+struct Counter(u64);
+
+impl Persistent for Counter {
+    type Event = u64;
+
+    fn apply(&mut self, added: &u64) {
+        self.0 += added;
+    }
+
+    fn encode_event(added: &u64) -> Vec<u8> {
+        added.to_le_bytes().to_vec()
+    }
+
+    fn decode_event(bytes: &[u8]) -> Option<u64> {
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    fn encode_snapshot(&self) -> Option<Vec<u8>> {
+        Some(self.0.to_le_bytes().to_vec())
+    }
+
+    fn decode_snapshot(bytes: &[u8]) -> Option<Self> {
+        Some(Counter(u64::from_le_bytes(bytes.try_into().ok()?)))
+    }
+}
+
+#[test]
+fn persistent_children_replay_events() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+    let storage = MemoryStorage::new();
+
+    let children_ref = Bastion::children(|children| {
+        children.with_persistence(
+            "counter",
+            storage.clone(),
+            move |ctx: BastionContext, mut journal: Journal<Counter>| {
+                let sender = sender.clone();
+                async move {
+                    sender.lock().unwrap().send(journal.state().0).unwrap();
+
+                    loop {
+                        msg! { ctx.recv().await?,
+                            added: u64 => {
+                                journal.persist(added).await.unwrap();
+                            };
+                            msg: &'static str => {
+                                match msg {
+                                    "snapshot" => assert!(journal.snapshot().await.unwrap()),
+                                    "panic" => panic!("Panicking at {}.", journal.seq()),
+                                    _ => (),
+                                }
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            },
+        )
+    })
+    .expect("Couldn't create the children group.");
+
+    let recv = || recver.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(recv(), 0);
+
+    let child_ref = children_ref.stable().elems()[0].clone();
+    child_ref.tell_anonymously(1u64).unwrap();
+    child_ref.tell_anonymously(2u64).unwrap();
+    child_ref.tell_anonymously("panic").unwrap();
+
+    // The restarted child replays the persisted events...
+    assert_eq!(recv(), 3);
+
+    child_ref.tell_anonymously("snapshot").unwrap();
+    child_ref.tell_anonymously(4u64).unwrap();
+    child_ref.tell_anonymously("panic").unwrap();
+
+    // ...after its last snapshot.
+    assert_eq!(recv(), 7);
+    assert_eq!(
+        storage.snapshot("counter.0").unwrap(),
+        Some((2, 3u64.to_le_bytes().to_vec()))
+    );
+    assert_eq!(storage.events("counter.0", 0).unwrap().len(), 3);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,73 @@
+use bastion::prelude::*;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn published_messages_reach_matching_subscribers() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    for pattern in &["orders.*.created", "orders.#", "invoices.#"] {
+        let sender = sender.clone();
+        Bastion::children(|children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let sender = sender.clone();
+                async move {
+                    ctx.subscribe(pattern).expect("Couldn't subscribe.");
+                    sender.lock().unwrap().send((*pattern, None)).unwrap();
+
+                    loop {
+                        msg! { ctx.recv().await?,
+                            ref msg: &'static str => {
+                                sender.lock().unwrap().send((*pattern, Some(*msg))).unwrap();
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+    }
+
+    for _ in 0..3 {
+        let (_, msg) = recver.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert!(msg.is_none());
+    }
+
+    assert_eq!(
+        Bastion::publish("payments.created", "nobody"),
+        Err("nobody")
+    );
+
+    Bastion::publish("orders.eu.created", "created").unwrap();
+    let mut received = vec![
+        recver.recv_timeout(Duration::from_secs(1)).unwrap(),
+        recver.recv_timeout(Duration::from_secs(1)).unwrap(),
+    ];
+    received.sort();
+    assert_eq!(
+        received,
+        vec![
+            ("orders.#", Some("created")),
+            ("orders.*.created", Some("created")),
+        ]
+    );
+
+    Bastion::publish("invoices", "invoiced").unwrap();
+    assert_eq!(
+        recver.recv_timeout(Duration::from_secs(1)).unwrap(),
+        ("invoices.#", Some("invoiced"))
+    );
+
+    thread::sleep(Duration::from_millis(50));
+    assert!(recver.try_recv().is_err());
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
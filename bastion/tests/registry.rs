@@ -0,0 +1,54 @@
+use bastion::prelude::*;
+
+mod common;
+
+use common::wait_for;
+
+#[test]
+fn registered_names_follow_restarts() {
+    Bastion::init();
+    Bastion::start();
+
+    Bastion::supervisor(|sp| {
+        sp.children(|children| {
+            children.with_exec(|ctx: BastionContext| async move {
+                ctx.register("flaky").expect("Couldn't register the child.");
+
+                loop {
+                    msg! { ctx.recv().await?,
+                        msg: &'static str => {
+                            if msg == "panic" {
+                                panic!("Asked to panic.");
+                            } else if msg == "stop" {
+                                return Ok(());
+                            }
+                        };
+                        _: _ => ();
+                    }
+                }
+            })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    wait_for(|| Bastion::whereis("flaky").is_some());
+    let first = Bastion::whereis("flaky").unwrap();
+
+    first.tell_anonymously("panic").unwrap();
+    wait_for(|| {
+        Bastion::whereis("flaky")
+            .map(|child| child.id() != first.id())
+            .unwrap_or(false)
+    });
+    let restarted = Bastion::whereis("flaky").unwrap();
+    assert!(Bastion::register("flaky", &first).is_err());
+    assert!(Bastion::register("flaky", &restarted).is_ok());
+    Bastion::unregister("flaky");
+    Bastion::register("flaky", &restarted).unwrap();
+
+    restarted.tell_anonymously("stop").unwrap();
+    wait_for(|| Bastion::whereis("flaky").is_none());
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
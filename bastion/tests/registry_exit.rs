@@ -0,0 +1,35 @@
+use bastion::prelude::*;
+
+mod common;
+
+use common::wait_for;
+
+#[test]
+fn names_unregistered_when_killed() {
+    Bastion::init();
+    Bastion::start();
+
+    let children = Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            ctx.register("killed")
+                .expect("Couldn't register the child.");
+
+            loop {
+                ctx.recv().await?;
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    wait_for(|| Bastion::whereis("killed").is_some());
+
+    // The group isn't restarted once killed...
+    children.kill().expect("Couldn't kill the children group.");
+    wait_for(|| Bastion::whereis("killed").is_none());
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+
+    // ...so the name of its element doesn't refer to it anymore.
+    assert!(Bastion::unregister("killed").is_none());
+}
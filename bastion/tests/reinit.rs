@@ -0,0 +1,39 @@
+use bastion::prelude::*;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn reinit_after_stop() {
+    for run in 0..3 {
+        Bastion::init();
+        // The names registered by the previous run's elements
+        // belong to the stopped system.
+        assert!(Bastion::whereis("job").is_none());
+        Bastion::start();
+
+        let (sender, recver) = mpsc::channel();
+        let sender = Arc::new(Mutex::new(sender));
+        Bastion::children(|children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let sender = sender.clone();
+                async move {
+                    ctx.register("job").expect("Couldn't register.");
+                    sender.lock().unwrap().send(run).unwrap();
+
+                    loop {
+                        ctx.recv().await?;
+                    }
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+
+        assert_eq!(recver.recv_timeout(Duration::from_secs(1)), Ok(run));
+        assert!(Bastion::whereis("job").is_some());
+
+        Bastion::stop();
+        Bastion::block_until_stopped();
+        assert!(Bastion::system().is_stopped());
+    }
+}
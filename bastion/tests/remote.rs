@@ -0,0 +1,193 @@
+use bastion::codec::Codecs;
+use bastion::prelude::*;
+use bastion::transport::{Node, NodeId};
+use futures::executor::block_on;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+use common::wait_for;
+
+// Set when this test is run as the remote node.
+const REMOTE_NODE: &str = "BASTION_TEST_REMOTE_NODE";
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Echo {
+    Tell(u64),
+    Ask(u64),
+    Reply(u64),
+}
+
+fn codecs() -> Codecs {
+    Codecs::new().register::<Echo>("echo")
+}
+
+fn echo_path() -> BastionPath {
+    "/remote/echo/0".parse().unwrap()
+}
+
+// Runs a node listening on the given address, with an element
+// echoing the messages it receives, until it gets killed.
+fn run_remote_node(addr: &str) {
+    Bastion::init();
+    Bastion::start();
+
+    let node = Node::listen(addr, codecs()).expect("Couldn't listen.");
+    Bastion::supervisor(|sp| {
+        sp.with_name("remote").children(|children| {
+            children
+                .with_name("echo")
+                .with_exec(|ctx: BastionContext| async move {
+                    loop {
+                        msg! { ctx.recv().await?,
+                            msg: Echo =!> {
+                                if let Echo::Ask(n) = msg {
+                                    answer!(ctx, Echo::Reply(n * 2)).unwrap();
+                                }
+                            };
+                            msg: Echo => {
+                                if let Echo::Tell(n) = msg {
+                                    ctx.tell(&signature!(), Echo::Reply(n + 1)).unwrap();
+                                }
+                            };
+                            _: _ => ();
+                        }
+                    }
+                })
+        })
+    })
+    .expect("Couldn't create the supervisor.");
+
+    wait_for(|| Bastion::resolve(&echo_path()).is_some());
+    println!("listening {}", node.id());
+
+    loop {
+        thread::park();
+    }
+}
+
+// Runs this test again in another process, as the remote node.
+fn spawn_remote_node(addr: &str) -> (Child, NodeId) {
+    let mut remote = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "remote_messaging", "--nocapture"])
+        .env(REMOTE_NODE, addr)
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Couldn't spawn the remote node.");
+
+    // NOTE: the line might start with the test harness' output.
+    let stdout = BufReader::new(remote.stdout.take().unwrap());
+    let id = stdout
+        .lines()
+        .find_map(|line| Some(line.ok()?.split("listening ").nth(1)?.parse().unwrap()))
+        .expect("The remote node didn't start.");
+
+    (remote, id)
+}
+
+#[test]
+fn remote_messaging() {
+    if let Ok(addr) = env::var(REMOTE_NODE) {
+        return run_remote_node(&addr);
+    }
+
+    Bastion::init();
+    Bastion::start();
+
+    let node = Node::listen("127.0.0.1:0", codecs()).expect("Couldn't listen.");
+    let (mut remote, remote_id) = spawn_remote_node("127.0.0.1:0");
+    let echo = node.remote(&remote_id, &echo_path());
+    assert_eq!(echo.path().node(), Some(&remote_id));
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let children_ref = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let echo = echo.clone();
+            let sender = sender.clone();
+            async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        // Asks are forwarded to the remote element...
+                        n: u64 =!> {
+                            let reply = match ctx.ask(&echo, Echo::Ask(n)).unwrap().await {
+                                Ok(reply) => msg! { reply,
+                                    reply: Echo => Some(reply);
+                                    _: _ => None;
+                                },
+                                Err(()) => None,
+                            };
+                            answer!(ctx, reply).unwrap();
+                        };
+                        // ...and so are tells, whose replies are sent back.
+                        msg: Echo => match msg {
+                            Echo::Tell(n) => ctx.tell(&echo, Echo::Tell(n)).unwrap(),
+                            reply => {
+                                let node = signature!().path().node().cloned();
+                                sender.lock().unwrap().send((reply, node)).unwrap();
+                            }
+                        };
+                        _: _ => ();
+                    }
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let child_ref = children_ref.elems()[0].clone();
+    let ask = |n: u64| {
+        let answer = child_ref.ask_anonymously(n).unwrap();
+        msg! { block_on(answer).unwrap(),
+            reply: Option<Echo> => reply;
+            _: _ => panic!("Unexpected answer.");
+        }
+    };
+
+    // Messages asked to a remote element are answered...
+    assert_eq!(ask(21), Some(Echo::Reply(42)));
+    assert!(node.is_connected(&remote_id));
+
+    // ...and the messages told to it arrive in order, signed by
+    // the remote element so that it can reply.
+    for n in 0..100u64 {
+        child_ref.tell_anonymously(Echo::Tell(n)).unwrap();
+    }
+
+    for n in 0..100u64 {
+        let (reply, node) = recver.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(reply, Echo::Reply(n + 1));
+        assert_eq!(node, Some(remote_id.clone()));
+    }
+
+    // When the remote node goes down...
+    remote.kill().unwrap();
+    remote.wait().unwrap();
+    wait_for(|| !node.is_connected(&remote_id));
+    // ...the messages sent to it are queued...
+    child_ref.tell_anonymously(Echo::Tell(100)).unwrap();
+
+    // ...until the link reconnects to it once it's back up.
+    let (mut remote, restarted_id) = spawn_remote_node(&remote_id.to_string());
+    assert_eq!(restarted_id, remote_id);
+
+    let (reply, _) = recver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(reply, Echo::Reply(101));
+    assert_eq!(ask(7), Some(Echo::Reply(14)));
+    assert!(node.is_connected(&remote_id));
+
+    remote.kill().unwrap();
+    remote.wait().unwrap();
+    node.shutdown();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
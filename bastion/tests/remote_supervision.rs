@@ -0,0 +1,92 @@
+use bastion::codec::Codecs;
+use bastion::prelude::*;
+use bastion::transport::{Node, NodeId};
+use futures::executor::block_on;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+use common::wait_for;
+
+// Runs a node hosting workers which fault the first time they
+// are deployed, counting how many times they were.
+fn listen_host(addr: SocketAddr, starts: &Arc<AtomicUsize>) -> Node {
+    let mut listened = Node::listen(addr, Codecs::new());
+    // The address might still be in use by a node which was just
+    // shut down.
+    for _ in 0..100 {
+        if listened.is_ok() {
+            break;
+        }
+
+        thread::sleep(Duration::from_millis(50));
+        listened = Node::listen(addr, Codecs::new());
+    }
+
+    let host = listened.expect("Couldn't listen.");
+    let starts = starts.clone();
+    host.register_factory("worker", move |children| {
+        let starts = starts.clone();
+        children
+            .with_name("worker")
+            .with_exec(move |ctx: BastionContext| {
+                let starts = starts.clone();
+                async move {
+                    if starts.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return Err(());
+                    }
+
+                    loop {
+                        ctx.recv().await?;
+                    }
+                }
+            })
+    });
+
+    host
+}
+
+#[test]
+fn remote_supervision() {
+    Bastion::init();
+    Bastion::start();
+
+    let starts = Arc::new(AtomicUsize::new(0));
+    let host = listen_host("127.0.0.1:0".parse().unwrap(), &starts);
+    let host_id: NodeId = host.id().clone();
+    let worker: BastionPath = format!("/deployed@{}/worker/0", host_id).parse().unwrap();
+
+    let node = Node::listen("127.0.0.1:0", Codecs::new()).expect("Couldn't listen.");
+    let supervisor =
+        Bastion::supervisor(|sp| sp.with_name("origin")).expect("Couldn't create the supervisor.");
+    let stand_in = supervisor
+        .remote_children(&node, &host_id, "worker")
+        .expect("Couldn't deploy the children group.");
+
+    // The remote group faults the first time it runs, which makes
+    // the group standing in for it fault...
+    assert_eq!(block_on(stand_in.terminated()), ExitReason::Faulted);
+    // ...and be restarted, deploying the remote group again.
+    wait_for(|| starts.load(Ordering::SeqCst) == 2 && Bastion::resolve(&worker).is_some());
+
+    // When the remote node goes down, the stand-in group faults
+    // too, and deploys the remote group again once it's back up.
+    host.shutdown();
+    let host = listen_host(host_id.addr(), &starts);
+    wait_for(|| starts.load(Ordering::SeqCst) == 3 && Bastion::resolve(&worker).is_some());
+
+    // Stopping the supervisor stops the remote group.
+    supervisor.stop().unwrap();
+    wait_for(|| Bastion::resolve(&worker).is_none());
+    assert_eq!(starts.load(Ordering::SeqCst), 3);
+
+    node.shutdown();
+    host.shutdown();
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,56 @@
+use bastion::prelude::*;
+
+mod common;
+
+use common::wait_for;
+
+#[test]
+fn resolve_and_select() {
+    Bastion::init();
+    Bastion::start();
+
+    let children_ref = Bastion::children(|children| {
+        children
+            .with_redundancy(3)
+            .with_exec(|ctx: BastionContext| async move {
+                loop {
+                    ctx.recv().await?;
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    let child_ref = children_ref.elems()[0].clone();
+    let path: BastionPath = child_ref.addr().path().to_string().parse().unwrap();
+    wait_for(|| Bastion::resolve(&path).is_some());
+    let addr = Bastion::resolve(&path).unwrap();
+    assert_eq!(addr.path().id(), child_ref.id());
+
+    let group = child_ref.addr().path().to_string();
+    let (group, _) = group.split_at(group.rfind('/').unwrap());
+    let pattern = format!("{}/*", group);
+    wait_for(|| Bastion::select(&pattern).unwrap().len() == 3);
+    let mut selected = Bastion::select(&pattern)
+        .unwrap()
+        .iter()
+        .map(|addr| addr.path().id().clone())
+        .collect::<Vec<_>>();
+    let mut elems = children_ref
+        .elems()
+        .iter()
+        .map(|child| child.id().clone())
+        .collect::<Vec<_>>();
+    selected.sort_by_key(|id| id.to_string());
+    elems.sort_by_key(|id| id.to_string());
+    assert_eq!(selected, elems);
+
+    assert!(Bastion::select("not-a-path").is_err());
+    assert!(Bastion::resolve(&"/".parse().unwrap()).is_none());
+
+    children_ref.stop().unwrap();
+    wait_for(|| Bastion::resolve(&path).is_none());
+    assert!(Bastion::select(&pattern).unwrap().is_empty());
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,42 @@
+use bastion::prelude::*;
+use futures::executor::block_on;
+use std::cell::Cell;
+use std::sync::mpsc::{self, Receiver};
+
+#[test]
+fn non_sync_messages_can_be_told_and_asked() {
+    Bastion::init();
+    Bastion::start();
+
+    let children_ref = Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            loop {
+                msg! { ctx.recv().await?,
+                    // Neither `Cell` nor `Receiver` are `Sync`...
+                    cell: Cell<u32> => cell.set(cell.get() + 1);
+                    recver: Receiver<u32> =!> {
+                        let cell = Cell::new(recver.recv().unwrap());
+                        // ...and neither is the answer.
+                        answer!(ctx, cell).unwrap();
+                    };
+                    _: _ => ();
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let child_ref = &children_ref.elems()[0];
+    child_ref.tell_anonymously(Cell::new(0u32)).unwrap();
+
+    let (sender, recver) = mpsc::channel();
+    sender.send(42u32).unwrap();
+    let answer = child_ref.ask_anonymously(recver).unwrap();
+    msg! { block_on(answer).unwrap(),
+        cell: Cell<u32> => assert_eq!(cell.get(), 42);
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,87 @@
+use bastion::prelude::*;
+use futures::executor::block_on;
+use futures::future;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Started,
+    Cancelled,
+}
+
+// Sends `Event::Cancelled` once the task owning it is dropped.
+struct Guard(Arc<Mutex<Sender<Event>>>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.0.lock().unwrap().send(Event::Cancelled).unwrap();
+    }
+}
+
+#[test]
+fn tasks_are_tied_to_their_child() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let children_ref = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let sender = sender.clone();
+            async move {
+                sender.lock().unwrap().send(Event::Started).unwrap();
+
+                // A task which never completes...
+                let guard = Guard(sender.clone());
+                ctx.spawn(async move {
+                    let _guard = guard;
+                    future::pending::<()>().await
+                });
+
+                loop {
+                    msg! { ctx.recv().await?,
+                        msg: &'static str =!> {
+                            assert_eq!(msg, "compute");
+                            let answer = ctx.spawn(async { 21u64 * 2 }).await;
+                            answer!(ctx, answer).unwrap();
+                        };
+                        msg: &'static str => {
+                            assert_eq!(msg, "panic");
+                            ctx.spawn(async { panic!("A task panicked.") });
+                        };
+                        _: _ => ();
+                    }
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let recv = || recver.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(recv(), Event::Started);
+
+    let child_ref = children_ref.stable().elems()[0].clone();
+
+    // The output of a task is sent to its handle...
+    let answer = child_ref.ask_anonymously("compute").unwrap();
+    msg! { block_on(answer).unwrap(),
+        answer: Option<u64> => assert_eq!(answer, Some(42));
+        _: _ => panic!("Unexpected answer.");
+    }
+
+    // ...a panicking task makes the child fault, which cancels
+    // its other tasks before it is restarted...
+    child_ref.tell_anonymously("panic").unwrap();
+    assert_eq!(recv(), Event::Cancelled);
+    assert_eq!(recv(), Event::Started);
+
+    // ...and so does stopping it.
+    child_ref.stop().unwrap();
+    assert_eq!(recv(), Event::Cancelled);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
@@ -0,0 +1,103 @@
+use bastion::prelude::*;
+use futures::executor::block_on;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+mod common;
+
+use common::wait_for;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Started(BastionId),
+    Received(String),
+}
+
+#[test]
+fn stable_refs_follow_restarts() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let children_ref = Bastion::children(|children| {
+        children.with_exec(move |ctx: BastionContext| {
+            let sender = sender.clone();
+            async move {
+                let id = ctx.current().id().clone();
+                sender.lock().unwrap().send(Event::Started(id)).unwrap();
+
+                loop {
+                    msg! { ctx.recv().await?,
+                        _msg: &'static str => {
+                            return Err(());
+                        };
+                        msg: String => {
+                            sender.lock().unwrap().send(Event::Received(msg)).unwrap();
+                        };
+                        _: _ => ();
+                    }
+                }
+            }
+        })
+    })
+    .expect("Couldn't create the children group.");
+
+    let recv = || recver.recv_timeout(Duration::from_secs(1)).unwrap();
+    let started = match recv() {
+        Event::Started(id) => id,
+        event => panic!("Unexpected event: {:?}", event),
+    };
+
+    let stable = children_ref.stable();
+    let child_ref = stable.elems()[0].clone();
+    let current = child_ref.current().expect("The child isn't running.");
+    assert_eq!(current.id(), &started);
+    assert_eq!(stable.current().unwrap().id(), children_ref.id());
+
+    // Make the group fault and wait for the child to be gone...
+    child_ref.tell_anonymously("fault").unwrap();
+    block_on(current.terminated());
+
+    // ...messages sent in the meantime reach the new child.
+    child_ref.tell_anonymously("hello".to_string()).unwrap();
+
+    let restarted = match recv() {
+        Event::Started(id) => id,
+        event => panic!("Unexpected event: {:?}", event),
+    };
+    assert_ne!(restarted, started);
+    assert_eq!(recv(), Event::Received("hello".to_string()));
+
+    assert_eq!(child_ref.current().unwrap().id(), &restarted);
+    assert_ne!(stable.current().unwrap().id(), children_ref.id());
+
+    // Once the group is stopped, messages can't be sent anymore.
+    let current = stable.current().unwrap();
+    stable.stop().unwrap();
+    block_on(current.terminated());
+    assert!(child_ref.tell_anonymously("bye".to_string()).is_err());
+
+    // Neither can they once a group is killed without being
+    // restarted.
+    let killed = Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            loop {
+                ctx.recv().await?;
+            }
+        })
+    })
+    .expect("Couldn't create the children group.")
+    .stable();
+    let killed_child = killed.elems()[0].clone();
+    wait_for(|| killed_child.current().is_some());
+
+    killed.kill().unwrap();
+    wait_for(|| killed.broadcast("bye".to_string()).is_err());
+    assert!(killed_child.tell_anonymously("bye".to_string()).is_err());
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
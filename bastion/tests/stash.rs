@@ -0,0 +1,88 @@
+use bastion::prelude::*;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    StashFull,
+    Received(String),
+}
+
+#[test]
+fn stash_and_unstash() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let children_ref = Bastion::children(|children| {
+        children
+            .with_stash_capacity(2)
+            .with_exec(move |ctx: BastionContext| {
+                let sender = sender.clone();
+                async move {
+                    let send = |event| sender.lock().unwrap().send(event).unwrap();
+
+                    // Stash everything until "ready" is received...
+                    loop {
+                        let msg = ctx.recv().await?;
+                        if msg.is::<&'static str>() {
+                            break;
+                        }
+
+                        if ctx.stash(msg).await.is_err() {
+                            send(Event::StashFull);
+                        }
+                    }
+
+                    // ...and then handle the stashed messages first.
+                    ctx.unstash_all().await?;
+                    loop {
+                        msg! { ctx.recv().await?,
+                            msg: String => {
+                                send(Event::Received(msg));
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    let child_ref = &children_ref.elems()[0];
+    for msg in &["a", "b", "c"] {
+        child_ref.tell_anonymously(msg.to_string()).unwrap();
+    }
+    child_ref.tell_anonymously("ready").unwrap();
+    child_ref.tell_anonymously("d".to_string()).unwrap();
+
+    let recv = || recver.recv_timeout(Duration::from_secs(1)).unwrap();
+    // The stash can only contain two messages...
+    assert_eq!(recv(), Event::StashFull);
+    // ...which are received in order before the next ones.
+    assert_eq!(recv(), Event::Received("a".to_string()));
+    assert_eq!(recv(), Event::Received("b".to_string()));
+    assert_eq!(recv(), Event::Received("d".to_string()));
+
+    // Messages still stashed when the child stops are sent to
+    // dead letters.
+    let stopped = Bastion::children(|children| {
+        children.with_exec(|ctx: BastionContext| async move {
+            let msg = ctx.recv().await?;
+            ctx.stash(msg).await.unwrap();
+            Ok(())
+        })
+    })
+    .expect("Couldn't create the children group.");
+    let terminated = stopped.terminated();
+    stopped.elems()[0]
+        .tell_anonymously("stashed".to_string())
+        .unwrap();
+    assert_eq!(futures::executor::block_on(terminated), ExitReason::Stopped);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
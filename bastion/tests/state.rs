@@ -0,0 +1,66 @@
+use bastion::prelude::*;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn state_is_restored_from_checkpoint() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::channel();
+    let sender = Arc::new(Mutex::new(sender));
+
+    let children_ref = Bastion::children(|children| {
+        children.with_state(
+            || 0u32,
+            move |ctx: BastionContext, state: State<u32>| {
+                let sender = sender.clone();
+                async move {
+                    let mut count = state.get();
+                    sender.lock().unwrap().send(count).unwrap();
+
+                    loop {
+                        msg! { ctx.recv().await?,
+                            msg: &'static str => {
+                                match msg {
+                                    "inc" => {
+                                        count += 1;
+                                        state.save(count);
+                                    }
+                                    // Not saved before panicking.
+                                    "inc-and-panic" => {
+                                        count += 1;
+                                        panic!("Panicking with count = {}.", count);
+                                    }
+                                    _ => (),
+                                }
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            },
+        )
+    })
+    .expect("Couldn't create the children group.");
+
+    let recv = || recver.recv_timeout(Duration::from_secs(1)).unwrap();
+    assert_eq!(recv(), 0);
+
+    let child_ref = children_ref.stable().elems()[0].clone();
+    child_ref.tell_anonymously("inc").unwrap();
+    child_ref.tell_anonymously("inc").unwrap();
+    child_ref.tell_anonymously("inc-and-panic").unwrap();
+
+    // The restarted child resumes from the last checkpoint...
+    assert_eq!(recv(), 2);
+
+    child_ref.tell_anonymously("inc").unwrap();
+    child_ref.tell_anonymously("inc-and-panic").unwrap();
+    // ...each time it is restarted.
+    assert_eq!(recv(), 3);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
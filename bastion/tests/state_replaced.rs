@@ -0,0 +1,40 @@
+use bastion::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn state_replaced_by_exec() {
+    Bastion::init();
+    Bastion::start();
+
+    let (sender, recver) = mpsc::sync_channel(1);
+    let initialized = Arc::new(AtomicBool::new(false));
+    let init = initialized.clone();
+    Bastion::children(|children| {
+        children
+            .with_state(
+                move || init.store(true, Ordering::SeqCst),
+                |_: BastionContext, _: State<()>| async { Ok(()) },
+            )
+            // The elements don't use the state anymore...
+            .with_exec(move |_: BastionContext| {
+                let sender = sender.clone();
+                async move {
+                    sender.send(()).unwrap();
+                    Ok(())
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    recver
+        .recv_timeout(Duration::from_secs(1))
+        .expect("Element didn't start.");
+    // ...so it isn't created.
+    assert!(!initialized.load(Ordering::SeqCst));
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
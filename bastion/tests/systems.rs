@@ -0,0 +1,74 @@
+use bastion::prelude::*;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Creates a children group in the given system whose element
+// registers itself as "worker", subscribes to the "jobs" topic
+// and forwards the messages it receives.
+fn worker(system: &BastionSystem, label: &'static str, sender: mpsc::Sender<&'static str>) {
+    let sender = Arc::new(Mutex::new(sender));
+    system
+        .children(|children| {
+            children.with_exec(move |ctx: BastionContext| {
+                let sender = sender.clone();
+                async move {
+                    ctx.register("worker").expect("Couldn't register.");
+                    ctx.subscribe("jobs").expect("Couldn't subscribe.");
+                    sender.lock().unwrap().send(label).unwrap();
+
+                    loop {
+                        msg! { ctx.recv().await?,
+                            msg: &'static str => {
+                                sender.lock().unwrap().send(msg).unwrap();
+                            };
+                            ref msg: &'static str => {
+                                sender.lock().unwrap().send(*msg).unwrap();
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+}
+
+#[test]
+fn independent_systems() {
+    let first = BastionSystem::new();
+    let second = BastionSystem::new();
+    first.start();
+    second.start();
+
+    let (first_sender, first_recver) = mpsc::channel();
+    let (second_sender, second_recver) = mpsc::channel();
+    worker(&first, "first", first_sender);
+    worker(&second, "second", second_sender);
+
+    let timeout = Duration::from_secs(1);
+    assert_eq!(first_recver.recv_timeout(timeout), Ok("first"));
+    assert_eq!(second_recver.recv_timeout(timeout), Ok("second"));
+
+    // Names and topics are only shared within a system.
+    let first_worker = first.whereis("worker").expect("Worker not found.");
+    let second_worker = second.whereis("worker").expect("Worker not found.");
+    assert_ne!(first_worker.id(), second_worker.id());
+    assert!(Bastion::whereis("worker").is_none());
+
+    first.publish("jobs", "first job").unwrap();
+    assert_eq!(first_recver.recv_timeout(timeout), Ok("first job"));
+    assert!(second_recver.recv_timeout(timeout / 10).is_err());
+
+    // Stopping a system leaves the other one running.
+    first.stop();
+    first.block_until_stopped();
+    assert!(first.is_stopped());
+    assert!(!second.is_stopped());
+
+    second_worker.tell_anonymously("second job").unwrap();
+    assert_eq!(second_recver.recv_timeout(timeout), Ok("second job"));
+
+    second.stop();
+    second.block_until_stopped();
+}
@@ -0,0 +1,57 @@
+use bastion::prelude::*;
+use futures::executor;
+
+#[test]
+fn terminated() {
+    Bastion::init();
+    Bastion::start();
+
+    let sp_ref = Bastion::supervisor(|sp| sp).expect("Couldn't create the supervisor.");
+    let children_ref = sp_ref
+        .children(|children| {
+            children.with_exec(|ctx: BastionContext| async move {
+                loop {
+                    msg! { ctx.recv().await?,
+                        msg: &'static str => {
+                            if msg == "fault" {
+                                return Err(());
+                            }
+                        };
+                        _: _ => ();
+                    }
+                }
+            })
+        })
+        .expect("Couldn't create the children group.");
+
+    // A faulted child gets restarted...
+    let child_ref = children_ref.elems()[0].clone();
+    assert!(child_ref.is_alive());
+    let terminated = child_ref.terminated();
+    let group_terminated = children_ref.terminated();
+    child_ref.tell_anonymously("fault").unwrap();
+    assert_eq!(executor::block_on(terminated), ExitReason::Faulted);
+    assert!(!child_ref.is_alive());
+    assert_eq!(
+        executor::block_on(child_ref.terminated()),
+        ExitReason::Unknown
+    );
+
+    // ...along with its children group, which can be killed...
+    assert_eq!(executor::block_on(group_terminated), ExitReason::Faulted);
+    assert!(!children_ref.is_alive());
+    let children_ref = Bastion::children(|children| children).unwrap();
+    let terminated = children_ref.terminated();
+    children_ref.kill().unwrap();
+    assert_eq!(executor::block_on(terminated), ExitReason::Killed);
+
+    // ...and supervisors can be stopped.
+    assert!(sp_ref.is_alive());
+    let terminated = sp_ref.terminated();
+    sp_ref.stop().unwrap();
+    assert_eq!(executor::block_on(terminated), ExitReason::Stopped);
+    assert!(!sp_ref.is_alive());
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}
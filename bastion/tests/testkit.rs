@@ -0,0 +1,66 @@
+use bastion::prelude::*;
+use bastion::testkit::{self, LifecycleEvent, LifecycleProbe, TestProbe};
+use std::time::Duration;
+
+#[test]
+fn probes() {
+    Bastion::init();
+    Bastion::start();
+
+    let timeout = Duration::from_secs(1);
+    let probe = TestProbe::new().expect("Couldn't create the probe.");
+    let lifecycle = LifecycleProbe::new();
+    let escalations = LifecycleProbe::new();
+
+    // The workers tell the probe when they start and forward it
+    // the messages they receive.
+    let probe_ref = probe.child().clone();
+    let workers = Bastion::children(|children| {
+        children
+            .with_callbacks(lifecycle.callbacks())
+            .with_exec(move |ctx: BastionContext| {
+                let probe = probe_ref.addr();
+                async move {
+                    ctx.tell(&probe, "started").unwrap();
+
+                    loop {
+                        msg! { ctx.recv().await?,
+                            msg: &'static str => {
+                                ctx.tell(&probe, msg).unwrap();
+                            };
+                            _: _ => ();
+                        }
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    lifecycle.expect_event(LifecycleEvent::Started, timeout);
+    assert_eq!(probe.expect_msg::<&str>(timeout), "started");
+    escalations.watch(workers.terminated());
+
+    let worker = &workers.elems()[0];
+    worker.tell_anonymously("ping").unwrap();
+    assert_eq!(probe.expect_msg::<&str>(timeout), "ping");
+    probe.expect_no_msg(Duration::from_millis(50));
+
+    // The injected fault is escalated to the workers' supervisor,
+    // which restarts them.
+    testkit::inject_fault(worker).expect("Couldn't inject the fault.");
+    escalations.expect_event(LifecycleEvent::Escalated, timeout);
+    lifecycle.expect_event(LifecycleEvent::Restarted, timeout);
+    assert_eq!(probe.expect_msg::<&str>(timeout), "started");
+
+    probe
+        .children()
+        .broadcast("broadcasted")
+        .expect("Couldn't broadcast the message.");
+    assert_eq!(*probe.expect_broadcast::<&str>(timeout), "broadcasted");
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+    lifecycle.expect_event(LifecycleEvent::Stopped, timeout);
+    lifecycle.expect_no_event(Duration::from_millis(50));
+    escalations.expect_no_event(Duration::from_millis(50));
+}
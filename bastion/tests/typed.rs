@@ -0,0 +1,62 @@
+use bastion::prelude::*;
+use futures::executor::block_on;
+
+#[derive(Debug, Clone)]
+enum Counter {
+    Add(u64),
+    Get,
+}
+
+#[test]
+fn typed_children_only_receive_their_protocol() {
+    Bastion::init();
+    Bastion::start();
+
+    let children_ref: TypedChildrenRef<Counter> = Bastion::typed_children(|children| {
+        children
+            .with_redundancy(2)
+            .with_exec(|ctx: TypedContext<Counter>| async move {
+                let mut count = 0;
+                loop {
+                    match ctx.recv().await? {
+                        (Counter::Add(added), req) => {
+                            assert!(!req.is_ask());
+                            count += added;
+                        }
+                        (Counter::Get, req) => {
+                            assert!(req.is_ask());
+                            req.answer(count).unwrap();
+                        }
+                    }
+                }
+            })
+    })
+    .expect("Couldn't create the children group.");
+
+    assert_eq!(children_ref.elems().len(), 2);
+    assert_eq!(children_ref.untyped().elems().len(), 2);
+
+    let get = |child_ref: &TypedChildRef<Counter>| {
+        let answer = child_ref.ask_anonymously(Counter::Get).unwrap();
+        msg! { block_on(answer).unwrap(),
+            count: u64 => count;
+            _: _ => panic!("Unexpected answer.");
+        }
+    };
+
+    let first = &children_ref.elems()[0];
+    first.tell_anonymously(Counter::Add(2)).unwrap();
+    // Messages of another type sent using the untyped reference
+    // are dropped...
+    first.untyped().tell_anonymously(1u64).unwrap();
+    first.tell_anonymously(Counter::Add(3)).unwrap();
+    assert_eq!(get(first), 5);
+
+    // ...and "broadcasted" messages are received by every element.
+    children_ref.broadcast(Counter::Add(1)).unwrap();
+    assert_eq!(get(first), 6);
+    assert_eq!(get(&children_ref.elems()[1]), 1);
+
+    Bastion::stop();
+    Bastion::block_until_stopped();
+}